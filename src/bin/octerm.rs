@@ -1,405 +1,296 @@
-use std::io::Write;
+use std::{path::PathBuf, sync::Arc};
 
+use crossterm::style::Stylize;
 use octerm::{
-    error::Error,
-    github::{Notification, NotificationTarget},
-    line_editor,
-    parser::types::{
-        Adapter, Command, Consumer, ConsumerWithArgs, Parsed, Producer, ProducerExpr,
-        ProducerWithArgs,
+    client::NotificationClient,
+    error::{exit_code, Error},
+    exec::{
+        filter, print_error, repl, run_script, set_scope, sync, sync_retrying, Failure, Session,
+    },
+    network::{
+        backend::{GithubBackend, MockBackend},
+        fixture::{Fixture, Recorder},
     },
+    parser::types::Producer,
+    util::ColorChoice,
 };
-use reedline::Signal;
-
-use crossterm::style::Stylize;
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let token = std::env::var("GITHUB_TOKEN").map_err(|_| Error::Authentication)?;
-
-    // Initialise a statically counted instance
-    let builder = octocrab::Octocrab::builder().personal_token(token);
-    octocrab::initialise(builder)?;
+const USAGE: &str = "Usage: octerm [--repo <owner/name>]... [--only <filter>]... [--json] \
+                     [--color=always|never|auto] [--fixture <file> | --record <file>] \
+                     [--dump-graphql <dir>] \
+                     [-c <pipeline> | run <script.oct> | completions <shell> | \
+                     state export|import <file>]";
+
+const EXIT_CODES: &str = "Exit codes of -c and run:
+  1  failure
+  2  invalid arguments or pipeline
+  3  authentication failed
+  4  rate limit exceeded
+  5  could not reach GitHub
+  6  the last list matched no notifications";
+
+/// How octerm was asked to run, from the command line arguments.
+enum Mode {
+    Interactive,
+    /// Run a single pipeline given with `-c` and exit.
+    Command(String),
+    /// Run every line of a script with `run` and exit.
+    Script(PathBuf),
+}
 
-    println!("Syncing notifications");
-    // TODO: Retry in case of bad connection, better error handling, etc.
-    let mut notifications = octerm::network::methods::notifications(octocrab::instance()).await?;
-    let mut line_editor = line_editor::line_editor();
+struct Args {
+    mode: Mode,
+    /// Print the results of bare `list` pipelines as JSON lines.
+    json: bool,
+    /// Filters given with `--repo` and `--only`, in the syntax of `list`.
+    filters: Vec<String>,
+    color: ColorChoice,
+    /// Replay the notifications and timelines saved in a fixture instead
+    /// of calling GitHub.
+    fixture: Option<PathBuf>,
+    /// Save the notifications and timelines fetched from GitHub to a
+    /// fixture.
+    record: Option<PathBuf>,
+    /// Write every GraphQL query and response to this directory.
+    dump_graphql: Option<PathBuf>,
+}
 
-    loop {
-        let sig = line_editor.read_line(&line_editor::prompt(notifications.len()));
-        match sig {
-            Ok(Signal::CtrlD) | Ok(Signal::CtrlC) => {
-                println!("Exiting.");
-                break;
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut parsed = Args {
+        mode: Mode::Interactive,
+        json: false,
+        filters: Vec::new(),
+        color: ColorChoice::Auto,
+        fixture: None,
+        record: None,
+        dump_graphql: None,
+    };
+    while let Some(arg) = args.next() {
+        // `--color` also takes its value after an `=`
+        let arg = match arg.strip_prefix("--color=") {
+            Some(choice) => {
+                parsed.color = color_choice(choice)?;
+                continue;
             }
-            Ok(Signal::Success(cmdline)) => match octerm::parser::parse(cmdline.trim()) {
-                Ok((rem_input, parsed)) => {
-                    if !rem_input.is_empty() {
-                        print_error(&format!("Invalid expression tail: `{rem_input}`"));
-                        continue;
-                    }
-                    if let Err(err) = run(parsed, &mut notifications).await {
-                        print_error(&err);
-                    }
+            None => arg,
+        };
+        match arg.as_str() {
+            "--json" => parsed.json = true,
+            "--color" => parsed.color = color_choice(&args.next().unwrap_or_default())?,
+            "--repo" => match args.next() {
+                Some(repo) => parsed.filters.push(format!("repo:{repo}")),
+                None => return Err("--repo requires a repository".to_string()),
+            },
+            "--only" => match args.next() {
+                Some(only) if Producer::list_filters().contains(&only.as_str()) => {
+                    parsed.filters.push(only)
                 }
-                Err(_) => {
-                    print_error("Invalid expression");
-                    continue;
+                Some(only) => {
+                    return Err(format!(
+                        "Invalid filter `{only}` for --only, expected one of {}",
+                        Producer::list_filters().join(", ")
+                    ))
                 }
+                None => return Err("--only requires a filter".to_string()),
+            },
+            "--fixture" => match args.next() {
+                Some(path) => parsed.fixture = Some(path.into()),
+                None => return Err("--fixture requires a file".to_string()),
+            },
+            "--record" => match args.next() {
+                Some(path) => parsed.record = Some(path.into()),
+                None => return Err("--record requires a file".to_string()),
             },
-            Err(err) => print_error(&err.to_string()),
+            "--dump-graphql" => match args.next() {
+                Some(dir) => parsed.dump_graphql = Some(dir.into()),
+                None => return Err("--dump-graphql requires a directory".to_string()),
+            },
+            "-c" => match args.next() {
+                Some(pipeline) => parsed.mode = Mode::Command(pipeline),
+                None => return Err("-c requires a pipeline".to_string()),
+            },
+            "run" => match args.next() {
+                Some(script) => parsed.mode = Mode::Script(script.into()),
+                None => return Err("run requires a script".to_string()),
+            },
+            "completions" => {
+                let shell = args.next().unwrap_or_default();
+                match octerm::shell_completion::script(&shell) {
+                    Some(script) => print!("{script}"),
+                    None => {
+                        return Err(format!(
+                            "completions requires a shell: {}",
+                            octerm::shell_completion::SHELLS.join(", ")
+                        ))
+                    }
+                }
+                std::process::exit(0);
+            }
+            "state" => {
+                let (action, path) = match (args.next(), args.next()) {
+                    (Some(action), Some(path)) => (action, PathBuf::from(path)),
+                    _ => return Err("state requires export or import and a file".to_string()),
+                };
+                let (result, done) = match action.as_str() {
+                    "export" => (octerm::state::export(&path), "Exported mutes and drafts to"),
+                    "import" => (
+                        octerm::state::import(&path),
+                        "Imported mutes and drafts from",
+                    ),
+                    _ => {
+                        return Err(format!(
+                            "Invalid state action `{action}`, expected export or import"
+                        ))
+                    }
+                };
+                if let Err(err) = result {
+                    fail(Failure {
+                        message: format!("{}: {}", path.display(), err.report()),
+                        ..err.into()
+                    });
+                }
+                println!("{done} {}", path.display());
+                std::process::exit(0);
+            }
+            "-h" | "--help" => {
+                println!("{USAGE}\n\n{EXIT_CODES}");
+                std::process::exit(0);
+            }
+            _ => return Err(format!("Unknown argument `{arg}`\n{USAGE}")),
         }
     }
-    Ok(())
-}
-
-type ExecResult = Result<(), String>;
-
-async fn run(parsed: Parsed, notifications: &mut Vec<Notification>) -> ExecResult {
-    match parsed {
-        Parsed::Command(cmd) => run_command(cmd, notifications).await?,
-        Parsed::ProducerExpr(pexpr) => run_producer_expr(pexpr, notifications).await?,
-        Parsed::ConsumerWithArgs(cons) => run_consumer(cons, notifications).await?,
-    };
-    Ok(())
+    if parsed.json && matches!(parsed.mode, Mode::Interactive) {
+        return Err(format!("--json needs -c or run\n{USAGE}"));
+    }
+    if parsed.fixture.is_some() && parsed.record.is_some() {
+        return Err("--fixture and --record can't be used together".to_string());
+    }
+    // Check the values of the filters before anything is fetched
+    filter(&[], &parsed.filters)?;
+    Ok(parsed)
 }
 
-async fn run_command(cmd: Command, notifications: &mut Vec<Notification>) -> ExecResult {
-    match cmd {
-        Command::Reload => reload(notifications).await?,
-    };
-    Ok(())
+fn color_choice(choice: &str) -> Result<ColorChoice, String> {
+    ColorChoice::parse(choice)
+        .ok_or_else(|| format!("Invalid color `{choice}`, expected always, never or auto"))
 }
 
-async fn run_producer_expr(
-    pexpr: ProducerExpr,
-    notifications: &mut Vec<Notification>,
-) -> ExecResult {
-    let ProducerExpr {
-        producer:
-            ProducerWithArgs {
-                producer,
-                args: producer_args,
-            },
-        adapters,
-        consumer,
-    } = pexpr;
-
-    let mut indices = match producer {
-        Producer::List => list(notifications, producer_args).await?,
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    octerm::util::set_color_choice(ColorChoice::Auto);
+    let mut args =
+        parse_args(std::env::args().skip(1)).unwrap_or_else(|err| fail(Failure::usage(err)));
+    octerm::util::set_color_choice(args.color);
+    let config = octerm::config::Config::load().unwrap_or_else(|err| fail(err.into()));
+    octerm::config::initialise(config);
+    let token = match std::env::var("GITHUB_TOKEN") {
+        Ok(token) => Some(token),
+        Err(_) => octerm::config::get()
+            .auth
+            .token()
+            .unwrap_or_else(|err| fail(err.into())),
     };
-
-    for adapter in adapters {
-        indices = match adapter.adapter {
-            Adapter::Confirm => adapters::confirm(notifications, &indices).await?,
-        }
+    // Fixtures are replayed without a token
+    if token.is_none() && args.fixture.is_none() {
+        fail(Error::Authentication.into());
     }
 
-    match consumer {
-        None => print_notifications(notifications, &indices),
-        Some(consumer) => {
-            run_consumer(
-                ConsumerWithArgs {
-                    consumer,
-                    args: indices,
-                },
-                notifications,
-            )
-            .await?
-        }
-    };
-
-    Ok(())
-}
-
-async fn run_consumer(cons: ConsumerWithArgs, notifications: &mut Vec<Notification>) -> ExecResult {
-    let ConsumerWithArgs {
-        consumer: cons,
-        args,
-    } = cons;
-
-    // TODO: Decide behaviour on empty args
-    match cons {
-        Consumer::Count => consumers::count(notifications, &args).await?,
-        Consumer::Open => consumers::open(notifications, &args).await?,
-        Consumer::Done => {
-            consumers::done(notifications, &args).await?;
-            // Print the list again since done will change the indices
-            // let indices = list(notifications, Vec::new()).await?;
-            // print_notifications(notifications, &indices);
-        }
-    };
-
-    Ok(())
-}
-
-pub async fn list(notifications: &[Notification], args: Vec<String>) -> Result<Vec<usize>, String> {
-    // TODO: Robust parsing (invalid tokens, etc)
-
-    let has_arg = |arg| args.iter().any(|a| *a == arg);
-    let is_pr = has_arg("pr");
-    let is_issue = has_arg("issue");
-    let is_closed = has_arg("closed");
-    let is_open = has_arg("open");
-    let is_merged = has_arg("merged");
-    let is_release = has_arg("release");
-    let is_discussion = has_arg("discussion");
-
-    if true_count(&[is_pr, is_issue, is_release, is_discussion]) > 1 {
-        return Err("pr, issue, discussion, release are mutually exclusive".to_string());
+    if let Some(dir) = args.dump_graphql {
+        octerm::network::graphql::enable_dumps(dir, token.clone())?;
     }
 
-    if true_count(&[is_open, is_closed, is_merged]) > 1 {
-        return Err("pr, issue, merged are mutually exclusive".to_string());
+    // Initialise a statically counted instance
+    if let Some(token) = token {
+        let builder = octocrab::Octocrab::builder().personal_token(token);
+        octocrab::initialise(builder)?;
     }
 
-    let filter_by_type = |n: &Notification| -> bool {
-        if is_pr {
-            matches!(n.target, NotificationTarget::PullRequest(_))
-        } else if is_issue {
-            matches!(n.target, NotificationTarget::Issue(_))
-        } else if is_release {
-            matches!(n.target, NotificationTarget::Release(_))
-        } else if is_discussion {
-            matches!(n.target, NotificationTarget::Discussion(_))
-        } else {
-            true
+    // The scope from the config is only used without --repo and --only
+    if args.filters.is_empty() {
+        args.filters
+            .extend(octerm::config::get().fetch.scope_filter());
+        filter(&[], &args.filters).unwrap_or_else(|err| {
+            fail(Failure::usage(format!(
+                "Invalid fetch.scope in config: {err}"
+            )))
+        });
+    }
+    set_scope(args.filters.clone());
+    if matches!(args.mode, Mode::Interactive) {
+        match args.filters.is_empty() {
+            true => println!("Syncing notifications"),
+            false => println!(
+                "Syncing notifications matching `{}`",
+                args.filters.join(" ")
+            ),
         }
-    };
-
-    let filter_by_state = |n: &Notification| -> bool {
-        if is_open {
-            match n.target {
-                NotificationTarget::Issue(ref issue) => issue.state.is_open(),
-                NotificationTarget::PullRequest(ref pr) => pr.state.is_open(),
-                _ => false,
-            }
-        } else if is_closed {
-            match n.target {
-                NotificationTarget::Issue(ref issue) => issue.state.is_closed(),
-                NotificationTarget::PullRequest(ref pr) => pr.state.is_closed(),
-                _ => false,
-            }
-        } else if is_merged {
-            match n.target {
-                NotificationTarget::PullRequest(ref pr) => pr.state.is_merged(),
-                _ => false,
-            }
-        } else {
-            true
+    }
+    let client = Arc::new(NotificationClient::new(octocrab::instance()));
+    let backend: Arc<dyn GithubBackend> = match (args.fixture, args.record) {
+        (Some(path), _) => {
+            let fixture = Fixture::load(&path).unwrap_or_else(|err| {
+                fail(Failure {
+                    message: format!("{}: {err}", path.display()),
+                    ..err.into()
+                })
+            });
+            Arc::new(MockBackend::from(fixture))
         }
+        (None, Some(path)) => Arc::new(Recorder::new(client, path)),
+        (None, None) => client,
     };
-
-    let notification_indices = notifications
-        .iter()
-        .enumerate()
-        .filter(|(_, n)| filter_by_type(n))
-        .filter(|(_, n)| filter_by_state(n))
-        .map(|(i, _)| i)
-        .collect();
-
-    Ok(notification_indices)
-}
-
-pub async fn reload(notifications: &mut Vec<Notification>) -> Result<(), String> {
-    println!("Syncing notifications");
-    *notifications = octerm::network::methods::notifications(octocrab::instance())
-        .await
-        .map_err(|err| err.to_string())?;
-
-    Ok(())
-}
-
-pub mod adapters {
-    use octerm::github::Notification;
-
-    use crate::{flush_stdout, format_colored_notification, read_char};
-
-    pub async fn confirm(
-        notifications: &[Notification],
-        filter: &[usize],
-    ) -> Result<Vec<usize>, String> {
-        crossterm::terminal::enable_raw_mode().map_err(|_| "Could not enable terminal raw mode")?;
-
-        let result = confirm_helper(notifications, filter);
-
-        // TODO: Register panic handler to always disable raw mode
-        crossterm::terminal::disable_raw_mode()
-            .map_err(|_| "Could not disable terminal raw mode")?;
-
-        if result.is_err() {
-            // Reset cursor to beginning of line
-            println!("\r");
+    let mut session = Session {
+        // TODO: Retry in case of bad connection, better error handling, etc.
+        notifications: match args.mode {
+            // Scripts fail fast instead of waiting out the rate limit
+            Mode::Interactive => sync_retrying(&*backend).await,
+            _ => sync(&*backend).await,
         }
+        .unwrap_or_else(|err| fail(err.into())),
+        last_result: None,
+        json: args.json,
+        backend,
+    };
 
-        result
-    }
-
-    fn confirm_helper(
-        notifications: &[Notification],
-        filter: &[usize],
-    ) -> Result<Vec<usize>, String> {
-        let mut it = filter.iter().map(|i| (*i, &notifications[*i]));
-        let mut next_notification = it.next();
-
-        if next_notification.is_some() {
-            println!("[y]es, [n]o, [a]ll, [d]one, [Q]uit/abort\r");
-        } else {
-            return Ok(Vec::new());
-        }
-
-        let mut indices = Vec::new();
-        while let Some((i, notification)) = next_notification {
-            print!("{}: ", format_colored_notification(i, notification));
-            flush_stdout()?;
-            let mut is_valid_input = true;
-
-            // TODO: Add undo
-            // TODO: Add show rest
-            let input = read_char().map_err(|_| "Couldn't read input")?;
-            print!("{}", input);
-            flush_stdout()?;
-
-            // Keybindings have been modeled after git add -p
-            // TODO: Add additional confirmation keybind for d and a
-            // (cannot undo if pressed by accident)?
-            match input {
-                'y' => indices.push(i),
-                'n' => {}
-                // Skip this notification and all the remaining ones
-                'd' => break,
-                // Confirm current notification and all the remaining ones
-                'a' => {
-                    indices.push(i);
-                    while let Some((i, _)) = it.next() {
-                        indices.push(i);
-                    }
-                    break;
-                }
-                'Q' => return Err("Aborted confirm queue".to_string()),
-                _invalid_input => {
-                    print!(" (invalid option)");
-                    is_valid_input = false;
-                }
-            }
-
-            // Reset cursor to beginning of line
-            println!("\r");
-
-            if is_valid_input {
-                next_notification = it.next();
+    match args.mode {
+        Mode::Command(pipeline) => {
+            if let Err(failure) = session.execute(&pipeline).await {
+                fail(failure);
             }
         }
-
-        Ok(indices)
-    }
-}
-
-pub mod consumers {
-    use futures::TryFutureExt;
-    use octerm::{
-        error::Error,
-        github::Notification,
-        network::methods::{mark_notification_as_read, open_notification_in_browser},
-    };
-
-    pub async fn count(
-        _notifications: &mut [Notification],
-        filter: &[usize],
-    ) -> Result<(), String> {
-        println!("{}", filter.len());
-        Ok(())
-    }
-
-    pub async fn open(notifications: &mut [Notification], filter: &[usize]) -> Result<(), String> {
-        let futs = filter
-            .iter()
-            .map(|i| &notifications[*i])
-            .map(open_notification_in_browser);
-        futures::future::join_all(futs)
-            .await
-            .into_iter()
-            .collect::<Result<Vec<()>, Error>>()
-            .map_err(|err| format!("Could not open browser: {err}"))?;
-
-        Ok(())
-    }
-
-    pub async fn done(
-        notifications: &mut Vec<Notification>,
-        filter: &[usize],
-    ) -> Result<(), String> {
-        let octo = octocrab::instance();
-        let futs = filter
-            .iter()
-            .map(|i| (i, &notifications[*i]))
-            .map(|(i, notification)| {
-                mark_notification_as_read(&octo, notification.inner.id).map_ok(|_| *i)
+        Mode::Script(path) => {
+            let script = std::fs::read_to_string(&path).unwrap_or_else(|err| {
+                fail(format!("Could not read {}: {err}", path.display()).into())
             });
-        let marked = futures::future::join_all(futs).await;
-        let has_error = marked.iter().any(|m| m.is_err());
-        let mut marked: Vec<usize> = marked.into_iter().filter_map(|m| m.ok()).collect();
-        marked.sort();
-
-        for idx in marked.iter().rev() {
-            // Remove from the end so that indices stay stable as items are removed.
-            notifications.remove(*idx);
-        }
-
-        if has_error {
-            return Err("Some notifications could not be marked as read".to_string());
+            if let Err(failure) = run_script(&mut session, &script).await {
+                fail(Failure {
+                    message: format!("{}:{}", path.display(), failure.message),
+                    ..failure
+                });
+            }
         }
-
-        Ok(())
-    }
-}
-
-fn read_char() -> crossterm::Result<char> {
-    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
-
-    loop {
-        if let Event::Key(event) = crossterm::event::read()? {
-            let KeyEvent {
-                code,
-                mut modifiers,
-            } = event;
-            if let KeyCode::Char(mut ch) = code {
-                if modifiers.contains(KeyModifiers::SHIFT) {
-                    modifiers.remove(KeyModifiers::SHIFT);
-                    ch.make_ascii_uppercase();
-                }
-                if modifiers.is_empty() {
-                    return Ok(ch);
+        Mode::Interactive => {
+            if let Some(path) = octerm::config::Config::dir().map(|d| d.join("init.oct")) {
+                if let Ok(script) = std::fs::read_to_string(&path) {
+                    if let Err(failure) = run_script(&mut session, &script).await {
+                        print_error(&format!("init.oct:{}", failure.message));
+                    }
                 }
             }
+            repl(&mut session).await;
+            return Ok(());
         }
     }
-}
-
-fn print_notifications(notifications: &[Notification], indices: &[usize]) {
-    for i in indices {
-        match notifications.get(*i) {
-            Some(n) => println!("{}", format_colored_notification(*i, n)),
-            None => print_error("Invalid notifications list index"),
-        }
+    // Only the last `list` of a script decides whether it matched anything
+    if session.last_result.as_deref() == Some(&[]) {
+        std::process::exit(exit_code::NO_MATCHES);
     }
+    Ok(())
 }
 
-fn format_colored_notification(index: usize, notification: &Notification) -> String {
-    format!("{index:2}. {}", notification.to_colored_string())
-}
-
-fn print_error(msg: &str) {
-    println!("{}: {msg}", "Error".red())
-}
-
-fn true_count(bools: &[bool]) -> usize {
-    bools.iter().map(|b| *b as usize).sum()
-}
-
-fn flush_stdout() -> Result<(), String> {
-    std::io::stdout()
-        .flush()
-        .map_err(|_| "Could not flush stdout".to_string())
+/// Report an error from non-interactive mode and exit with its code.
+fn fail(failure: Failure) -> ! {
+    let msg = format!("{}: {}", "Error".red(), failure.message);
+    eprintln!("{}", octerm::util::maybe_strip_ansi(msg));
+    std::process::exit(failure.code)
 }
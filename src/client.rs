@@ -0,0 +1,112 @@
+//! A high level client for embedding octerm's GitHub inbox in other tools.
+//!
+//! ```no_run
+//! # async fn run() -> octerm::error::Result<()> {
+//! let client = octerm::client::NotificationClient::with_token("ghp_...".into())?;
+//! for notification in client.fetch_all().await? {
+//!     if let Some(events) = client.timeline(&notification).await? {
+//!         println!("{}: {} events", notification.inner.subject.title, events.len());
+//!     }
+//!     client.mark_done(&notification).await?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::sync::Arc;
+
+use octocrab::Octocrab;
+
+use crate::{
+    error::Result,
+    github::{events::Event, Discussion, Notification, NotificationTarget},
+    network::methods,
+};
+
+/// Fetches notifications along with the issues, pull requests and other
+/// targets they are about, and acts on them.
+#[derive(Clone)]
+pub struct NotificationClient {
+    octo: Arc<Octocrab>,
+}
+
+impl NotificationClient {
+    pub fn new(octo: Arc<Octocrab>) -> Self {
+        Self { octo }
+    }
+
+    /// A client authenticated with a personal access token.
+    pub fn with_token(token: String) -> Result<Self> {
+        let octo = Octocrab::builder().personal_token(token).build()?;
+        Ok(Self::new(Arc::new(octo)))
+    }
+
+    pub fn octocrab(&self) -> &Arc<Octocrab> {
+        &self.octo
+    }
+
+    /// All notifications with their targets, newest first.
+    pub async fn fetch_all(&self) -> Result<Vec<Notification>> {
+        methods::notifications(Arc::clone(&self.octo)).await
+    }
+
+    /// All notifications without their targets, which can be fetched
+    /// later with [`Self::details`].
+    pub async fn fetch_threads(&self) -> Result<Vec<Notification>> {
+        methods::notification_threads(Arc::clone(&self.octo)).await
+    }
+
+    /// Fetch the target of a notification from [`Self::fetch_threads`].
+    /// Notifications that already have one are returned as is.
+    pub async fn details(&self, notification: Notification) -> Result<Notification> {
+        match notification.target {
+            NotificationTarget::Pending => {
+                methods::octo_notif_to_notif(Arc::clone(&self.octo), notification.inner).await
+            }
+            _ => Ok(notification),
+        }
+    }
+
+    /// Mark the thread of a notification as read, which removes it from
+    /// the inbox.
+    pub async fn mark_done(&self, notification: &Notification) -> Result<()> {
+        methods::mark_notification_as_read(&self.octo, notification.inner.id).await
+    }
+
+    /// Stop receiving notifications for the thread of a notification.
+    pub async fn unsubscribe(&self, notification: &Notification) -> Result<()> {
+        methods::unsubscribe_from_thread(&self.octo, notification.inner.id).await
+    }
+
+    /// Comments, reviews and other events of an issue or pull request.
+    /// Other targets have no timeline.
+    pub async fn timeline(&self, notification: &Notification) -> Result<Option<Vec<Event>>> {
+        match notification.target {
+            NotificationTarget::Issue(ref issue) => {
+                let repo = &issue.repo;
+                methods::issue_timeline(&self.octo, &repo.owner, &repo.name, issue.number).await
+            }
+            NotificationTarget::PullRequest(ref pr) => {
+                let repo = &pr.repo;
+                methods::pr_timeline(&self.octo, &repo.owner, &repo.name, pr.number).await
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// The comments and answers of a discussion. Other targets have no
+    /// discussion.
+    pub async fn discussion(&self, notification: &Notification) -> Result<Option<Discussion>> {
+        match notification.target {
+            NotificationTarget::Discussion(ref meta) => {
+                methods::discussion(&self.octo, meta.clone()).await
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// The page of the notification target on github.com.
+    pub async fn html_url(&self, notification: &Notification) -> Result<String> {
+        methods::resolve_html_url(&self.octo, notification).await
+    }
+}
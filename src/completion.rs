@@ -1,31 +1,267 @@
-use reedline::{Completer, DefaultCompleter};
+use std::sync::{Arc, Mutex};
 
-use crate::parser::types::{Adapter, Command, Consumer, Producer};
+use reedline::{Completer, Span, Suggestion};
 
-pub fn completer() -> impl Completer {
-    let completions = Command::all()
-        .iter()
-        .chain(&Producer::all())
-        .chain(&Adapter::all())
-        .chain(&Consumer::all())
-        .map(ToString::to_string)
-        .collect();
-    let mut completer = DefaultCompleter::default().set_min_word_len(0);
-    // Calling set_min_word_len after DefaultCompleter::new(completions)
-    // has no effect since minimum length has to be set first.
-    completer.insert(completions);
-    completer
+use crate::{
+    github::Notification,
+    help,
+    parser::types::{Adapter, Command, Consumer, Producer},
+};
+
+pub fn completer(data: SharedCompletionData) -> impl Completer {
+    ReplCompleter { data }
+}
+
+/// Details of the loaded notifications that are offered as completions.
+#[derive(Debug, Default)]
+pub struct CompletionData {
+    /// Full names of the repositories with notifications, without duplicates.
+    pub repos: Vec<String>,
+    /// Title of the notification at each index.
+    pub titles: Vec<String>,
+}
+
+/// Completion data shared between the REPL, which updates it after every
+/// command, and the completer.
+pub type SharedCompletionData = Arc<Mutex<CompletionData>>;
+
+impl CompletionData {
+    pub fn new(notifications: &[Notification]) -> Self {
+        let mut repos: Vec<String> = notifications
+            .iter()
+            .map(|n| {
+                let repo = &n.inner.repository;
+                repo.full_name.clone().unwrap_or_else(|| repo.name.clone())
+            })
+            .collect();
+        repos.sort_unstable();
+        repos.dedup();
+        let titles = notifications
+            .iter()
+            .map(|n| n.inner.subject.title.clone())
+            .collect();
+        Self { repos, titles }
+    }
 }
 
-struct ReplCompleter;
+/// Options and fixed arguments accepted after a name.
+pub(crate) fn options(name: &str) -> &'static [&'static str] {
+    match name {
+        "repo" => &["issues", "prs", "open", "closed", "merged"],
+        "sort" => &["updated", "repo", "relevance"],
+        "count" => &["repo", "type", "reason"],
+        "mute" => &["thread", "repo"],
+        "browse" => &["issues", "pulls", "releases", "actions"],
+        "export" => &["csv"],
+        "assets" => &["copy", "download"],
+        "show" | "view" => &["all", "conversation", "status", "expand"],
+        _ => &[],
+    }
+}
+
+/// Completes names and arguments depending on their position in the
+/// pipeline: producers, commands and consumers at the start, adapters and
+/// consumers after a pipe and the arguments of the name before the cursor
+/// anywhere else. Repository names and indices come from the loaded
+/// notifications.
+struct ReplCompleter {
+    data: SharedCompletionData,
+}
+
+impl ReplCompleter {
+    /// Indices starting with `word`, described by the notification title.
+    fn indices(&self, word: &str, span: Span) -> Vec<Suggestion> {
+        let data = self.data.lock().unwrap();
+        data.titles
+            .iter()
+            .enumerate()
+            .map(|(i, title)| (i.to_string(), title))
+            .filter(|(i, _)| i.starts_with(word))
+            .map(|(value, title)| Suggestion {
+                value,
+                description: Some(title.clone()),
+                extra: None,
+                span,
+                append_whitespace: true,
+            })
+            .collect()
+    }
+
+    /// Repository names starting with `word` after `prefix`, as in
+    /// `repo:helix-editor/helix`.
+    fn repos(&self, prefix: &str, word: &str, span: Span) -> Vec<Suggestion> {
+        let data = self.data.lock().unwrap();
+        data.repos
+            .iter()
+            .map(|repo| format!("{prefix}{repo}"))
+            .filter(|value| value.starts_with(word))
+            .map(|value| Suggestion {
+                value,
+                description: None,
+                extra: None,
+                span,
+                append_whitespace: true,
+            })
+            .collect()
+    }
+}
 
 impl Completer for ReplCompleter {
-    fn complete(&mut self, line: &str, pos: usize) -> Vec<reedline::Suggestion> {
-        let suggestions = Vec::new();
+    fn complete(&mut self, line: &str, pos: usize) -> Vec<Suggestion> {
         let line = &line[..pos];
+        let start = line
+            .rfind(|ch: char| ch.is_whitespace() || ch == '|')
+            .map_or(0, |i| i + 1);
+        let word = &line[start..];
+        let span = Span::new(start, pos);
+
+        let (segment, piped) = match line[..start].rsplit_once('|') {
+            Some((_, segment)) => (segment, true),
+            None => (&line[..start], false),
+        };
+        let candidates: Vec<&str> = match segment.split_whitespace().next() {
+            None if piped => Adapter::all().into_iter().chain(Consumer::all()).collect(),
+            None => ["help", "new"]
+                .into_iter()
+                .chain(Command::all())
+                .chain(Producer::all())
+                .chain(Consumer::all())
+                .collect(),
+            Some("help") => ["help"]
+                .into_iter()
+                .chain(Command::all())
+                .chain(Producer::all())
+                .chain(Adapter::all())
+                .chain(Consumer::all())
+                .collect(),
+            Some("list") if !piped => {
+                let negated = word.starts_with('!');
+                if let Some(("", _)) = word.trim_start_matches('!').split_once("repo:") {
+                    let prefix = if negated { "!repo:" } else { "repo:" };
+                    return self.repos(prefix, word, span);
+                }
+                let filters = Producer::list_filters()
+                    .into_iter()
+                    .chain(Producer::list_qualifiers());
+                return filters
+                    .filter_map(|filter| {
+                        // Negated filters are completed after the `!`
+                        let value = match negated {
+                            true => format!("!{filter}"),
+                            false => filter.to_string(),
+                        };
+                        value.starts_with(word).then(|| Suggestion {
+                            append_whitespace: !filter.ends_with(':'),
+                            value,
+                            description: None,
+                            extra: None,
+                            span,
+                        })
+                    })
+                    .collect();
+            }
+            Some("new") if !piped && segment.split_whitespace().count() == 1 => {
+                return ["issue"]
+                    .into_iter()
+                    .filter(|name| name.starts_with(word))
+                    .map(|name| Suggestion {
+                        value: name.to_string(),
+                        description: None,
+                        extra: None,
+                        span,
+                        append_whitespace: true,
+                    })
+                    .collect();
+            }
+            Some("new") if !piped && segment.split_whitespace().count() == 2 => {
+                return self.repos("", word, span);
+            }
+            Some("repo") if !piped && segment.split_whitespace().count() == 1 => {
+                return self.repos("", word, span);
+            }
+            Some(name) if !piped && Consumer::all().contains(&name) => {
+                let mut suggestions = self.indices(word, span);
+                if word.starts_with(|ch: char| ch.is_ascii_digit()) {
+                    return suggestions;
+                }
+                let options = options(name).iter().filter(|opt| opt.starts_with(word));
+                let options = options.map(|opt| Suggestion {
+                    value: opt.to_string(),
+                    description: None,
+                    extra: None,
+                    span,
+                    append_whitespace: true,
+                });
+                suggestions.splice(0..0, options);
+                return suggestions;
+            }
+            Some(name) => options(name).to_vec(),
+        };
+
+        candidates
+            .into_iter()
+            .filter(|candidate| candidate.starts_with(word))
+            .map(|candidate| Suggestion {
+                value: candidate.to_string(),
+                description: help::usage(candidate)
+                    .filter(|_| segment.trim().is_empty())
+                    .map(|(_, description)| description.to_string()),
+                extra: None,
+                span,
+                append_whitespace: true,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn completer() -> ReplCompleter {
+        let data = CompletionData {
+            repos: vec!["helix-editor/helix".into(), "sudormrfbin/octerm".into()],
+            titles: (0..12).map(|i| format!("Issue {i}")).collect(),
+        };
+        ReplCompleter {
+            data: Arc::new(Mutex::new(data)),
+        }
+    }
+
+    fn complete(line: &str) -> Vec<String> {
+        completer()
+            .complete(line, line.len())
+            .into_iter()
+            .map(|s| s.value)
+            .collect()
+    }
+
+    #[test]
+    fn test_completion() {
+        assert_eq!(complete("rel"), ["reload", "releases"]);
+        assert_eq!(complete("li"), ["list", "links"]);
+        assert_eq!(complete("list | li"), ["limit", "links"]);
+        assert_eq!(complete("list|do"), ["done"]);
+        assert_eq!(complete("list pr c"), ["ci", "commit", "closed"]);
+        assert_eq!(complete("list !re"), ["!release", "!repo:", "!reason:"]);
+        assert_eq!(complete("list | sort u"), ["updated"]);
+        assert_eq!(complete("count r"), ["repo", "reason"]);
+        assert_eq!(complete("done 1"), ["1", "10", "11"]);
+        assert_eq!(complete("count repo 1"), ["1", "10", "11"]);
+        assert_eq!(complete("mute ").len(), 2 + 12);
+        assert_eq!(complete("list repo:s"), ["repo:sudormrfbin/octerm"]);
+        assert_eq!(complete("list pr !repo:h"), ["!repo:helix-editor/helix"]);
+        assert_eq!(complete("repo h"), ["helix-editor/helix"]);
+        assert!(complete("repo helix-editor/helix i").contains(&"issues".to_string()));
+        assert_eq!(complete("help gr"), ["grep"]);
+        assert!(complete("search is:o").is_empty());
 
-        if line.find('|').is_some() {}
+        let suggestion = &completer().complete("done 3", 6)[0];
+        assert_eq!(suggestion.description.as_deref(), Some("Issue 3"));
 
-        suggestions
+        let suggestion = &completer().complete("list la", 7)[0];
+        assert_eq!(suggestion.value, "label:");
+        assert_eq!(suggestion.span, Span::new(5, 7));
+        assert!(!suggestion.append_whitespace);
     }
 }
@@ -0,0 +1,506 @@
+//! User configuration, read from `$XDG_CONFIG_HOME/octerm/config.toml`.
+//! Every key is optional and falls back to its default value.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use chrono::format::{Item, StrftimeItems};
+use once_cell::sync::OnceCell;
+use serde::Deserialize;
+
+use crate::{
+    error::{Error, Result},
+    github::{NotificationRecord, PullRequestMeta, RepoMeta, User},
+};
+
+static CONFIG: OnceCell<Config> = OnceCell::new();
+
+/// Set the global config instance. Has no effect if the config has already
+/// been initialised.
+pub fn initialise(config: Config) {
+    let _ = CONFIG.set(config);
+}
+
+/// Get the global config instance, or the default config if it has not been
+/// initialised.
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub dates: DateConfig,
+    pub timeline: TimelineConfig,
+    pub list: ListConfig,
+    pub markdown: MarkdownConfig,
+    pub export: ExportConfig,
+    pub alerts: AlertConfig,
+    pub auth: AuthConfig,
+    pub fetch: FetchConfig,
+    pub checkouts: CheckoutConfig,
+}
+
+impl Config {
+    /// Directory containing all config files, usually `~/.config/octerm`.
+    pub fn dir() -> Option<PathBuf> {
+        dirs::config_dir().map(|d| d.join("octerm"))
+    }
+
+    /// Read the config file, returning the default config if it doesn't exist.
+    pub fn load() -> Result<Self> {
+        let path = match Self::dir() {
+            Some(dir) => dir.join("config.toml"),
+            None => return Ok(Self::default()),
+        };
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Self::parse(&contents),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(Error::ConfigRead(err)),
+        }
+    }
+
+    pub fn parse(contents: &str) -> Result<Self> {
+        let config: Self = toml::from_str(contents).map_err(Error::ConfigParse)?;
+        // chrono panics when asked to display a date with an invalid format
+        if !is_valid_strftime(&config.dates.format) || !is_valid_strftime(&config.dates.strftime())
+        {
+            return Err(Error::ConfigDateFormat(config.dates.format));
+        }
+        Ok(config)
+    }
+}
+
+fn is_valid_strftime(format: &str) -> bool {
+    StrftimeItems::new(format).all(|item| !matches!(item, Item::Error))
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DateConfig {
+    /// strftime style format used for absolute dates, checked by
+    /// [`Config::parse`].
+    pub format: String,
+    /// Show the time of day next to absolute dates.
+    pub show_time: bool,
+    pub clock: Clock,
+    pub timezone: Timezone,
+}
+
+impl Default for DateConfig {
+    fn default() -> Self {
+        Self {
+            format: "%d %b %Y".to_string(),
+            show_time: false,
+            clock: Clock::TwentyFourHour,
+            timezone: Timezone::Local,
+        }
+    }
+}
+
+impl DateConfig {
+    /// The complete strftime format for absolute dates, including the time
+    /// if enabled.
+    pub fn strftime(&self) -> String {
+        if !self.show_time {
+            return self.format.clone();
+        }
+        let time = match self.clock {
+            Clock::TwelveHour => "%I:%M %p",
+            Clock::TwentyFourHour => "%H:%M",
+        };
+        format!("{} {time}", self.format)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TimelineConfig {
+    /// Show comments by bots collapsed to a single line, unless they are
+    /// expanded with `show expand`.
+    pub collapse_bots: bool,
+    /// Logins of bots whose comments are never collapsed.
+    pub expand_bots: Vec<String>,
+}
+
+impl Default for TimelineConfig {
+    fn default() -> Self {
+        Self {
+            collapse_bots: true,
+            expand_bots: Vec::new(),
+        }
+    }
+}
+
+impl TimelineConfig {
+    /// Whether comments by the given user should be collapsed.
+    pub fn should_collapse(&self, author: &User) -> bool {
+        self.collapse_bots
+            && author.is_bot()
+            && !self.expand_bots.iter().any(|b| b == author.login())
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ListConfig {
+    /// Show PRs opened by dependabot and renovate as a single line.
+    pub group_dependency_updates: bool,
+    /// Sync only the list of notifications and fetch the issues, pull
+    /// requests and other targets they are about when a pipeline needs
+    /// them. Makes syncing large inboxes much faster.
+    pub lazy_details: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MarkdownConfig {
+    /// Display images inline with `show` in terminals that support the kitty
+    /// or iTerm2 protocol. Sixel is not supported. Images are never shown
+    /// when the output is not a terminal or colors are disabled.
+    pub images: bool,
+    /// Convert `:shortcode:`s to emoji. Can be disabled for fonts without
+    /// emoji.
+    pub emoji: bool,
+    /// Show the language of code blocks above them.
+    pub code_headers: bool,
+    /// Number the lines of fenced code blocks.
+    pub code_line_numbers: bool,
+}
+
+impl Default for MarkdownConfig {
+    fn default() -> Self {
+        Self {
+            images: false,
+            emoji: true,
+            code_headers: false,
+            code_line_numbers: false,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExportConfig {
+    /// Columns written by `export csv`, see
+    /// [`crate::github::NotificationRecord::COLUMNS`].
+    pub columns: Vec<String>,
+}
+
+impl Default for ExportConfig {
+    fn default() -> Self {
+        Self {
+            columns: NotificationRecord::COLUMNS.map(String::from).to_vec(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AlertConfig {
+    /// Minutes between checks for new notifications while the REPL waits
+    /// for input. 0 disables the checks.
+    pub interval: u64,
+}
+
+impl Default for AlertConfig {
+    fn default() -> Self {
+        Self { interval: 5 }
+    }
+}
+
+/// How notifications are fetched from GitHub.
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FetchConfig {
+    /// Start with only the notifications of threads the user is
+    /// participating in or mentioned in, see `participating` in the REPL.
+    pub participating: bool,
+    /// Only sync the notifications of a repository (`owner/name`), which
+    /// are fetched from their own endpoint, or of an owner (`owner`). The
+    /// `--repo` and `--only` arguments replace it.
+    pub scope: Option<String>,
+    /// Notifications in each page of the list, at most 100. Bigger pages
+    /// need fewer requests to sync large inboxes.
+    pub per_page: u8,
+    /// Stop after this many pages, leaving out the oldest notifications.
+    /// All pages are fetched if unset.
+    pub max_pages: Option<u32>,
+}
+
+impl Default for FetchConfig {
+    fn default() -> Self {
+        Self {
+            participating: false,
+            scope: None,
+            per_page: 50,
+            max_pages: None,
+        }
+    }
+}
+
+impl FetchConfig {
+    /// [`Self::per_page`] within the range accepted by GitHub.
+    pub fn page_size(&self) -> u8 {
+        self.per_page.clamp(1, 100)
+    }
+
+    /// [`Self::scope`] as a filter of `list`, like `repo:owner/name`.
+    pub fn scope_filter(&self) -> Option<String> {
+        let scope = self.scope.as_deref()?;
+        Some(match scope.contains('/') {
+            true => format!("repo:{scope}"),
+            false => format!("org:{scope}"),
+        })
+    }
+}
+
+/// Where to read the GitHub token from when `GITHUB_TOKEN` isn't set, so
+/// that it doesn't have to be kept in the environment.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthConfig {
+    /// Shell command printing the token, like `pass show github/pat`.
+    pub token_command: Option<String>,
+    /// File containing the token. A leading `~/` is the home directory.
+    pub token_file: Option<PathBuf>,
+}
+
+impl AuthConfig {
+    /// The token from `token_command`, or else from `token_file`. `None` if
+    /// neither is set.
+    pub fn token(&self) -> Result<Option<String>> {
+        let token = match (&self.token_command, &self.token_file) {
+            (Some(command), _) => {
+                let output = std::process::Command::new("sh")
+                    .args(["-c", command])
+                    .stderr(std::process::Stdio::inherit())
+                    .output()
+                    .map_err(|err| Error::TokenCommand(err.to_string()))?;
+                if !output.status.success() {
+                    return Err(Error::TokenCommand(format!(
+                        "`{command}` {}",
+                        output.status
+                    )));
+                }
+                String::from_utf8(output.stdout).map_err(|_| {
+                    Error::TokenCommand(format!("`{command}` printed invalid UTF-8"))
+                })?
+            }
+            (None, Some(path)) => {
+                std::fs::read_to_string(expand_home(path)).map_err(Error::TokenFile)?
+            }
+            (None, None) => return Ok(None),
+        };
+        match token.trim() {
+            "" => Err(Error::Authentication),
+            token => Ok(Some(token.to_string())),
+        }
+    }
+}
+
+/// Local clones of repositories, opened by `edit`.
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CheckoutConfig {
+    /// Command the checkout is opened with, like `code`. Defaults to
+    /// `$VISUAL` or `$EDITOR`.
+    pub editor: Option<String>,
+    /// Paths of the clones by repository, like
+    /// `"helix-editor/helix" = "~/src/helix"`.
+    pub repos: BTreeMap<String, PathBuf>,
+    /// Command copied by `checkout` to check out a pull request, with
+    /// `{owner}`, `{repo}`, `{number}` and `{branch}` replaced.
+    pub pr_command: String,
+}
+
+impl Default for CheckoutConfig {
+    fn default() -> Self {
+        Self {
+            editor: None,
+            repos: BTreeMap::new(),
+            pr_command: "gh pr checkout {number}".to_string(),
+        }
+    }
+}
+
+impl CheckoutConfig {
+    /// [`Self::pr_command`] for a pull request.
+    pub fn pr_command(&self, pr: &PullRequestMeta) -> String {
+        self.pr_command
+            .replace("{owner}", &pr.repo.owner)
+            .replace("{repo}", &pr.repo.name)
+            .replace("{number}", &pr.number.to_string())
+            .replace("{branch}", &pr.branch)
+    }
+
+    /// Where `repo` is cloned, if it is. Repository names on GitHub are
+    /// case insensitive.
+    pub fn path(&self, repo: &RepoMeta) -> Option<PathBuf> {
+        let full_name = format!("{}/{}", repo.owner, repo.name);
+        self.repos
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(&full_name))
+            .map(|(_, path)| expand_home(path))
+    }
+}
+
+/// Replace a leading `~/` in `path` with the home directory.
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+pub enum Clock {
+    #[serde(rename = "12h")]
+    TwelveHour,
+    #[serde(rename = "24h")]
+    TwentyFourHour,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Timezone {
+    Local,
+    Utc,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let config = Config::parse("").unwrap();
+        assert_eq!(config.dates.strftime(), "%d %b %Y");
+
+        let config = Config::parse(
+            r#"
+            [dates]
+            format = "%Y-%m-%d"
+            show_time = true
+            clock = "12h"
+            timezone = "utc"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.dates.strftime(), "%Y-%m-%d %I:%M %p");
+        assert_eq!(config.dates.timezone, Timezone::Utc);
+        assert!(Config::parse("[dates]\nformat = \"%Q\"").is_err());
+        assert!(Config::parse("[dates]\nformat = \"%Y %\"\nshow_time = true").is_err());
+
+        assert!(!config.list.group_dependency_updates);
+        assert!(!config.list.lazy_details);
+        assert!(!config.fetch.participating);
+        assert_eq!(config.fetch.page_size(), 50);
+        let fetch = |toml: &str| Config::parse(toml).unwrap().fetch;
+        assert_eq!(fetch("[fetch]\nper_page = 100").page_size(), 100);
+        assert_eq!(fetch("[fetch]\nper_page = 250").page_size(), 100);
+        assert_eq!(fetch("[fetch]\nmax_pages = 2").max_pages, Some(2));
+        let scope = |toml: &str| fetch(toml).scope_filter();
+        assert_eq!(scope(""), None);
+        assert_eq!(
+            scope("[fetch]\nscope = \"helix-editor/helix\""),
+            Some("repo:helix-editor/helix".to_string())
+        );
+        assert_eq!(
+            scope("[fetch]\nscope = \"helix-editor\""),
+            Some("org:helix-editor".to_string())
+        );
+        assert!(
+            Config::parse("[fetch]\nparticipating = true")
+                .unwrap()
+                .fetch
+                .participating
+        );
+        assert_eq!(config.alerts.interval, 5);
+        assert_eq!(
+            Config::parse("[alerts]\ninterval = 0")
+                .unwrap()
+                .alerts
+                .interval,
+            0
+        );
+        assert_eq!(
+            config.export.columns.len(),
+            NotificationRecord::COLUMNS.len()
+        );
+
+        let auth = |toml: &str| Config::parse(toml).unwrap().auth.token();
+        assert!(auth("").unwrap().is_none());
+        assert_eq!(
+            auth("[auth]\ntoken_command = \"echo ghp_abc\"").unwrap(),
+            Some("ghp_abc".to_string())
+        );
+        assert!(auth("[auth]\ntoken_command = \"exit 1\"").is_err());
+        assert!(auth("[auth]\ntoken_command = \"true\"").is_err());
+        assert!(auth("[auth]\ntoken_file = \"/nonexistent/token\"").is_err());
+
+        let checkouts = Config::parse(
+            "[checkouts]\neditor = \"code\"\nrepos = { \"helix-editor/helix\" = \"/src/helix\" }",
+        )
+        .unwrap()
+        .checkouts;
+        let repo = |owner: &str, name: &str| RepoMeta {
+            owner: owner.to_string(),
+            name: name.to_string(),
+        };
+        assert_eq!(checkouts.editor.as_deref(), Some("code"));
+        assert_eq!(
+            checkouts.path(&repo("Helix-Editor", "helix")),
+            Some(PathBuf::from("/src/helix"))
+        );
+        assert_eq!(checkouts.path(&repo("sudormrfbin", "octerm")), None);
+
+        let pr = PullRequestMeta {
+            repo: repo("helix-editor", "helix"),
+            title: String::new(),
+            body: String::new(),
+            number: 12,
+            author: User::default(),
+            state: crate::github::PullRequestState::Open,
+            created_at: Default::default(),
+            labels: Vec::new(),
+            branch: "fix-crash".to_string(),
+        };
+        assert_eq!(checkouts.pr_command(&pr), "gh pr checkout 12");
+        let checkouts = Config::parse(
+            "[checkouts]\npr_command = \"git fetch origin pull/{number}/head:{branch}\"",
+        )
+        .unwrap()
+        .checkouts;
+        assert_eq!(
+            checkouts.pr_command(&pr),
+            "git fetch origin pull/12/head:fix-crash"
+        );
+
+        assert!(Config::parse("[dates]\nclock = \"13h\"").is_err());
+        assert!(Config::parse("unknown = 1").is_err());
+    }
+
+    #[test]
+    fn test_collapse_bots() {
+        let bot = |name: &str| User {
+            name: name.to_string(),
+            bot: true,
+        };
+        let config = Config::parse("[timeline]\nexpand_bots = [\"github-actions\"]").unwrap();
+        assert!(config.timeline.should_collapse(&bot("codecov")));
+        assert!(config
+            .timeline
+            .should_collapse(&User::new("dependabot[bot]")));
+        assert!(!config.timeline.should_collapse(&bot("github-actions")));
+        assert!(!config
+            .timeline
+            .should_collapse(&User::new("github-actions[bot]")));
+        assert!(!config.timeline.should_collapse(&User::new("octocat")));
+
+        let config = Config::parse("[timeline]\ncollapse_bots = false").unwrap();
+        assert!(!config.timeline.should_collapse(&bot("codecov")));
+    }
+}
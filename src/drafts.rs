@@ -0,0 +1,101 @@
+//! Comments that could not be posted, stored in
+//! `$XDG_DATA_HOME/octerm/drafts.toml` so that they can be restored the
+//! next time a comment is written on the same thread.
+
+use std::{collections::BTreeMap, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{Error, Result},
+    github::RepoMeta,
+};
+
+#[derive(Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Drafts {
+    /// Draft text by thread, keyed like `helix-editor/helix#1234`.
+    pub drafts: BTreeMap<String, String>,
+}
+
+impl Drafts {
+    pub fn path() -> Option<PathBuf> {
+        dirs::data_dir().map(|d| d.join("octerm").join("drafts.toml"))
+    }
+
+    /// Read the saved drafts, returning no drafts if none have been saved.
+    pub fn load() -> Result<Self> {
+        let path = match Self::path() {
+            Some(path) => path,
+            None => return Ok(Self::default()),
+        };
+        match std::fs::read_to_string(path) {
+            Ok(contents) => toml::from_str(&contents).map_err(Error::StateParse),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(Error::StateRead(err)),
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path().ok_or_else(|| {
+            Error::StateWrite(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "no data directory",
+            ))
+        })?;
+        let contents = toml::to_string(self).map_err(|err| {
+            Error::StateWrite(std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+        })?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(Error::StateWrite)?;
+        }
+        std::fs::write(path, contents).map_err(Error::StateWrite)
+    }
+
+    pub fn key(repo: &RepoMeta, number: usize) -> String {
+        format!("{}/{}#{number}", repo.owner, repo.name)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.drafts.get(key).map(String::as_str)
+    }
+
+    /// Save `text` as the draft for a thread. Blank drafts are discarded.
+    pub fn set(&mut self, key: String, text: &str) {
+        match text.trim() {
+            "" => self.remove(&key),
+            _ => {
+                self.drafts.insert(key, text.to_string());
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        self.drafts.remove(key);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_drafts() {
+        let repo = RepoMeta {
+            name: "octerm".into(),
+            owner: "sudormrfbin".into(),
+        };
+        let key = Drafts::key(&repo, 12);
+        assert_eq!(key, "sudormrfbin/octerm#12");
+
+        let mut drafts = Drafts::default();
+        drafts.set(key.clone(), "LGTM\n");
+        assert_eq!(drafts.get(&key), Some("LGTM\n"));
+
+        let saved = toml::to_string(&drafts).unwrap();
+        assert_eq!(toml::from_str::<Drafts>(&saved).unwrap(), drafts);
+
+        drafts.set(key.clone(), " \n");
+        assert_eq!(drafts.get(&key), None);
+    }
+}
@@ -0,0 +1,232 @@
+//! Conversion of `:shortcode:` emoji used on GitHub to unicode.
+
+use std::borrow::Cow;
+
+/// Shortcodes and their emoji, sorted by shortcode for binary search. Only
+/// the commonly used subset of the emoji supported by GitHub is included.
+const EMOJI: &[(&str, &str)] = &[
+    ("+1", "👍"),
+    ("-1", "👎"),
+    ("100", "💯"),
+    ("adhesive_bandage", "🩹"),
+    ("airplane", "✈️"),
+    ("alembic", "⚗️"),
+    ("alien", "👽"),
+    ("apple", "🍎"),
+    ("arrow_down", "⬇️"),
+    ("arrow_left", "⬅️"),
+    ("arrow_right", "➡️"),
+    ("arrow_up", "⬆️"),
+    ("art", "🎨"),
+    ("beer", "🍺"),
+    ("bell", "🔔"),
+    ("bento", "🍱"),
+    ("bomb", "💣"),
+    ("book", "📖"),
+    ("bookmark", "🔖"),
+    ("books", "📚"),
+    ("boom", "💥"),
+    ("bug", "🐛"),
+    ("building_construction", "🏗️"),
+    ("bulb", "💡"),
+    ("calendar", "📆"),
+    ("camera", "📷"),
+    ("card_file_box", "🗃️"),
+    ("cat", "🐱"),
+    ("chart_with_upwards_trend", "📈"),
+    ("children_crossing", "🚸"),
+    ("clap", "👏"),
+    ("clipboard", "📋"),
+    ("closed_lock_with_key", "🔐"),
+    ("cloud", "☁️"),
+    ("coffee", "☕"),
+    ("coffin", "⚰️"),
+    ("confused", "😕"),
+    ("construction", "🚧"),
+    ("cool", "🆒"),
+    ("crab", "🦀"),
+    ("cry", "😢"),
+    ("dart", "🎯"),
+    ("dizzy", "💫"),
+    ("dog", "🐶"),
+    ("egg", "🥚"),
+    ("exclamation", "❗"),
+    ("eyes", "👀"),
+    ("fire", "🔥"),
+    ("fireworks", "🎆"),
+    ("gear", "⚙️"),
+    ("ghost", "👻"),
+    ("gift", "🎁"),
+    ("globe_with_meridians", "🌐"),
+    ("goal_net", "🥅"),
+    ("green_apple", "🍏"),
+    ("green_heart", "💚"),
+    ("grimacing", "😬"),
+    ("grin", "😁"),
+    ("grinning", "😀"),
+    ("hammer", "🔨"),
+    ("hammer_and_wrench", "🛠️"),
+    ("hankey", "💩"),
+    ("heart", "❤️"),
+    ("heart_eyes", "😍"),
+    ("heavy_check_mark", "✔️"),
+    ("heavy_exclamation_mark", "❗"),
+    ("heavy_minus_sign", "➖"),
+    ("heavy_plus_sign", "➕"),
+    ("hourglass", "⌛"),
+    ("hugs", "🤗"),
+    ("information_source", "ℹ️"),
+    ("iphone", "📱"),
+    ("joy", "😂"),
+    ("key", "🔑"),
+    ("label", "🏷️"),
+    ("lady_beetle", "🐞"),
+    ("laughing", "😆"),
+    ("link", "🔗"),
+    ("lipstick", "💄"),
+    ("lock", "🔒"),
+    ("loud_sound", "🔊"),
+    ("loudspeaker", "📢"),
+    ("mag", "🔍"),
+    ("mega", "📣"),
+    ("memo", "📝"),
+    ("microscope", "🔬"),
+    ("money_with_wings", "💸"),
+    ("monocle_face", "🧐"),
+    ("muscle", "💪"),
+    ("mute", "🔇"),
+    ("new", "🆕"),
+    ("no_entry", "⛔"),
+    ("no_entry_sign", "🚫"),
+    ("ok", "🆗"),
+    ("ok_hand", "👌"),
+    ("package", "📦"),
+    ("page_facing_up", "📄"),
+    ("panda_face", "🐼"),
+    ("partying_face", "🥳"),
+    ("passport_control", "🛂"),
+    ("pencil2", "✏️"),
+    ("penguin", "🐧"),
+    ("point_right", "👉"),
+    ("point_up", "👆"),
+    ("poop", "💩"),
+    ("pray", "🙏"),
+    ("pushpin", "📌"),
+    ("question", "❓"),
+    ("raised_hands", "🙌"),
+    ("recycle", "♻️"),
+    ("red_circle", "🔴"),
+    ("relaxed", "☺️"),
+    ("rocket", "🚀"),
+    ("rotating_light", "🚨"),
+    ("safety_vest", "🦺"),
+    ("scream", "😱"),
+    ("see_no_evil", "🙈"),
+    ("seedling", "🌱"),
+    ("shrug", "🤷"),
+    ("skull", "💀"),
+    ("slightly_smiling_face", "🙂"),
+    ("smile", "😄"),
+    ("smiley", "😃"),
+    ("smirk", "😏"),
+    ("snake", "🐍"),
+    ("sob", "😭"),
+    ("sparkles", "✨"),
+    ("speech_balloon", "💬"),
+    ("star", "⭐"),
+    ("star2", "🌟"),
+    ("stop_sign", "🛑"),
+    ("sunglasses", "😎"),
+    ("sweat_smile", "😅"),
+    ("tada", "🎉"),
+    ("technologist", "🧑‍💻"),
+    ("test_tube", "🧪"),
+    ("thinking", "🤔"),
+    ("thread", "🧵"),
+    ("thumbsdown", "👎"),
+    ("thumbsup", "👍"),
+    ("triangular_flag_on_post", "🚩"),
+    ("trophy", "🏆"),
+    ("truck", "🚚"),
+    ("twisted_rightwards_arrows", "🔀"),
+    ("unlock", "🔓"),
+    ("upside_down_face", "🙃"),
+    ("v", "✌️"),
+    ("warning", "⚠️"),
+    ("wastebasket", "🗑️"),
+    ("wave", "👋"),
+    ("whale", "🐳"),
+    ("white_check_mark", "✅"),
+    ("wink", "😉"),
+    ("wrench", "🔧"),
+    ("x", "❌"),
+    ("zap", "⚡"),
+    ("zzz", "💤"),
+];
+
+/// The emoji for a shortcode without the surrounding colons.
+pub fn lookup(shortcode: &str) -> Option<&'static str> {
+    EMOJI
+        .binary_search_by_key(&shortcode, |(name, _)| name)
+        .ok()
+        .map(|i| EMOJI[i].1)
+}
+
+/// Replace the known `:shortcode:`s in the text with their emoji.
+pub fn replace_shortcodes(text: &str) -> Cow<'_, str> {
+    if !text.contains(':') {
+        return Cow::Borrowed(text);
+    }
+
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(':') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let emoji = after.find(':').and_then(|end| {
+            let name = &after[..end];
+            let is_shortcode = !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '+' | '-'));
+            is_shortcode
+                .then(|| lookup(name))
+                .flatten()
+                .map(|e| (e, end))
+        });
+        match emoji {
+            Some((emoji, end)) => {
+                result.push_str(emoji);
+                rest = &after[end + 1..];
+            }
+            None => {
+                result.push(':');
+                rest = after;
+            }
+        }
+    }
+    result.push_str(rest);
+    Cow::Owned(result)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_table_is_sorted() {
+        assert!(EMOJI.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    fn test_replace_shortcodes() {
+        assert_eq!(replace_shortcodes("Released :tada: :+1:"), "Released 🎉 👍");
+        assert_eq!(
+            replace_shortcodes(":not_an_emoji: :rocket:"),
+            ":not_an_emoji: 🚀"
+        );
+        assert_eq!(replace_shortcodes("at 10:30: done"), "at 10:30: done");
+        assert_eq!(replace_shortcodes("a::b:bug:"), "a::b🐛");
+        assert_eq!(replace_shortcodes("no colons"), "no colons");
+    }
+}
@@ -1,31 +1,309 @@
+use std::sync::Mutex;
+
+use crate::github::events::DateTimeUtc;
+
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
-    // TODO: Add InvalidToken and wrap Auth like RateLimit
     #[error("authentication with github failed")]
     Authentication,
+    #[error("github rejected the token, it may be invalid or expired; create a new one at https://github.com/settings/tokens")]
+    InvalidToken(#[source] octocrab::Error),
+    #[error("the token has to be authorized for the single sign-on of this organization; configure SSO for it at https://github.com/settings/tokens")]
+    SsoRequired(#[source] octocrab::Error),
+    #[error("the token doesn't have the permissions (scopes) for this; add them at https://github.com/settings/tokens")]
+    MissingScopes(#[source] octocrab::Error),
+    #[error(
+        "not found on github, it may have been deleted or the token may not have access to it"
+    )]
+    NotFound(#[source] octocrab::Error),
+    #[error("token command failed: {0}")]
+    TokenCommand(String),
+    #[error("could not read token file")]
+    TokenFile(#[source] std::io::Error),
     #[error("target html url for {api_url} not found")]
     HtmlUrlNotFound { api_url: String },
     #[error("could not communicate with github")]
     GitHub(#[source] octocrab::Error),
-    #[error("github api rate limit exceeded")]
-    GitHubRateLimitExceeded(#[source] octocrab::Error),
+    #[error("github api rate limit exceeded, {}", resets_in(.reset))]
+    GitHubRateLimitExceeded {
+        #[source]
+        source: octocrab::Error,
+        /// When the exhausted limit resets, see
+        /// [`crate::network::methods::with_rate_limit_reset`].
+        reset: Option<DateTimeUtc>,
+    },
+    #[error("github secondary rate limit hit for making too many requests at once, wait a few minutes before trying again")]
+    GitHubSecondaryRateLimit(#[source] octocrab::Error),
+    #[error("could not read the response from github")]
+    GitHubResponse(#[source] reqwest::Error),
+    #[error("unexpected response from github: {0}")]
+    GitHubJson(#[source] serde_json::Error),
     #[error("graphql error")]
     Graphql(Vec<graphql_client::Error>),
+    #[error("unexpected graphql response: {0}")]
+    GraphqlResponse(#[source] serde_json::Error),
+    #[error("could not write graphql debug dump")]
+    GraphqlDump(#[source] std::io::Error),
     #[error("could not complete concurrent network requests")]
     NetworkTask,
+    #[error("could not download workflow job log")]
+    JobLog,
+    #[error("could not download image")]
+    ImageDownload,
+    #[error("could not download release asset")]
+    AssetDownload,
     #[error("could not open browser")]
     BrowserNotAvailable,
+    #[error("could not read config file")]
+    ConfigRead(#[source] std::io::Error),
+    #[error("invalid config file: {0}")]
+    ConfigParse(#[source] toml::de::Error),
+    #[error("invalid config file: `{0}` is not a valid dates.format")]
+    ConfigDateFormat(String),
+    #[error("could not convert search result")]
+    SyntheticNotification,
+    #[error("could not read saved state")]
+    StateRead(#[source] std::io::Error),
+    #[error("invalid saved state: {0}")]
+    StateParse(#[source] toml::de::Error),
+    #[error("could not save state")]
+    StateWrite(#[source] std::io::Error),
+    #[error("could not read fixture")]
+    FixtureRead(#[source] std::io::Error),
+    #[error("invalid fixture: {0}")]
+    FixtureParse(#[source] serde_json::Error),
+    #[error("could not write fixture")]
+    FixtureWrite(#[source] std::io::Error),
+    #[error("could not draw to the terminal")]
+    Terminal(#[source] std::io::Error),
 }
 
 impl From<octocrab::Error> for Error {
     fn from(e: octocrab::Error) -> Self {
-        if let octocrab::Error::GitHub { ref source, .. } = e {
-            if source.message.contains("rate limit exceeded") {
-                return Self::GitHubRateLimitExceeded(e);
+        let message = match e {
+            octocrab::Error::GitHub { ref source, .. } => source.message.as_str(),
+            _ => return Self::GitHub(e),
+        };
+        match ApiFailure::from_message(message) {
+            ApiFailure::InvalidToken => Self::InvalidToken(e),
+            ApiFailure::SsoRequired => Self::SsoRequired(e),
+            ApiFailure::MissingScopes => Self::MissingScopes(e),
+            ApiFailure::NotFound => Self::NotFound(e),
+            ApiFailure::RateLimit => Self::GitHubRateLimitExceeded {
+                source: e,
+                reset: None,
+            },
+            ApiFailure::SecondaryRateLimit => Self::GitHubSecondaryRateLimit(e),
+            ApiFailure::Other => Self::GitHub(e),
+        }
+    }
+}
+
+fn resets_in(reset: &Option<DateTimeUtc>) -> String {
+    match reset {
+        Some(reset) => {
+            let secs = (*reset - chrono::Utc::now()).num_seconds();
+            format!("resets in {}", crate::util::format_countdown(secs))
+        }
+        None => "run `stats` to see when it resets".to_string(),
+    }
+}
+
+static LAST_ERROR: Mutex<Option<String>> = Mutex::new(None);
+
+/// Details of the last error given to [`Error::report`].
+pub fn last_error_detail() -> Option<String> {
+    LAST_ERROR.lock().unwrap().clone()
+}
+
+impl Error {
+    /// The one line message of the error, keeping its details to be shown
+    /// by the `error` command.
+    pub fn report(&self) -> String {
+        *LAST_ERROR.lock().unwrap() = Some(self.detail());
+        self.to_string()
+    }
+
+    /// The message of the error followed by everything known about its
+    /// causes, like the errors of a GraphQL response or the status and url
+    /// of a failed request.
+    pub fn detail(&self) -> String {
+        let mut lines = vec![self.to_string()];
+        if let Self::Graphql(errors) = self {
+            lines.extend(errors.iter().map(|err| format!("  {err}")));
+        }
+        let mut source = std::error::Error::source(self);
+        while let Some(err) = source {
+            if let Some(err) = err.downcast_ref::<octocrab::Error>() {
+                // The sources of octocrab errors are covered here, and their
+                // messages include backtraces.
+                lines.extend(octocrab_detail(err));
+                break;
+            }
+            lines.push(format!("  caused by: {err}"));
+            source = err.source();
+        }
+        lines.join("\n")
+    }
+}
+
+fn octocrab_detail(err: &octocrab::Error) -> Vec<String> {
+    match err {
+        octocrab::Error::GitHub { source, .. } => {
+            let mut lines = vec![format!("  github: {}", source.message)];
+            if let Some(ref url) = source.documentation_url {
+                lines.push(format!("  documentation: {url}"));
+            }
+            for error in source.errors.iter().flatten() {
+                lines.push(format!("  - {error}"));
+            }
+            lines
+        }
+        octocrab::Error::Http { source, .. } => {
+            let mut lines = vec![format!("  http: {source}")];
+            if let Some(status) = source.status() {
+                lines.push(format!("  status: {status}"));
+            }
+            if let Some(url) = source.url() {
+                lines.push(format!("  url: {url}"));
+            }
+            lines
+        }
+        octocrab::Error::Url { source, .. } => vec![format!("  invalid url: {source}")],
+        octocrab::Error::Serde { source, .. } => vec![format!("  invalid json: {source}")],
+        octocrab::Error::Json { source, .. } => vec![format!(
+            "  invalid json at {}: {}",
+            source.path(),
+            source.inner()
+        )],
+        octocrab::Error::JWT { source, .. } => vec![format!("  jwt: {source}")],
+        octocrab::Error::Other { source, .. } => vec![format!("  caused by: {source}")],
+    }
+}
+
+/// Kinds of errors returned by the GitHub API. octocrab doesn't keep the
+/// status code of failed requests, so they are told apart by the message.
+#[derive(Debug, PartialEq, Eq)]
+enum ApiFailure {
+    /// 401
+    InvalidToken,
+    /// 403 from an organization enforcing SAML single sign-on
+    SsoRequired,
+    /// 403 for a fine-grained token or app without the permission
+    MissingScopes,
+    /// 404, also returned for private repositories the token can't see
+    NotFound,
+    RateLimit,
+    /// Abuse detection for too many concurrent requests or content creation
+    SecondaryRateLimit,
+    Other,
+}
+
+impl ApiFailure {
+    fn from_message(message: &str) -> Self {
+        let message = message.to_lowercase();
+        match message.as_str() {
+            "bad credentials" | "requires authentication" => Self::InvalidToken,
+            m if m.contains("saml") => Self::SsoRequired,
+            m if m.starts_with("resource not accessible by") => Self::MissingScopes,
+            "not found" => Self::NotFound,
+            m if m.contains("secondary rate limit") || m.contains("abuse detection") => {
+                Self::SecondaryRateLimit
             }
+            m if m.contains("rate limit exceeded") => Self::RateLimit,
+            _ => Self::Other,
         }
-        Self::GitHub(e)
+    }
+}
+
+/// Exit codes of `octerm -c` and `octerm run`, so that scripts can tell
+/// failures apart.
+pub mod exit_code {
+    /// Any error without a more specific code.
+    pub const FAILURE: i32 = 1;
+    /// Invalid arguments or pipelines.
+    pub const USAGE: i32 = 2;
+    pub const AUTHENTICATION: i32 = 3;
+    pub const RATE_LIMIT: i32 = 4;
+    pub const NETWORK: i32 = 5;
+    /// A `list` pipeline matched no notifications.
+    pub const NO_MATCHES: i32 = 6;
+}
+
+impl Error {
+    /// The [`exit_code`] for a run that failed with this error.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Authentication
+            | Self::TokenCommand(_)
+            | Self::TokenFile(_)
+            | Self::InvalidToken(_)
+            | Self::SsoRequired(_)
+            | Self::MissingScopes(_) => exit_code::AUTHENTICATION,
+            Self::GitHubRateLimitExceeded { .. } | Self::GitHubSecondaryRateLimit(_) => {
+                exit_code::RATE_LIMIT
+            }
+            Self::GitHub(_)
+            | Self::GitHubResponse(_)
+            | Self::NetworkTask
+            | Self::JobLog
+            | Self::ImageDownload
+            | Self::AssetDownload => exit_code::NETWORK,
+            _ => exit_code::FAILURE,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_api_failure() {
+        let kind = ApiFailure::from_message;
+        assert_eq!(kind("Bad credentials"), ApiFailure::InvalidToken);
+        assert_eq!(
+            kind("Resource protected by organization SAML enforcement. You must grant your Personal Access token access to this organization."),
+            ApiFailure::SsoRequired
+        );
+        assert_eq!(
+            kind("Resource not accessible by personal access token"),
+            ApiFailure::MissingScopes
+        );
+        assert_eq!(kind("Not Found"), ApiFailure::NotFound);
+        assert_eq!(
+            kind("API rate limit exceeded for user ID 1."),
+            ApiFailure::RateLimit
+        );
+        assert_eq!(
+            kind("You have exceeded a secondary rate limit. Please wait a few minutes before you try again."),
+            ApiFailure::SecondaryRateLimit
+        );
+        assert_eq!(kind("Validation Failed"), ApiFailure::Other);
+    }
+
+    #[test]
+    fn test_detail() {
+        let err = Error::FixtureRead(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "no such file",
+        ));
+        assert_eq!(
+            err.detail(),
+            "could not read fixture\n  caused by: no such file"
+        );
+
+        let err = Error::Graphql(vec![graphql_client::Error {
+            message: "Could not resolve to a Repository".to_string(),
+            locations: None,
+            path: None,
+            extensions: None,
+        }]);
+        assert_eq!(
+            err.detail(),
+            "graphql error\n  <query>:0:0: Could not resolve to a Repository"
+        );
     }
 }
@@ -0,0 +1,1458 @@
+//! The engine behind the REPL, `octerm -c` and `octerm run`: runs parsed
+//! pipelines against the synced notifications and prints their output.
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    io::Write,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::OnceCell;
+
+use crate::{
+    completion::CompletionData,
+    error::{exit_code, Error},
+    github::{
+        Notification, NotificationRecord, NotificationTarget, RepoMeta, NOTIFICATION_REASONS,
+    },
+    line_editor,
+    mutes::MuteRules,
+    network::{
+        backend::GithubBackend,
+        methods::{self, rate_limit, with_rate_limit_reset},
+        stats::{self, Refresh},
+    },
+    parsec::{and, duration, eof, pred},
+    parser::types::{
+        Adapter, Command, Consumer, ConsumerWithArgs, Parsed, Producer, ProducerExpr,
+        ProducerWithArgs,
+    },
+};
+use reedline::{ExternalPrinter, Signal};
+
+use crossterm::style::Stylize;
+
+// Shadow the printing macros of std so that all output drops its colors
+// when they are disabled with `--color` or `NO_COLOR`. Declared before the
+// submodules so that they are used there too.
+macro_rules! print {
+    ($($arg:tt)*) => {
+        std::print!("{}", crate::util::maybe_strip_ansi(format!($($arg)*)))
+    };
+}
+
+macro_rules! println {
+    () => {
+        std::println!()
+    };
+    ($($arg:tt)*) => {
+        std::println!("{}", crate::util::maybe_strip_ansi(format!($($arg)*)))
+    };
+}
+
+pub mod adapters;
+pub mod consumers;
+
+/// Read and execute lines until Ctrl-C or Ctrl-D.
+pub async fn repl(session: &mut Session) {
+    let completion_data = Arc::new(Mutex::new(CompletionData::new(&session.notifications)));
+    let printer = ExternalPrinter::new(16);
+    let mut line_editor = line_editor::line_editor(completion_data.clone(), printer.clone());
+    spawn_alerts(printer.clone());
+
+    loop {
+        let prompt = line_editor::prompt(session.notifications.len(), &UNSEEN);
+        let synced_at = *SYNCED_AT.lock().unwrap();
+        let sig = line_editor.read_line(&prompt);
+        match sig {
+            Ok(Signal::CtrlD) | Ok(Signal::CtrlC) => {
+                println!("Exiting.");
+                break;
+            }
+            Ok(Signal::Success(cmdline)) => {
+                if let Err(failure) = session.execute(&cmdline).await {
+                    print_error(&failure.message);
+                }
+                *completion_data.lock().unwrap() = CompletionData::new(&session.notifications);
+                // Alerts queued before a reload are out of date
+                if *SYNCED_AT.lock().unwrap() != synced_at {
+                    while printer.get_line().is_some() {}
+                }
+            }
+            Err(err) => print_error(&err.to_string()),
+        }
+    }
+}
+
+/// Check for new notifications every few minutes, as set in the config,
+/// and print a line above the prompt when more threads have been updated
+/// since the last sync than at the previous check.
+fn spawn_alerts(printer: ExternalPrinter<String>) {
+    let minutes = crate::config::get().alerts.interval;
+    if minutes == 0 {
+        return;
+    }
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(minutes * 60));
+        // The first tick completes immediately, right after the startup sync
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            let since = match *SYNCED_AT.lock().unwrap() {
+                Some(since) => since,
+                None => continue,
+            };
+            let threads = match crate::network::methods::updated_threads(
+                &octocrab::instance(),
+                since,
+            )
+            .await
+            {
+                Ok(threads) => threads,
+                Err(_) => continue,
+            };
+            let mutes = MuteRules::load().unwrap_or_default();
+            let count = threads.iter().filter(|t| !mutes.is_thread_muted(t)).count();
+            // Skip the count if a reload finished during the request
+            if *SYNCED_AT.lock().unwrap() != Some(since) {
+                continue;
+            }
+            if count > UNSEEN.swap(count, Ordering::Relaxed) {
+                let plural = if count == 1 { "" } else { "s" };
+                let line = format!("{count} new notification{plural} (reload to sync)");
+                let _ = printer.sender().try_send(line.dark_grey().to_string());
+            }
+        }
+    });
+}
+
+/// Run each line of a script, skipping blank lines and `#` comments.
+/// Stops at the first failing line and reports its line number.
+pub async fn run_script(session: &mut Session, script: &str) -> Result<(), Failure> {
+    for (lineno, line) in script.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        session.execute(line).await.map_err(|failure| Failure {
+            message: format!("{}: {}", lineno + 1, failure.message),
+            ..failure
+        })?;
+    }
+    Ok(())
+}
+
+/// State kept between the lines of a REPL session or script.
+pub struct Session {
+    pub notifications: Vec<Notification>,
+    /// Indices listed by the last `list` pipeline, used for `$_`.
+    pub last_result: Option<Vec<usize>>,
+    /// Print the results of bare `list` pipelines as JSON lines.
+    pub json: bool,
+    pub backend: Arc<dyn GithubBackend>,
+}
+
+/// An error from running a line, with the exit code it is reported with
+/// outside of the REPL.
+#[derive(Debug)]
+pub struct Failure {
+    pub message: String,
+    pub code: i32,
+}
+
+impl Failure {
+    pub fn usage(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            code: exit_code::USAGE,
+        }
+    }
+}
+
+impl From<String> for Failure {
+    fn from(message: String) -> Self {
+        Self {
+            message,
+            code: exit_code::FAILURE,
+        }
+    }
+}
+
+impl From<Error> for Failure {
+    fn from(err: Error) -> Self {
+        Self {
+            message: err.report(),
+            code: err.exit_code(),
+        }
+    }
+}
+
+impl Session {
+    /// Parse and run a line, remembering the indices it listed for `$_`.
+    pub async fn execute(&mut self, cmdline: &str) -> Result<(), Failure> {
+        let cmdline = crate::parser::expand_last_result(cmdline, self.last_result.as_deref())
+            .map_err(Failure::usage)?;
+        let cmdline = cmdline.trim();
+        let parsed = match crate::parser::parse(cmdline) {
+            Ok((_, parsed)) => parsed,
+            Err(err) => return Err(Failure::usage(err.report(cmdline))),
+        };
+        let parsed = match parsed {
+            Parsed::ProducerExpr(
+                pexpr @ ProducerExpr {
+                    consumer: None,
+                    redirect: None,
+                    ..
+                },
+            ) if self.json => Parsed::ProducerExpr(ProducerExpr {
+                consumer: Some(ConsumerWithArgs {
+                    consumer: Consumer::Json,
+                    opts: vec![],
+                    args: vec![],
+                }),
+                ..pexpr
+            }),
+            parsed => parsed,
+        };
+
+        let ids: Vec<_> = self.notifications.iter().map(|n| n.inner.id).collect();
+        let result = run(parsed, &mut self.notifications, &*self.backend).await;
+        if let Ok(Some(indices)) = &result {
+            self.last_result = Some(indices.clone());
+        }
+        // Indices of the last result are stale if the list changed.
+        if !self.notifications.iter().map(|n| n.inner.id).eq(ids) {
+            self.last_result = None;
+        }
+        result?;
+        Ok(())
+    }
+}
+
+type ExecResult = Result<(), String>;
+
+/// Run a parsed expression, returning the notification indices it produced
+/// if it was a `list` pipeline.
+async fn run(
+    parsed: Parsed,
+    notifications: &mut Vec<Notification>,
+    backend: &dyn GithubBackend,
+) -> Result<Option<Vec<usize>>, String> {
+    match parsed {
+        Parsed::Help(None) => println!("{}", crate::help::overview()),
+        Parsed::Help(Some(name)) => match crate::help::topic(&name) {
+            Some(topic) => println!("{topic}"),
+            None => return Err(format!("No help for `{name}`")),
+        },
+        Parsed::NewIssue { repo, labels } => consumers::new_issue(&repo, labels).await?,
+        Parsed::Command(cmd) => run_command(cmd, notifications, backend).await?,
+        Parsed::ProducerExpr(pexpr) => {
+            return run_producer_expr(pexpr, notifications, backend).await
+        }
+        Parsed::ConsumerWithArgs(cons) => run_consumer(cons, notifications, backend).await?,
+    };
+    Ok(None)
+}
+
+async fn run_command(
+    cmd: Command,
+    notifications: &mut Vec<Notification>,
+    backend: &dyn GithubBackend,
+) -> ExecResult {
+    match cmd {
+        Command::Reload => reload(notifications, backend).await?,
+        Command::Dates => match crate::util::toggle_relative_dates() {
+            true => println!("Showing relative dates"),
+            false => println!("Showing absolute dates"),
+        },
+        Command::Releases => {
+            let all: Vec<usize> = (0..notifications.len()).collect();
+            fetch_details(notifications, &all, backend)
+                .await
+                .map_err(|err| err.report())?;
+            print_release_digest(notifications)
+        }
+        Command::Stats => {
+            let rate_limit = rate_limit(&octocrab::instance()).await.ok();
+            let report = stats::report(stats::last_refresh(), stats::totals(), rate_limit.as_ref());
+            print_lines(&report);
+        }
+        Command::LastError => match crate::error::last_error_detail() {
+            Some(detail) => print_lines(&[detail]),
+            None => println!("No errors so far"),
+        },
+        Command::Digest => {
+            let until = Utc::now();
+            let since = until - chrono::Duration::weeks(1);
+            let week: Vec<usize> = (0..notifications.len())
+                .filter(|&i| notifications[i].inner.updated_at >= since)
+                .collect();
+            fetch_details(notifications, &week, backend)
+                .await
+                .map_err(|err| err.report())?;
+            let records = consumers::records(notifications, &week).await;
+            print!("{}", markdown_digest(records, since, until));
+        }
+        Command::Participating => {
+            match methods::toggle_participating() {
+                true => println!("Syncing only participating notifications"),
+                false => println!("Syncing all notifications"),
+            }
+            reload(notifications, backend).await?;
+        }
+    };
+    Ok(())
+}
+
+async fn run_producer_expr(
+    pexpr: ProducerExpr,
+    notifications: &mut Vec<Notification>,
+    backend: &dyn GithubBackend,
+) -> Result<Option<Vec<usize>>, String> {
+    let ProducerExpr {
+        producer:
+            ProducerWithArgs {
+                producer,
+                args: producer_args,
+            },
+        adapters,
+        consumer,
+        redirect,
+    } = pexpr;
+
+    // Grouping is skipped when explicitly listing dependency updates.
+    let group_deps = crate::config::get().list.group_dependency_updates
+        && !producer_args.iter().any(|a| a == "deps");
+    // Producers other than list yield items that aren't notifications, which
+    // go through the rest of the pipeline in a list of their own.
+    let mut results;
+    let (notifications, mut indices) = match producer {
+        Producer::List => {
+            // Participating is a mode of the sync rather than a filter,
+            // since GitHub doesn't say which threads are participating. The
+            // mode stays on so that indices of the listed threads stay valid.
+            if producer_args.iter().any(|a| a == "participating") && !methods::participating() {
+                methods::set_participating(true);
+                println!("Syncing only participating notifications");
+                reload(notifications, backend).await?;
+            }
+            if filters_need_details(&producer_args) {
+                let all: Vec<usize> = (0..notifications.len()).collect();
+                fetch_details(notifications, &all, backend)
+                    .await
+                    .map_err(|err| err.report())?;
+            }
+            let indices = list(notifications, producer_args).await?;
+            (notifications, indices)
+        }
+        Producer::Search => {
+            results = search(producer_args).await?;
+            let indices = (0..results.len()).collect();
+            (&mut results, indices)
+        }
+        Producer::Repo => {
+            results = repo(producer_args).await?;
+            let indices = (0..results.len()).collect();
+            (&mut results, indices)
+        }
+        Producer::Assigned | Producer::Reviews | Producer::Created => {
+            // Extra args are added to the query, like `assigned repo:foo/bar`.
+            let query = match producer {
+                Producer::Assigned => "is:open assignee:@me",
+                Producer::Reviews => "is:open is:pr review-requested:@me",
+                _ => "is:open is:pr author:@me",
+            };
+            let mut args = vec![query.to_string()];
+            args.extend(producer_args);
+            results = search(args).await?;
+            let indices = (0..results.len()).collect();
+            (&mut results, indices)
+        }
+    };
+
+    for adapter in adapters {
+        indices = match adapter.adapter {
+            Adapter::Confirm => adapters::confirm(notifications, &indices, backend).await?,
+            Adapter::Sort => adapters::sort(notifications, indices, &adapter.args)?,
+            Adapter::Head => adapters::head(indices, &adapter.args)?,
+            Adapter::Tail => adapters::tail(indices, &adapter.args)?,
+            Adapter::Reverse => indices.into_iter().rev().collect(),
+            Adapter::Grep => adapters::grep(notifications, indices, &adapter.args)?,
+            Adapter::Tee => {
+                print_notifications(notifications, &indices);
+                indices
+            }
+        }
+    }
+
+    fetch_details(notifications, &indices, backend)
+        .await
+        .map_err(|err| err.report())?;
+    let produced = matches!(producer, Producer::List).then(|| indices.clone());
+    match (consumer, redirect) {
+        (Some(_), Some(_)) => return Err("Only lists can be redirected to a file".to_string()),
+        (None, Some(path)) => {
+            let lines = match group_deps {
+                true => notification_lines_grouped(notifications, &indices),
+                false => notification_lines(notifications, &indices),
+            };
+            let text: String = lines
+                .iter()
+                .map(|line| crate::util::strip_ansi(line) + "\n")
+                .collect();
+            std::fs::write(&path, text).map_err(|err| format!("Could not write {path}: {err}"))?;
+        }
+        (None, None) if group_deps => print_notifications_grouped(notifications, &indices),
+        (None, None) => print_notifications(notifications, &indices),
+        (Some(consumer), None) => {
+            run_consumer(
+                ConsumerWithArgs {
+                    args: indices,
+                    ..consumer
+                },
+                notifications,
+                backend,
+            )
+            .await?
+        }
+    };
+
+    Ok(produced)
+}
+
+async fn run_consumer(
+    cons: ConsumerWithArgs,
+    notifications: &mut Vec<Notification>,
+    backend: &dyn GithubBackend,
+) -> ExecResult {
+    let ConsumerWithArgs {
+        consumer: cons,
+        opts,
+        args,
+    } = cons;
+
+    if let Some(i) = args.iter().find(|i| **i >= notifications.len()) {
+        return Err(format!("No notification at index {i}"));
+    }
+    fetch_details(notifications, &args, backend)
+        .await
+        .map_err(|err| err.report())?;
+    // TODO: Decide behaviour on empty args
+    match cons {
+        Consumer::Count => consumers::count(notifications, &args, &opts).await?,
+        Consumer::Open => consumers::open(notifications, &args).await?,
+        Consumer::Logs => consumers::logs(notifications, &args).await?,
+        Consumer::Accept => consumers::respond(notifications, &args, true, backend).await?,
+        Consumer::Decline => consumers::respond(notifications, &args, false, backend).await?,
+        Consumer::Rebase => consumers::rebase(notifications, &args).await?,
+        Consumer::Assets => consumers::assets(notifications, &args, &opts).await?,
+        Consumer::Compare => consumers::compare(notifications, &args).await?,
+        Consumer::Links => consumers::links(notifications, &args, backend).await?,
+        Consumer::Copy => consumers::copy(notifications, &args, backend).await?,
+        Consumer::Unsubscribe => consumers::unsubscribe(notifications, &args).await?,
+        Consumer::Mute => consumers::mute(notifications, &args, &opts).await?,
+        Consumer::Show => consumers::show(notifications, &args, &opts, backend).await?,
+        Consumer::View => consumers::view(notifications, &args, &opts, backend).await?,
+        Consumer::Browse => consumers::browse(notifications, &args, &opts).await?,
+        Consumer::Json => consumers::json(notifications, &args).await?,
+        Consumer::Export => consumers::export(notifications, &args, &opts).await?,
+        Consumer::Comment => consumers::comment(notifications, &args).await?,
+        Consumer::Edit => consumers::edit(notifications, &args, &opts).await?,
+        Consumer::Checkout => consumers::checkout(notifications, &args).await?,
+        Consumer::Done => {
+            consumers::done(notifications, &args, backend).await?;
+            // Print the list again since done will change the indices
+            // let indices = list(notifications, Vec::new()).await?;
+            // print_notifications(notifications, &indices);
+        }
+    };
+
+    Ok(())
+}
+
+/// Fetch the targets of the notifications at `indices` that are still
+/// pending. The order of notifications isn't changed, so that indices shown
+/// earlier stay valid.
+async fn fetch_details(
+    notifications: &mut [Notification],
+    indices: &[usize],
+    backend: &dyn GithubBackend,
+) -> Result<(), Error> {
+    let pending: Vec<usize> = indices
+        .iter()
+        .copied()
+        .filter(|&i| {
+            notifications
+                .get(i)
+                .is_some_and(|n| matches!(n.target, NotificationTarget::Pending))
+        })
+        .collect();
+    let details = pending
+        .iter()
+        .map(|&i| backend.details(notifications[i].clone()));
+    let details = futures::future::join_all(details).await;
+    for (i, notification) in pending.into_iter().zip(details) {
+        notifications[i] = notification?;
+    }
+    Ok(())
+}
+
+/// Whether any of the `list` filters look at the targets of notifications
+/// rather than just the threads.
+fn filters_need_details(args: &[String]) -> bool {
+    const THREAD_FILTERS: [&str; 4] = ["repo:", "org:", "reason:", "updated:"];
+    args.iter().any(|arg| {
+        let arg = arg.trim_start_matches('!');
+        arg != "participating" && !THREAD_FILTERS.iter().any(|prefix| arg.starts_with(prefix))
+    })
+}
+
+pub async fn list(notifications: &[Notification], args: Vec<String>) -> Result<Vec<usize>, String> {
+    // Every negated argument like `!release` or `!repo:foo/bar` is matched
+    // on its own and its matches are removed from the result.
+    let (negated, args): (Vec<_>, Vec<_>) = args.into_iter().partition(|a| a.starts_with('!'));
+    let mut excluded = HashSet::new();
+    for arg in negated {
+        match &arg[1..] {
+            "" => return Err("Expected a filter after `!`".to_string()),
+            "participating" => {
+                return Err(
+                    "participating can't be negated, use `participating` to sync all notifications"
+                        .to_string(),
+                )
+            }
+            arg if arg.starts_with('!') => return Err(format!("Invalid filter `!{arg}`")),
+            arg => excluded.extend(filter(notifications, &[arg.to_string()])?),
+        }
+    }
+
+    let mut indices = filter(notifications, &args)?;
+    indices.retain(|i| !excluded.contains(i));
+    Ok(indices)
+}
+
+pub fn filter(notifications: &[Notification], args: &[String]) -> Result<Vec<usize>, String> {
+    // Unknown filters are rejected by the parser, the values of qualifiers
+    // like `repo:` are validated here.
+    let has_arg = |arg| args.iter().any(|a| *a == arg);
+    let is_pr = has_arg("pr");
+    let is_issue = has_arg("issue");
+    let is_closed = has_arg("closed");
+    let is_open = has_arg("open");
+    let is_merged = has_arg("merged");
+    let is_release = has_arg("release");
+    let is_discussion = has_arg("discussion");
+    let is_ci = has_arg("ci");
+    let is_commit = has_arg("commit");
+    let is_invitation = has_arg("invitation");
+    let is_alert = has_arg("alert");
+    let is_gist = has_arg("gist");
+    let is_deps = has_arg("deps");
+
+    let types = [
+        ("pr", is_pr),
+        ("issue", is_issue),
+        ("discussion", is_discussion),
+        ("release", is_release),
+        ("ci", is_ci),
+        ("commit", is_commit),
+        ("invitation", is_invitation),
+        ("alert", is_alert),
+        ("gist", is_gist),
+        ("deps", is_deps),
+    ];
+    if true_count(&types.map(|(_, is_type)| is_type)) > 1 {
+        let names: Vec<_> = types.iter().map(|(name, _)| *name).collect();
+        return Err(format!("{} are mutually exclusive", names.join(", ")));
+    }
+
+    if true_count(&[is_open, is_closed, is_merged]) > 1 {
+        return Err("pr, issue, merged are mutually exclusive".to_string());
+    }
+
+    let mut repos = Vec::new();
+    let mut orgs = Vec::new();
+    let mut labels = Vec::new();
+    let mut updated = Vec::new();
+    let mut reasons = Vec::new();
+    for arg in args {
+        if let Some(repo) = arg.strip_prefix("repo:") {
+            match repo.split_once('/') {
+                Some((owner, name))
+                    if !owner.is_empty() && !name.is_empty() && !name.contains('/') =>
+                {
+                    repos.push((owner, name))
+                }
+                _ => {
+                    return Err(format!(
+                        "Invalid repository `{repo}`, expected repo:owner/name"
+                    ))
+                }
+            }
+        } else if let Some(org) = arg.strip_prefix("org:") {
+            if org.is_empty() || org.contains('/') {
+                return Err(format!("Invalid owner `{org}`, expected org:owner"));
+            }
+            orgs.push(org);
+        } else if let Some(label) = arg.strip_prefix("label:") {
+            if label.is_empty() {
+                return Err("Empty label, expected label:name".to_string());
+            }
+            labels.push(label);
+        } else if let Some(reason) = arg.strip_prefix("reason:") {
+            if !NOTIFICATION_REASONS.contains(&reason) {
+                return Err(format!(
+                    "Invalid reason `{reason}`, expected one of {}",
+                    NOTIFICATION_REASONS.join(", ")
+                ));
+            }
+            reasons.push(reason);
+        } else if let Some(age) = arg.strip_prefix("updated:") {
+            let cmp = pred(|ch| ch == '<' || ch == '>');
+            match eof(and(cmp, duration()))(age) {
+                Ok((_, (cmp, duration))) => updated.push((cmp, duration)),
+                Err(_) => {
+                    return Err(format!(
+                        "Invalid age `{age}`, expected updated:>3d or updated:<1w"
+                    ))
+                }
+            }
+        }
+    }
+
+    let filter_by_type = |n: &Notification| -> bool {
+        if is_pr {
+            matches!(n.target, NotificationTarget::PullRequest(_))
+        } else if is_issue {
+            matches!(n.target, NotificationTarget::Issue(_))
+        } else if is_release {
+            matches!(n.target, NotificationTarget::Release(_))
+        } else if is_discussion {
+            matches!(n.target, NotificationTarget::Discussion(_))
+        } else if is_ci {
+            matches!(n.target, NotificationTarget::CiBuild(_))
+        } else if is_commit {
+            matches!(n.target, NotificationTarget::Commit(_))
+        } else if is_invitation {
+            matches!(n.target, NotificationTarget::Invitation(_))
+        } else if is_alert {
+            matches!(n.target, NotificationTarget::SecurityAlert(_))
+        } else if is_gist {
+            matches!(n.target, NotificationTarget::Gist(_))
+        } else if is_deps {
+            matches!(n.target, NotificationTarget::PullRequest(ref pr) if pr.dependency_bot().is_some())
+        } else {
+            true
+        }
+    };
+
+    let filter_by_state = |n: &Notification| -> bool {
+        if is_open {
+            match n.target {
+                NotificationTarget::Issue(ref issue) => issue.state.is_open(),
+                NotificationTarget::PullRequest(ref pr) => pr.state.is_open(),
+                _ => false,
+            }
+        } else if is_closed {
+            match n.target {
+                NotificationTarget::Issue(ref issue) => issue.state.is_closed(),
+                NotificationTarget::PullRequest(ref pr) => pr.state.is_closed(),
+                _ => false,
+            }
+        } else if is_merged {
+            match n.target {
+                NotificationTarget::PullRequest(ref pr) => pr.state.is_merged(),
+                _ => false,
+            }
+        } else {
+            true
+        }
+    };
+
+    // Repository names on GitHub are case insensitive.
+    let filter_by_repo = |n: &Notification| -> bool {
+        if repos.is_empty() && orgs.is_empty() {
+            return true;
+        }
+        let repo = RepoMeta::from(&n.inner.repository);
+        let is_owner = |owner: &str| owner.eq_ignore_ascii_case(&repo.owner);
+        repos
+            .iter()
+            .any(|(owner, name)| is_owner(owner) && name.eq_ignore_ascii_case(&repo.name))
+            || orgs.iter().any(|owner| is_owner(owner))
+    };
+
+    // Notifications must have all the given labels.
+    let filter_by_label = |n: &Notification| -> bool {
+        let target_labels = n.target.labels();
+        labels.iter().all(|label| {
+            target_labels
+                .iter()
+                .any(|l| l.name.eq_ignore_ascii_case(label))
+        })
+    };
+
+    // `>3d` keeps notifications last updated more than three days ago and
+    // `<3d` the ones updated within the last three days.
+    let now = chrono::Utc::now();
+    let filter_by_age = |n: &Notification| -> bool {
+        let age = now - n.inner.updated_at;
+        updated.iter().all(|(cmp, duration)| match cmp {
+            '>' => age > *duration,
+            _ => age < *duration,
+        })
+    };
+
+    let filter_by_reason = |n: &Notification| -> bool {
+        reasons.is_empty() || reasons.contains(&n.inner.reason.as_str())
+    };
+
+    let notification_indices = notifications
+        .iter()
+        .enumerate()
+        .filter(|(_, n)| filter_by_type(n))
+        .filter(|(_, n)| filter_by_state(n))
+        .filter(|(_, n)| filter_by_repo(n))
+        .filter(|(_, n)| filter_by_label(n))
+        .filter(|(_, n)| filter_by_age(n))
+        .filter(|(_, n)| filter_by_reason(n))
+        .map(|(i, _)| i)
+        .collect();
+
+    Ok(notification_indices)
+}
+
+/// Search issues and pull requests, like `search is:open label:bug`.
+pub async fn search(args: Vec<String>) -> Result<Vec<Notification>, String> {
+    if args.is_empty() {
+        return Err("search needs a query".to_string());
+    }
+    crate::network::methods::search(&octocrab::instance(), &args.join(" "))
+        .await
+        .map_err(|err| format!("Search failed: {}", err.report()))
+}
+
+/// Issues and pull requests of a repository, like
+/// `repo helix-editor/helix issues open`.
+pub async fn repo(args: Vec<String>) -> Result<Vec<Notification>, String> {
+    let (repo, filters) = match args.split_first() {
+        Some((repo, filters)) => (repo, filters),
+        None => return Err("Usage: repo owner/name [issues|prs] [open|closed|merged]".to_string()),
+    };
+    match repo.split_once('/') {
+        Some((owner, name)) if !owner.is_empty() && !name.is_empty() && !name.contains('/') => {}
+        _ => return Err(format!("Invalid repository `{repo}`, expected owner/name")),
+    }
+
+    let mut query = format!("repo:{repo}");
+    for filter in filters {
+        let qualifier = match filter.as_str() {
+            "issues" => "is:issue",
+            "prs" => "is:pr",
+            "open" => "is:open",
+            "closed" => "is:closed",
+            "merged" => "is:merged",
+            _ => return Err(format!("Invalid filter `{filter}`")),
+        };
+        query += " ";
+        query += qualifier;
+    }
+    search(vec![query]).await
+}
+
+pub async fn reload(
+    notifications: &mut Vec<Notification>,
+    backend: &dyn GithubBackend,
+) -> Result<(), String> {
+    println!("Syncing notifications");
+    *notifications = sync_retrying(backend).await.map_err(|err| err.report())?;
+
+    Ok(())
+}
+
+/// [`sync`], but if the rate limit is exceeded wait until it resets and
+/// try once more instead of failing.
+pub async fn sync_retrying(backend: &dyn GithubBackend) -> Result<Vec<Notification>, Error> {
+    match sync(backend).await {
+        Err(Error::GitHubRateLimitExceeded {
+            reset: Some(reset), ..
+        }) => {
+            let secs = (reset - Utc::now()).num_seconds().max(0);
+            println!(
+                "GitHub rate limit exceeded, retrying when it resets in {}",
+                crate::util::format_countdown(secs)
+            );
+            // A little extra in case our clock is behind GitHub's
+            tokio::time::sleep(Duration::from_secs(secs as u64 + 5)).await;
+            sync(backend).await
+        }
+        result => result,
+    }
+}
+
+/// When notifications were last synced, set by [`sync`].
+static SYNCED_AT: Mutex<Option<DateTime<Utc>>> = Mutex::new(None);
+
+/// Number of threads updated on GitHub since the last sync, found by the
+/// background check started by [`spawn_alerts`].
+static UNSEEN: AtomicUsize = AtomicUsize::new(0);
+
+/// Filters from the command line that every sync is narrowed to.
+static SCOPE: OnceCell<Vec<String>> = OnceCell::new();
+
+/// Narrow every sync to the notifications matching `filters`, which must
+/// have been checked with [`filter`]. Has no effect after the first call.
+/// A single repository is fetched from its own endpoint rather than
+/// filtering the whole inbox.
+pub fn set_scope(filters: Vec<String>) {
+    let repos: Vec<_> = filters
+        .iter()
+        .filter_map(|f| f.strip_prefix("repo:")?.split_once('/'))
+        .collect();
+    if let [(owner, name)] = repos[..] {
+        methods::set_fetch_repo(RepoMeta {
+            owner: owner.to_string(),
+            name: name.to_string(),
+        });
+    }
+    let _ = SCOPE.set(filters);
+}
+
+/// Fetch all notifications, hiding the ones matched by mute rules and the
+/// ones outside of the [`SCOPE`]. With
+/// [`lazy_details`](crate::config::ListConfig::lazy_details) only the
+/// threads are fetched, the rest is fetched by the pipelines that need it.
+pub async fn sync(backend: &dyn GithubBackend) -> Result<Vec<Notification>, Error> {
+    let mutes = MuteRules::load()?;
+    let scope = SCOPE.get().filter(|scope| !scope.is_empty());
+    if scope.is_some_and(|scope| scope.iter().any(|f| f == "participating")) {
+        methods::set_participating(true);
+    }
+    let started = Utc::now();
+    let (requests, timer) = (stats::totals(), Instant::now());
+    let fetched = match crate::config::get().list.lazy_details {
+        true => backend.threads().await,
+        false => backend.notifications().await,
+    };
+    let mut notifications = match fetched {
+        Ok(notifications) => notifications,
+        Err(err) => return Err(with_rate_limit_reset(&octocrab::instance(), err).await),
+    };
+    stats::set_last_refresh(Refresh {
+        duration: timer.elapsed(),
+        requests: stats::totals().since(&requests),
+    });
+    notifications.retain(|n| !mutes.is_muted(n));
+    if let Some(scope) = scope {
+        if filters_need_details(scope) {
+            let all: Vec<usize> = (0..notifications.len()).collect();
+            fetch_details(&mut notifications, &all, backend).await?;
+        }
+        // The filters were checked when the arguments were parsed
+        let keep: HashSet<usize> = filter(&notifications, scope)
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+        notifications = notifications
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, n)| keep.contains(&i).then_some(n))
+            .collect();
+    }
+    *SYNCED_AT.lock().unwrap() = Some(started);
+    UNSEEN.store(0, Ordering::Relaxed);
+    Ok(notifications)
+}
+
+fn read_char() -> crossterm::Result<char> {
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+    loop {
+        if let Event::Key(event) = crossterm::event::read()? {
+            let KeyEvent {
+                code,
+                mut modifiers,
+            } = event;
+            if let KeyCode::Char(mut ch) = code {
+                if modifiers.contains(KeyModifiers::SHIFT) {
+                    modifiers.remove(KeyModifiers::SHIFT);
+                    ch.make_ascii_uppercase();
+                }
+                if modifiers.is_empty() {
+                    return Ok(ch);
+                }
+            }
+        }
+    }
+}
+
+fn print_notifications(notifications: &[Notification], indices: &[usize]) {
+    print_lines(&notification_lines(notifications, indices));
+}
+
+/// Like [`print_notifications`], but PRs by dependency bots are collapsed
+/// into a single line in place of the first one.
+fn print_notifications_grouped(notifications: &[Notification], indices: &[usize]) {
+    print_lines(&notification_lines_grouped(notifications, indices));
+}
+
+/// Print lines, paging them if they don't fit in the terminal.
+fn print_lines(lines: &[String]) {
+    let text: String = lines.iter().map(|line| format!("{line}\n")).collect();
+    if let Err(err) = crate::pager::print_or_page(&text) {
+        print_error(&err.to_string());
+    }
+}
+
+fn notification_lines(notifications: &[Notification], indices: &[usize]) -> Vec<String> {
+    let mut lines = Vec::with_capacity(indices.len());
+    for i in indices {
+        match notifications.get(*i) {
+            Some(n) => lines.push(format_colored_notification(*i, n)),
+            None => print_error("Invalid notifications list index"),
+        }
+    }
+    lines
+}
+
+fn notification_lines_grouped(notifications: &[Notification], indices: &[usize]) -> Vec<String> {
+    let dependency_bot = |i: &usize| match notifications.get(*i).map(|n| &n.target) {
+        Some(NotificationTarget::PullRequest(pr)) => pr.dependency_bot(),
+        _ => None,
+    };
+    let deps: Vec<usize> = indices
+        .iter()
+        .copied()
+        .filter(|i| dependency_bot(i).is_some())
+        .collect();
+    if deps.len() < 2 {
+        return notification_lines(notifications, indices);
+    }
+
+    let mut bots: Vec<&str> = deps.iter().filter_map(dependency_bot).collect();
+    bots.sort();
+    bots.dedup();
+    let group = format!(
+        "{:2}. {} dependency updates by {} [{}]",
+        deps[0],
+        deps.len(),
+        bots.join(", "),
+        deps.iter()
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join(" "),
+    );
+
+    // The group takes the place of the first dependency update, keeping
+    // the order of the other lines after sorting or reversing
+    let first = indices
+        .iter()
+        .position(|i| *i == deps[0])
+        .unwrap_or_default();
+    let (before, after) = indices.split_at(first);
+    let not_deps = |indices: &[usize]| -> Vec<usize> {
+        indices
+            .iter()
+            .copied()
+            .filter(|i| dependency_bot(i).is_none())
+            .collect()
+    };
+    let (before, after) = (not_deps(before), not_deps(after));
+    let mut lines = notification_lines(notifications, &before);
+    lines.push(group.dark_grey().to_string());
+    lines.extend(notification_lines(notifications, &after));
+    lines
+}
+
+/// Print one line per release notification, newest first.
+fn print_release_digest(notifications: &[Notification]) {
+    let mut releases: Vec<_> = notifications
+        .iter()
+        .filter_map(|n| match n.target {
+            NotificationTarget::Release(ref release) => Some(release),
+            _ => None,
+        })
+        .collect();
+    releases.sort_by_key(|r| std::cmp::Reverse(r.published_at));
+
+    for release in releases {
+        let date = release
+            .published_at
+            .map(|d| crate::util::format_date(&d))
+            .unwrap_or_default();
+        println!(
+            "{repo} {tag} {date} {headline}",
+            repo = format!("{}/{}", release.repo.owner, release.repo.name).bold(),
+            tag = release.tag_name.as_str().blue(),
+            date = date.dark_grey(),
+            headline = release.headline(),
+        );
+    }
+}
+
+/// Notifications grouped by repository as markdown, newest first, to be
+/// pasted into a team update or notes.
+fn markdown_digest(
+    mut records: Vec<NotificationRecord>,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> String {
+    // Formatting can't fail since `Config::parse` rejects invalid formats
+    let format = &crate::config::get().dates.format;
+    let mut digest = format!(
+        "# Notifications from {} to {}\n",
+        since.format(format),
+        until.format(format)
+    );
+    if records.is_empty() {
+        digest += "\nNo notifications.\n";
+    }
+    records.sort_by_key(|r| std::cmp::Reverse(r.updated_at));
+    let mut repos: BTreeMap<&str, Vec<&NotificationRecord>> = BTreeMap::new();
+    for record in &records {
+        repos.entry(&record.repo).or_default().push(record);
+    }
+    for (repo, records) in repos {
+        digest += &format!("\n## {repo}\n\n");
+        for record in records {
+            let title = record.title.replace('[', "\\[").replace(']', "\\]");
+            let title = match record.url {
+                Some(ref url) => format!("[{title}]({url})"),
+                None => title,
+            };
+            let kind = match record.state {
+                Some(state) => format!("{}, {state}", record.kind),
+                None => record.kind.to_string(),
+            };
+            digest += &format!("- {title} ({kind})\n");
+        }
+    }
+    digest
+}
+
+fn format_colored_notification(index: usize, notification: &Notification) -> String {
+    format!("{index:2}. {}", notification.to_colored_string())
+}
+
+pub fn print_error(msg: &str) {
+    println!("{}: {msg}", "Error".red())
+}
+
+fn true_count(bools: &[bool]) -> usize {
+    bools.iter().map(|b| *b as usize).sum()
+}
+
+fn flush_stdout() -> Result<(), String> {
+    std::io::stdout()
+        .flush()
+        .map_err(|_| "Could not flush stdout".to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{Duration, TimeZone};
+
+    use octocrab::models::NotificationId;
+
+    use super::*;
+    use crate::{
+        github::{
+            events::{AuthorAssociation, EventKind, IssueCloser, Label, Reaction, ReactionGroup},
+            Discussion, DiscussionMeta, DiscussionState, DiscussionSuggestedAnswer, InvitationMeta,
+            IssueClosedReason, IssueMeta, IssueState, PullRequestMeta, PullRequestState, User,
+        },
+        network::backend::MockBackend,
+    };
+
+    /// An open issue and a merged PR on helix updated 2 days and an hour
+    /// ago, and a closed issue on octerm updated 10 days ago, with thread
+    /// ids 1 to 3.
+    fn notifications() -> Vec<Notification> {
+        let repo = |owner: &str, name: &str| RepoMeta {
+            owner: owner.to_string(),
+            name: name.to_string(),
+        };
+        let issue = |repo, number, title: &str, state| {
+            NotificationTarget::Issue(IssueMeta {
+                repo,
+                title: title.to_string(),
+                body: String::new(),
+                number,
+                author: User::new("octocat"),
+                state,
+                created_at: Utc::now(),
+                labels: Vec::new(),
+            })
+        };
+        let pr = NotificationTarget::PullRequest(PullRequestMeta {
+            repo: repo("helix-editor", "helix"),
+            title: "Fix crash".to_string(),
+            body: String::new(),
+            number: 2,
+            author: User::new("octocat"),
+            state: PullRequestState::Merged,
+            created_at: Utc::now(),
+            labels: Vec::new(),
+            branch: "fix-crash".to_string(),
+        });
+        let closed = IssueState::Closed(IssueClosedReason::Completed);
+        [
+            (
+                "Issue",
+                Duration::days(2),
+                issue(
+                    repo("helix-editor", "helix"),
+                    1,
+                    "Crash on startup",
+                    IssueState::Open,
+                ),
+            ),
+            ("PullRequest", Duration::hours(1), pr),
+            (
+                "Issue",
+                Duration::days(10),
+                issue(repo("sudormrfbin", "octerm"), 3, "Add a pager", closed),
+            ),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, (kind, age, target))| {
+            let mut notification = Notification::synthetic(kind, Utc::now() - age, target)?;
+            // Synthetic notifications aren't threads, which can't be marked as done
+            notification.inner.id = NotificationId(i as u64 + 1);
+            Ok(notification)
+        })
+        .collect::<crate::error::Result<_>>()
+        .unwrap()
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_filter() {
+        let n = notifications();
+        let filter = |a: &[&str]| filter(&n, &args(a));
+        assert_eq!(filter(&[]).unwrap(), [0, 1, 2]);
+        assert_eq!(filter(&["pr"]).unwrap(), [1]);
+        assert_eq!(filter(&["issue", "open"]).unwrap(), [0]);
+        assert_eq!(filter(&["merged"]).unwrap(), [1]);
+        assert_eq!(filter(&["repo:Helix-Editor/helix"]).unwrap(), [0, 1]);
+        assert_eq!(filter(&["org:sudormrfbin"]).unwrap(), [2]);
+        assert_eq!(filter(&["updated:<1d"]).unwrap(), [1]);
+        assert_eq!(filter(&["updated:>1w"]).unwrap(), [2]);
+
+        assert!(filter(&["pr", "issue"]).is_err());
+        assert!(filter(&["repo:helix"]).is_err());
+        assert!(filter(&["reason:bogus"]).is_err());
+    }
+
+    #[test]
+    fn test_notification_lines_grouped() {
+        let mut n = notifications();
+        if let NotificationTarget::PullRequest(ref mut pr) = n[1].target {
+            pr.author = User::new("dependabot[bot]");
+        }
+        n.push(n[1].clone());
+        let lines: Vec<_> = notification_lines_grouped(&n, &[3, 2, 1, 0])
+            .iter()
+            .map(|line| crate::util::strip_ansi(line))
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], " 3. 2 dependency updates by dependabot [3 1]");
+        assert!(lines[1].starts_with(" 2. "));
+        assert!(lines[2].starts_with(" 0. "));
+    }
+
+    #[tokio::test]
+    async fn test_list() {
+        let n = notifications();
+        assert_eq!(list(&n, args(&["!pr"])).await.unwrap(), [0, 2]);
+        assert_eq!(list(&n, args(&["issue", "!closed"])).await.unwrap(), [0]);
+        assert!(list(&n, args(&["!"])).await.is_err());
+    }
+
+    #[test]
+    fn test_adapters() {
+        let n = notifications();
+        let all = || vec![0, 1, 2];
+        assert_eq!(
+            adapters::sort(&n, all(), &args(&["updated"])).unwrap(),
+            [1, 0, 2]
+        );
+        assert_eq!(
+            adapters::sort(&n, vec![2, 1, 0], &args(&["repo"])).unwrap(),
+            [1, 0, 2]
+        );
+        assert!(adapters::sort(&n, all(), &[]).is_err());
+
+        assert_eq!(
+            adapters::grep(&n, all(), &args(&["CRASH"])).unwrap(),
+            [0, 1]
+        );
+        assert_eq!(adapters::grep(&n, all(), &args(&["/^fix/"])).unwrap(), [1]);
+        assert_eq!(adapters::grep(&n, all(), &args(&["octerm"])).unwrap(), [2]);
+
+        assert_eq!(adapters::head(all(), &args(&["2"])).unwrap(), [0, 1]);
+        assert_eq!(adapters::tail(all(), &args(&["1"])).unwrap(), [2]);
+        assert!(adapters::head(all(), &args(&["two"])).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_execute() {
+        let mut session = Session {
+            notifications: notifications(),
+            last_result: None,
+            json: false,
+            backend: Arc::new(MockBackend::new(notifications())),
+        };
+        assert!(session.execute("count $_").await.is_err());
+        session.execute("list issue | sort updated").await.unwrap();
+        assert_eq!(session.last_result.as_deref(), Some(&[0, 2][..]));
+        session.execute("count $_").await.unwrap();
+        assert_eq!(session.last_result.as_deref(), Some(&[0, 2][..]));
+        session.execute("list pr open").await.unwrap();
+        assert_eq!(session.last_result.as_deref(), Some(&[][..]));
+        session.execute("list participating issue").await.unwrap();
+        assert_eq!(session.last_result.as_deref(), Some(&[0, 2][..]));
+        assert!(session.execute("list !participating").await.is_err());
+
+        assert!(session.execute("assets frob 0").await.is_err());
+        let failure = session
+            .execute("assets download /nonexistent/dir 0")
+            .await
+            .unwrap_err();
+        assert_eq!(failure.message, "/nonexistent/dir is not a directory");
+
+        let failure = session.execute("lst").await.unwrap_err();
+        assert_eq!(failure.code, exit_code::USAGE);
+        let failure = session.execute("list | sort bogus").await.unwrap_err();
+        assert_eq!(failure.code, exit_code::FAILURE);
+
+        let failure = Failure::from(Error::Authentication);
+        assert_eq!(failure.code, exit_code::AUTHENTICATION);
+    }
+
+    #[tokio::test]
+    async fn test_done() {
+        let backend = Arc::new(MockBackend {
+            failing: vec![NotificationId(3)],
+            ..MockBackend::new(notifications())
+        });
+        let mut session = Session {
+            notifications: notifications(),
+            last_result: None,
+            json: false,
+            backend: backend.clone(),
+        };
+        let failure = session.execute("done 0 2").await.unwrap_err();
+        assert!(failure.message.starts_with("1 of 2 notifications"));
+        // The failed notification moves up to index 1
+        assert!(failure.message.contains("1: Add a pager"));
+        assert_eq!(*backend.marked.lock().unwrap(), [NotificationId(1)]);
+        assert_eq!(session.notifications.len(), 2);
+        assert!(session.execute("done 0").await.is_ok());
+        assert_eq!(session.notifications.len(), 1);
+
+        assert!(session.execute("show 0").await.is_ok());
+        assert!(session.execute("show conversation 0").await.is_ok());
+        assert!(session.execute("show expand 0").await.is_ok());
+        let failure = session.execute("show comments 0").await.unwrap_err();
+        assert_eq!(failure.message, "Invalid option `comments`");
+        let failure = session.execute("show 999").await.unwrap_err();
+        assert_eq!(failure.message, "No notification at index 999");
+        assert!(session.execute("done 0 1").await.is_err());
+        assert_eq!(session.notifications.len(), 1);
+        session.execute("reload").await.unwrap();
+        assert_eq!(session.notifications.len(), 3);
+    }
+
+    #[test]
+    fn test_markdown_digest() {
+        let n = notifications();
+        let mut records: Vec<_> = n.iter().map(|n| NotificationRecord::new(n, None)).collect();
+        records[0].url = Some("https://github.com/helix-editor/helix/issues/1".to_string());
+        records[0].title = "Crash on [startup]".to_string();
+        let until = Utc.with_ymd_and_hms(2026, 10, 17, 12, 0, 0).unwrap();
+        let digest = markdown_digest(records, until - Duration::weeks(1), until);
+        let mut lines = digest.lines();
+        assert_eq!(
+            lines.next(),
+            Some("# Notifications from 10 Oct 2026 to 17 Oct 2026")
+        );
+        let lines: Vec<_> = lines.filter(|l| l.starts_with(['#', '-'])).collect();
+        assert_eq!(lines[0], "## helix-editor/helix");
+        assert_eq!(lines[1], "- Fix crash (pr, merged)");
+        assert_eq!(
+            lines[2],
+            "- [Crash on \\[startup\\]](https://github.com/helix-editor/helix/issues/1) (issue, open)"
+        );
+        assert_eq!(lines[3], "## sudormrfbin/octerm");
+        assert!(markdown_digest(Vec::new(), until, until).contains("No notifications."));
+    }
+
+    #[tokio::test]
+    async fn test_lazy_details() {
+        let backend = Arc::new(MockBackend {
+            lazy: true,
+            ..MockBackend::new(notifications())
+        });
+        let mut session = Session {
+            notifications: backend.threads().await.unwrap(),
+            last_result: None,
+            json: false,
+            backend: backend.clone(),
+        };
+        let pending = |session: &Session| {
+            session
+                .notifications
+                .iter()
+                .filter(|n| matches!(n.target, NotificationTarget::Pending))
+                .count()
+        };
+        assert_eq!(pending(&session), 3);
+
+        session
+            .execute("list repo:helix-editor/helix | head 1")
+            .await
+            .unwrap();
+        assert_eq!(*backend.detailed.lock().unwrap(), [NotificationId(1)]);
+        assert_eq!(pending(&session), 2);
+
+        session.execute("count 2").await.unwrap();
+        assert_eq!(pending(&session), 1);
+        session.execute("list issue").await.unwrap();
+        assert_eq!(session.last_result.as_deref(), Some(&[0, 2][..]));
+        assert_eq!(pending(&session), 0);
+
+        assert!(!filters_need_details(&args(&["!repo:a/b", "updated:>1d"])));
+        assert!(filters_need_details(&args(&["repo:a/b", "!open"])));
+    }
+
+    #[tokio::test]
+    async fn test_show_snapshot() {
+        let mut backend = MockBackend::new(notifications());
+        let ago = |days| Utc::now() - Duration::days(days);
+        let comment = |body: &str, reactions| EventKind::Commented {
+            body: body.to_string(),
+            reactions,
+            minimized: None,
+            edited: false,
+            association: AuthorAssociation::Contributor,
+        };
+        backend.timelines.insert(
+            "helix-editor/helix#1".to_string(),
+            vec![
+                comment(
+                    "Same here, with `hx --health`:\n\n```\nthread 'main' panicked\n```",
+                    vec![ReactionGroup {
+                        reaction: Reaction::ThumbsUp,
+                        count: 4,
+                    }],
+                )
+                .with(User::new("pascalkuthe"), ago(2)),
+                EventKind::Labeled {
+                    label: Label {
+                        name: "C-bug".to_string(),
+                        color: "d73a4a".to_string(),
+                    },
+                }
+                .with(User::new("archseer"), ago(1)),
+                EventKind::Closed {
+                    closer: Some(IssueCloser::PullRequest { number: 2 }),
+                }
+                .with(User::new("archseer"), ago(1)),
+            ],
+        );
+        let notifications = notifications();
+        let detail = consumers::detail(0, &notifications[0], None, Default::default(), &backend)
+            .await
+            .unwrap();
+        crate::snapshot::assert_snapshot("show_issue", &detail);
+    }
+
+    #[tokio::test]
+    async fn test_show_discussion_and_invitation() {
+        let repo = RepoMeta {
+            owner: "helix-editor".to_string(),
+            name: "helix".to_string(),
+        };
+        let meta = DiscussionMeta {
+            repo: repo.clone(),
+            title: "Plugin system".to_string(),
+            number: 4,
+            state: DiscussionState::Answered,
+        };
+        let mut backend = MockBackend::new(notifications());
+        backend.discussions.insert(
+            "helix-editor/helix#4".to_string(),
+            Discussion {
+                meta: meta.clone(),
+                author: User::new("archseer"),
+                upvotes: 7,
+                body: "Which language should plugins use?".to_string(),
+                created_at: Utc::now(),
+                suggested_answers: vec![DiscussionSuggestedAnswer {
+                    author: User::new("pascalkuthe"),
+                    is_answer: true,
+                    upvotes: 3,
+                    body: "Scheme".to_string(),
+                    created_at: Utc::now(),
+                    replies: Vec::new(),
+                }],
+            },
+        );
+        let mut n = notifications();
+        n[0].target = NotificationTarget::Discussion(meta);
+        n[1].target = NotificationTarget::Invitation(InvitationMeta {
+            repo,
+            id: 1,
+            inviter: User::new("archseer"),
+            permissions: "write".to_string(),
+            expired: false,
+            html_url: String::new(),
+        });
+
+        let detail = |i: usize| consumers::detail(i, &n[i], None, Default::default(), &backend);
+        let discussion = crate::util::strip_ansi(&detail(0).await.unwrap());
+        assert!(discussion.contains("Which language should plugins use?"));
+        assert!(discussion.contains("✓ Answer"));
+        assert!(discussion.contains("Scheme"));
+        let invitation = crate::util::strip_ansi(&detail(1).await.unwrap());
+        assert!(invitation.contains("archseer invited you to helix-editor/helix with write access"));
+        assert!(invitation.contains("`accept 1`"));
+    }
+
+    #[tokio::test]
+    async fn test_expand_bot_comment() {
+        let mut backend = MockBackend::new(notifications());
+        let comment = EventKind::Commented {
+            body: "Coverage decreased by 0.1%".to_string(),
+            reactions: Vec::new(),
+            minimized: None,
+            edited: false,
+            association: AuthorAssociation::None,
+        };
+        backend.timelines.insert(
+            "helix-editor/helix#1".to_string(),
+            vec![comment.with(User::new("codecov[bot]"), Utc::now())],
+        );
+        let notifications = notifications();
+        let detail = |options| consumers::detail(0, &notifications[0], None, options, &backend);
+
+        let collapsed = detail(Default::default()).await.unwrap();
+        assert!(!collapsed.contains("Coverage decreased"));
+        let options = consumers::DetailOptions {
+            expand: true,
+            ..Default::default()
+        };
+        let expanded = detail(options).await.unwrap();
+        assert!(expanded.contains("Coverage decreased"));
+    }
+}
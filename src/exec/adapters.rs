@@ -0,0 +1,177 @@
+use crate::{github::Notification, network::backend::GithubBackend};
+use crossterm::style::Stylize;
+
+use super::{consumers, flush_stdout, format_colored_notification, read_char};
+
+pub async fn confirm(
+    notifications: &[Notification],
+    filter: &[usize],
+    backend: &dyn GithubBackend,
+) -> Result<Vec<usize>, String> {
+    crossterm::terminal::enable_raw_mode().map_err(|_| "Could not enable terminal raw mode")?;
+
+    let result = confirm_helper(notifications, filter, backend).await;
+
+    // TODO: Register panic handler to always disable raw mode
+    crossterm::terminal::disable_raw_mode().map_err(|_| "Could not disable terminal raw mode")?;
+
+    if result.is_err() {
+        // Reset cursor to beginning of line
+        println!("\r");
+    }
+
+    result
+}
+
+/// Reorder the notifications by `updated` (newest first), `repo`
+/// or `relevance` (the default order of `list`).
+pub fn sort(
+    notifications: &[Notification],
+    mut filter: Vec<usize>,
+    args: &[String],
+) -> Result<Vec<usize>, String> {
+    let key = match args {
+        [key] => key.as_str(),
+        [] => return Err("sort needs a key: updated, repo, relevance".to_string()),
+        _ => return Err("sort takes a single key".to_string()),
+    };
+
+    match key {
+        "updated" => filter.sort_by_key(|i| std::cmp::Reverse(notifications[*i].inner.updated_at)),
+        "repo" => filter.sort_by_key(|i| {
+            let repo = &notifications[*i].inner.repository;
+            repo.full_name.clone().unwrap_or_else(|| repo.name.clone())
+        }),
+        "relevance" => filter.sort_by_key(|i| notifications[*i].sorter()),
+        _ => return Err(format!("Invalid sort key `{key}`")),
+    }
+
+    Ok(filter)
+}
+
+/// Keep notifications whose title or repository contain the pattern,
+/// ignoring case. Patterns wrapped in slashes like `/fla+ky/` are
+/// matched as regular expressions.
+pub fn grep(
+    notifications: &[Notification],
+    mut filter: Vec<usize>,
+    args: &[String],
+) -> Result<Vec<usize>, String> {
+    if args.is_empty() {
+        return Err("grep needs a pattern".to_string());
+    }
+    let pattern = args.join(" ");
+    let pattern = match pattern.strip_prefix('/').and_then(|p| p.strip_suffix('/')) {
+        Some(re) => re.to_string(),
+        None => regex::escape(&pattern),
+    };
+    let re = regex::RegexBuilder::new(&pattern)
+        .case_insensitive(true)
+        .build()
+        .map_err(|err| format!("Invalid pattern: {err}"))?;
+
+    filter.retain(|i| {
+        let inner = &notifications[*i].inner;
+        re.is_match(&inner.subject.title)
+            || re.is_match(
+                inner
+                    .repository
+                    .full_name
+                    .as_ref()
+                    .unwrap_or(&inner.repository.name),
+            )
+    });
+    Ok(filter)
+}
+
+/// Keep only the first N notifications.
+pub fn head(mut filter: Vec<usize>, args: &[String]) -> Result<Vec<usize>, String> {
+    filter.truncate(count_arg("head", args)?);
+    Ok(filter)
+}
+
+/// Keep only the last N notifications.
+pub fn tail(mut filter: Vec<usize>, args: &[String]) -> Result<Vec<usize>, String> {
+    let skip = filter.len().saturating_sub(count_arg("tail", args)?);
+    Ok(filter.split_off(skip))
+}
+
+fn count_arg(adapter: &str, args: &[String]) -> Result<usize, String> {
+    match args {
+        [count] => count
+            .parse()
+            .map_err(|_| format!("Invalid count for {adapter}: `{count}`")),
+        _ => Err(format!("{adapter} takes a single count")),
+    }
+}
+
+async fn confirm_helper(
+    notifications: &[Notification],
+    filter: &[usize],
+    backend: &dyn GithubBackend,
+) -> Result<Vec<usize>, String> {
+    if filter.is_empty() {
+        return Ok(Vec::new());
+    }
+    println!("[y]es, [n]o, [a]ll, [d]one, [u]ndo, [s]how, [Q]uit/abort\r");
+
+    // Whether each notification before the current one was confirmed
+    let mut decisions: Vec<bool> = Vec::with_capacity(filter.len());
+    while let Some(&i) = filter.get(decisions.len()) {
+        let progress = format!("{}/{}", decisions.len() + 1, filter.len());
+        let notification = &notifications[i];
+        print!(
+            "{} {}: ",
+            progress.dark_grey(),
+            format_colored_notification(i, notification)
+        );
+        flush_stdout()?;
+
+        let input = read_char().map_err(|_| "Couldn't read input")?;
+        print!("{}", input);
+        flush_stdout()?;
+
+        // Keybindings have been modeled after git add -p
+        match input {
+            'y' => decisions.push(true),
+            'n' => decisions.push(false),
+            // Confirm current notification and all the remaining ones (a),
+            // or skip them (d). These can't be undone, so ask first.
+            'a' | 'd' => {
+                let remaining = filter.len() - decisions.len();
+                let action = if input == 'a' { "Confirm" } else { "Skip" };
+                print!(" {action} all {remaining} remaining? [y/n] ");
+                flush_stdout()?;
+                let answer = read_char().map_err(|_| "Couldn't read input")?;
+                print!("{answer}");
+                if answer == 'y' {
+                    decisions.resize(filter.len(), input == 'a');
+                }
+            }
+            'u' => match decisions.pop() {
+                Some(_) => print!(" (undone)"),
+                None => print!(" (nothing to undo)"),
+            },
+            's' => {
+                println!("\r");
+                let text =
+                    match consumers::detail(i, notification, None, Default::default(), backend)
+                        .await
+                    {
+                        Ok(text) => text,
+                        Err(err) => err.red().to_string(),
+                    };
+                // Raw mode doesn't return the cursor to the start of lines
+                print!("{}", text.trim_end().replace('\n', "\r\n"));
+            }
+            'Q' => return Err("Aborted confirm queue".to_string()),
+            _invalid_input => print!(" (invalid option)"),
+        }
+
+        // Reset cursor to beginning of line
+        println!("\r");
+    }
+
+    let indices = filter.iter().zip(decisions).filter(|(_, yes)| *yes);
+    Ok(indices.map(|(i, _)| *i).collect())
+}
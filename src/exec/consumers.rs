@@ -0,0 +1,1141 @@
+use crate::{
+    drafts::Drafts,
+    error::Error,
+    github::{
+        events::{format_reactions, Event, EventKind, Label, TimelineFilter},
+        Notification, NotificationRecord, NotificationTarget, RepoMeta, User,
+    },
+    graphics::Protocol,
+    markdown::{CodeBlock, Link, RenderOptions},
+    mutes::MuteRules,
+    network::backend::GithubBackend,
+    network::methods::{
+        comment_on_issue, commit, compare_with_previous_release, create_issue, download_asset,
+        download_image, gist, open_notification_in_browser, resolve_html_url,
+        respond_to_invitation, unsubscribe_from_thread, workflow_job_log,
+    },
+    util::{copy_to_clipboard, csv_field, format_date, open_url_in_browser},
+};
+use crossterm::{style::Stylize, tty::IsTty};
+use futures::FutureExt;
+
+/// Number of lines printed from the end of a job log.
+const LOG_TAIL_LINES: usize = 50;
+
+/// Print the number of notifications, or a frequency table when grouped
+/// by `repo`, `type` or `reason`.
+pub async fn count(
+    notifications: &mut [Notification],
+    filter: &[usize],
+    opts: &[String],
+) -> Result<(), String> {
+    let key: fn(&Notification) -> String = match opts {
+        [] => {
+            println!("{}", filter.len());
+            return Ok(());
+        }
+        [key] if key == "repo" => |n| {
+            let repo = &n.inner.repository;
+            repo.full_name.clone().unwrap_or_else(|| repo.name.clone())
+        },
+        [key] if key == "type" => |n| n.target.kind().to_string(),
+        [key] if key == "reason" => |n| n.inner.reason.clone(),
+        [key] => return Err(format!("Invalid count key `{key}`")),
+        _ => return Err("count takes a single key: repo, type, reason".to_string()),
+    };
+
+    let mut counts = std::collections::HashMap::<String, usize>::new();
+    for i in filter {
+        *counts.entry(key(&notifications[*i])).or_default() += 1;
+    }
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    counts.sort_by(|(k1, c1), (k2, c2)| c2.cmp(c1).then_with(|| k1.cmp(k2)));
+
+    for (key, count) in counts {
+        println!("{count:>4} {key}");
+    }
+    Ok(())
+}
+
+pub async fn open(notifications: &mut [Notification], filter: &[usize]) -> Result<(), String> {
+    let futs = filter
+        .iter()
+        .map(|i| &notifications[*i])
+        .map(open_notification_in_browser);
+    futures::future::join_all(futs)
+        .await
+        .into_iter()
+        .collect::<Result<Vec<()>, Error>>()
+        .map_err(|err| format!("Could not open browser: {}", err.report()))?;
+
+    Ok(())
+}
+
+/// Open the repository pages of the notifications, or one of their
+/// `issues`, `pulls`, `releases` or `actions` tabs. Each repository is
+/// opened only once.
+pub async fn browse(
+    notifications: &mut [Notification],
+    filter: &[usize],
+    opts: &[String],
+) -> Result<(), String> {
+    let tab = match opts {
+        [] => "",
+        [tab] if ["issues", "pulls", "releases", "actions"].contains(&tab.as_str()) => tab,
+        [tab] => return Err(format!("Invalid tab `{tab}`")),
+        _ => return Err("browse takes a single tab".to_string()),
+    };
+
+    let mut urls: Vec<String> = Vec::new();
+    for i in filter {
+        let repo = RepoMeta::from(&notifications[*i].inner.repository);
+        let url = match tab {
+            "" => format!("https://github.com/{}/{}", repo.owner, repo.name),
+            tab => format!("https://github.com/{}/{}/{tab}", repo.owner, repo.name),
+        };
+        if !urls.contains(&url) {
+            urls.push(url);
+        }
+    }
+    for url in urls {
+        open_url_in_browser(url)
+            .map_err(|err| format!("Could not open browser: {}", err.report()))?;
+    }
+    Ok(())
+}
+
+/// Print one JSON object per notification, for use with tools like jq.
+pub async fn json(notifications: &mut [Notification], filter: &[usize]) -> Result<(), String> {
+    for record in records(notifications, filter).await {
+        let json = serde_json::to_string(&record).map_err(|err| err.to_string())?;
+        println!("{json}");
+    }
+    Ok(())
+}
+
+/// Write the notifications to a CSV file: `export csv <file> [columns]`,
+/// where columns are comma separated and default to the ones in the
+/// config.
+pub async fn export(
+    notifications: &mut [Notification],
+    filter: &[usize],
+    opts: &[String],
+) -> Result<(), String> {
+    let (path, columns) = match opts {
+        [format, ..] if format != "csv" => {
+            return Err(format!("Unsupported export format `{format}`"))
+        }
+        [_, path] => (path, crate::config::get().export.columns.clone()),
+        [_, path, columns] => (path, columns.split(',').map(String::from).collect()),
+        _ => return Err("Usage: export csv <file> [columns]".to_string()),
+    };
+    if let Some(column) = columns
+        .iter()
+        .find(|c| !NotificationRecord::COLUMNS.contains(&c.as_str()))
+    {
+        return Err(format!(
+            "Invalid column `{column}`, expected one of {}",
+            NotificationRecord::COLUMNS.join(", ")
+        ));
+    }
+
+    let mut csv = columns.join(",") + "\n";
+    for record in records(notifications, filter).await {
+        let row: Vec<_> = columns
+            .iter()
+            .map(|c| csv_field(&record.field(c).unwrap_or_default()).into_owned())
+            .collect();
+        csv += &(row.join(",") + "\n");
+    }
+    std::fs::write(path, csv).map_err(|err| format!("Could not write {path}: {err}"))?;
+    println!("Exported {} notifications to {path}", filter.len());
+    Ok(())
+}
+
+/// Summaries of the notifications with their html urls resolved.
+pub(super) async fn records(
+    notifications: &[Notification],
+    filter: &[usize],
+) -> Vec<NotificationRecord> {
+    let octo = octocrab::instance();
+    let futs = filter.iter().map(|i| {
+        let notification = &notifications[*i];
+        resolve_html_url(&octo, notification)
+            .map(|url| NotificationRecord::new(notification, url.ok()))
+    });
+    futures::future::join_all(futs).await
+}
+
+pub async fn logs(notifications: &mut [Notification], filter: &[usize]) -> Result<(), String> {
+    let octo = octocrab::instance();
+    let builds = filter
+        .iter()
+        .filter_map(|i| match notifications[*i].target {
+            NotificationTarget::CiBuild(ref build) => Some(build),
+            _ => None,
+        });
+
+    for build in builds {
+        let jobs = build.run.iter().flat_map(|run| &run.failed_jobs);
+        let mut has_failed_jobs = false;
+        for job in jobs {
+            has_failed_jobs = true;
+            println!("{}", format!("{} / {}", build.workflow, job.name).bold());
+            let log = workflow_job_log(&octo, &build.repo, job.id)
+                .await
+                .map_err(|err| format!("Could not download log: {}", err.report()))?;
+            for line in crate::util::log_tail(&log, LOG_TAIL_LINES) {
+                match line.starts_with("##[error]") {
+                    true => println!("{}", line.red()),
+                    false => println!("{line}"),
+                }
+            }
+        }
+        if !has_failed_jobs {
+            println!("{}: no failed jobs", build);
+        }
+    }
+
+    Ok(())
+}
+
+/// Accept or decline the repository invitations in the list, and mark
+/// their notifications as done.
+pub async fn respond(
+    notifications: &mut Vec<Notification>,
+    filter: &[usize],
+    accept: bool,
+    backend: &dyn GithubBackend,
+) -> Result<(), String> {
+    let octo = octocrab::instance();
+    let mut responded = Vec::new();
+    let mut failed = Vec::new();
+    for i in filter {
+        let invitation = match notifications[*i].target {
+            NotificationTarget::Invitation(ref invitation) => invitation,
+            _ => continue,
+        };
+        if let Err(err) = respond_to_invitation(&octo, invitation.id, accept).await {
+            failed.push(format!("  {}: {}", invitation.repo.name, err.report()));
+            continue;
+        }
+        println!(
+            "{} invitation to {} from {}",
+            if accept { "Accepted" } else { "Declined" },
+            invitation.repo.name,
+            invitation.inviter
+        );
+        responded.push(*i);
+    }
+
+    // Invitations that were responded to are done even if others failed
+    let marked = done(notifications, &responded, backend).await;
+    if failed.is_empty() {
+        return marked;
+    }
+    let mut message = format!(
+        "{} of {} invitations could not be responded to:\n{}",
+        failed.len(),
+        failed.len() + responded.len(),
+        failed.join("\n")
+    );
+    if let Err(err) = marked {
+        message += &format!("\n{err}");
+    }
+    Err(message)
+}
+
+/// List the downloadable files of releases, or pick one of them to copy
+/// its url with `assets copy` or save it with `assets download <dir>`.
+pub async fn assets(
+    notifications: &mut [Notification],
+    filter: &[usize],
+    opts: &[String],
+) -> Result<(), String> {
+    let dir = match opts {
+        [] => None,
+        [action] if action == "copy" => None,
+        [action, dir] if action == "download" => Some(std::path::Path::new(dir)),
+        _ => return Err("Usage: assets [copy|download <dir>] <index>...".to_string()),
+    };
+    if let Some(dir) = dir.filter(|dir| !dir.is_dir()) {
+        return Err(format!("{} is not a directory", dir.display()));
+    }
+    let releases = filter
+        .iter()
+        .filter_map(|i| match notifications[*i].target {
+            NotificationTarget::Release(ref release) => Some(release),
+            _ => None,
+        });
+
+    if opts.is_empty() {
+        for release in releases {
+            println!("{}", release.title.as_str().bold());
+            if release.assets.is_empty() {
+                println!("  No assets");
+            }
+            for asset in &release.assets {
+                println!("  {asset}");
+                println!("    {}", asset.download_url.as_str().dark_grey());
+            }
+        }
+        return Ok(());
+    }
+
+    let assets: Vec<_> = releases.flat_map(|release| &release.assets).collect();
+    if assets.is_empty() {
+        println!("No assets");
+        return Ok(());
+    }
+    for (i, asset) in assets.iter().enumerate() {
+        println!("{i:2}. {asset}");
+    }
+    let prompt = match dir {
+        Some(_) => "Download asset",
+        None => "Copy url of asset",
+    };
+    let asset = match pick(prompt, assets.len())? {
+        Some(i) => assets[i],
+        None => return Ok(()),
+    };
+    let dir = match dir {
+        Some(dir) => dir,
+        None => {
+            copy_to_clipboard(&asset.download_url).map_err(|_| "Could not copy to clipboard")?;
+            println!("Copied {}", asset.download_url);
+            return Ok(());
+        }
+    };
+    // Asset names can't contain slashes, but don't trust them with a path
+    let name = std::path::Path::new(&asset.name)
+        .file_name()
+        .ok_or_else(|| format!("Invalid asset name `{}`", asset.name))?;
+    let path = dir.join(name);
+    let bytes = download_asset(&asset.download_url)
+        .await
+        .map_err(|err| err.report())?;
+    std::fs::write(&path, bytes)
+        .map_err(|err| format!("Could not write {}: {err}", path.display()))?;
+    println!("Saved {}", path.display());
+    Ok(())
+}
+
+pub async fn compare(notifications: &mut [Notification], filter: &[usize]) -> Result<(), String> {
+    let octo = octocrab::instance();
+    let releases = filter
+        .iter()
+        .filter_map(|i| match notifications[*i].target {
+            NotificationTarget::Release(ref release) => Some(release),
+            _ => None,
+        });
+
+    for release in releases {
+        let comparison = compare_with_previous_release(&octo, release)
+            .await
+            .map_err(|err| format!("Could not compare releases: {}", err.report()))?;
+        let comparison = match comparison {
+            Some(comparison) => comparison,
+            None => {
+                println!("{}: no previous release", release.title);
+                continue;
+            }
+        };
+
+        println!(
+            "{} {}...{} ({} commits)",
+            release.repo.name.as_str().bold(),
+            comparison.base_tag,
+            comparison.head_tag,
+            comparison.total_commits
+        );
+        println!("  {}", comparison.html_url.as_str().dark_grey());
+        for (sha, subject) in &comparison.commits {
+            println!("  {} {subject}", sha.as_str().yellow());
+        }
+        let prs = comparison.merged_prs();
+        if !prs.is_empty() {
+            let prs: Vec<_> = prs.iter().map(|n| format!("#{n}")).collect();
+            println!("  Merged PRs: {}", prs.join(" "));
+        }
+    }
+
+    Ok(())
+}
+
+/// List the links in the text and comments of a notification, and open
+/// the one picked.
+pub async fn links(
+    notifications: &mut [Notification],
+    filter: &[usize],
+    backend: &dyn GithubBackend,
+) -> Result<(), String> {
+    let notification = match filter {
+        [i] => &notifications[*i],
+        _ => return Err("links takes a single notification".to_string()),
+    };
+    let repo = RepoMeta::from(&notification.inner.repository);
+    let repo_url = format!("https://github.com/{}/{}", repo.owner, repo.name);
+
+    let mut links: Vec<Link> = Vec::new();
+    for body in bodies(notification, backend).await? {
+        for link in crate::markdown::links(&body, &repo_url) {
+            if !links.iter().any(|l| l.url == link.url) {
+                links.push(link);
+            }
+        }
+    }
+    if links.is_empty() {
+        println!("No links found");
+        return Ok(());
+    }
+
+    for (i, link) in links.iter().enumerate() {
+        match link.text == link.url {
+            true => println!("{i:2}. {}", link.url),
+            false => println!("{i:2}. {} {}", link.text, link.url.as_str().dark_grey()),
+        }
+    }
+    let link = match pick("Open link", links.len())? {
+        Some(i) => &links[i],
+        None => return Ok(()),
+    };
+    open_url_in_browser(link.url.clone()).map_err(|err| err.report())
+}
+
+/// List the code blocks in the text and comments of a notification, and
+/// copy the one picked to the clipboard.
+pub async fn copy(
+    notifications: &mut [Notification],
+    filter: &[usize],
+    backend: &dyn GithubBackend,
+) -> Result<(), String> {
+    let notification = match filter {
+        [i] => &notifications[*i],
+        _ => return Err("copy takes a single notification".to_string()),
+    };
+    let blocks: Vec<CodeBlock> = bodies(notification, backend)
+        .await?
+        .iter()
+        .flat_map(|body| crate::markdown::code_blocks(body))
+        .collect();
+    if blocks.is_empty() {
+        println!("No code blocks found");
+        return Ok(());
+    }
+
+    for (i, block) in blocks.iter().enumerate() {
+        let first_line = block.code.lines().next().unwrap_or_default();
+        let lines = format!("[{} lines]", block.code.lines().count());
+        println!(
+            "{i:2}. {} {first_line} {}",
+            block.lang.as_str().yellow(),
+            lines.dark_grey()
+        );
+    }
+    let block = match pick("Copy block", blocks.len())? {
+        Some(i) => &blocks[i],
+        None => return Ok(()),
+    };
+    copy_to_clipboard(&block.code).map_err(|_| "Could not copy to clipboard")?;
+    println!("Copied");
+    Ok(())
+}
+
+/// Ask for the number of an item in a list of `len` items. Returns `None`
+/// if nothing was entered.
+fn pick(prompt: &str, len: usize) -> Result<Option<usize>, String> {
+    print!("{prompt}: ");
+    super::flush_stdout()?;
+    let mut input = String::new();
+    std::io::stdin()
+        .read_line(&mut input)
+        .map_err(|_| "Couldn't read input")?;
+    if input.trim().is_empty() {
+        return Ok(None);
+    }
+    match input.trim().parse::<usize>() {
+        Ok(i) if i < len => Ok(Some(i)),
+        _ => Err("Invalid number".to_string()),
+    }
+}
+
+/// The markdown text of a notification and its comments.
+async fn bodies(
+    notification: &Notification,
+    backend: &dyn GithubBackend,
+) -> Result<Vec<String>, String> {
+    let (meta_body, events) = match notification.target {
+        NotificationTarget::Issue(ref issue) => (
+            issue.body.clone(),
+            backend.issue_timeline(&issue.repo, issue.number).await,
+        ),
+        NotificationTarget::PullRequest(ref pr) => (
+            pr.body.clone(),
+            backend.pr_timeline(&pr.repo, pr.number).await,
+        ),
+        NotificationTarget::Release(ref release) => (release.body.clone(), Ok(None)),
+        NotificationTarget::Commit(ref commit) => (commit.message.clone(), Ok(None)),
+        _ => return Err("Notification has no text".to_string()),
+    };
+    let events = events.map_err(|err| format!("Could not fetch comments: {}", err.report()))?;
+
+    let mut bodies = vec![meta_body];
+    bodies.extend(
+        events
+            .iter()
+            .flatten()
+            .filter_map(|e| e.body())
+            .map(String::from),
+    );
+    Ok(bodies)
+}
+
+/// Print the rendered text of a notification followed by its comments
+/// or timeline.
+pub async fn show(
+    notifications: &mut [Notification],
+    filter: &[usize],
+    opts: &[String],
+    backend: &dyn GithubBackend,
+) -> Result<(), String> {
+    let index = match filter {
+        [i] => *i,
+        _ => return Err("show takes a single notification".to_string()),
+    };
+    let options = DetailOptions::parse(opts)?;
+    // Escape sequences of images would end up as garbage in a pipe or a file
+    let terminal = std::io::stdout().is_tty() && crate::util::colors_enabled();
+    let images = match crate::config::get().markdown.images && terminal {
+        true => Protocol::detect(),
+        false => None,
+    };
+    let text = detail(index, &notifications[index], images, options, backend).await?;
+    match images {
+        // Images are drawn where the cursor is, so they can't be paged
+        Some(_) => print!("{text}"),
+        None => crate::pager::print_or_page(&text).map_err(|err| err.report())?,
+    }
+    Ok(())
+}
+
+/// Show the same text as `show` in a full screen, scrollable view and
+/// return to the prompt on quit.
+pub async fn view(
+    notifications: &mut [Notification],
+    filter: &[usize],
+    opts: &[String],
+    backend: &dyn GithubBackend,
+) -> Result<(), String> {
+    let index = match filter {
+        [i] => *i,
+        _ => return Err("view takes a single notification".to_string()),
+    };
+    let options = DetailOptions::parse(opts)?;
+    // Images can't be scrolled along with the text.
+    let text = detail(index, &notifications[index], None, options, backend).await?;
+    crate::pager::page(&text).map_err(|err| err.report())
+}
+
+/// How [`detail`] renders a notification, from the options of `show` and
+/// `view`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DetailOptions {
+    /// Only the timeline events that pass the filter are shown.
+    pub timeline: TimelineFilter,
+    /// Render the contents of `<details>` blocks and of bot comments that
+    /// are otherwise collapsed, with the `expand` option.
+    pub expand: bool,
+}
+
+impl DetailOptions {
+    pub fn parse(opts: &[String]) -> Result<Self, String> {
+        let mut options = Self::default();
+        for opt in opts {
+            match TimelineFilter::from_name(opt) {
+                Some(timeline) => options.timeline = timeline,
+                None if opt == "expand" => options.expand = true,
+                None => return Err(format!("Invalid option `{opt}`")),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Render the text of a notification followed by its comments or
+/// timeline. Images are displayed inline with the given protocol.
+pub async fn detail(
+    index: usize,
+    notification: &Notification,
+    images: Option<Protocol>,
+    options: DetailOptions,
+    backend: &dyn GithubBackend,
+) -> Result<String, String> {
+    let mut detail = Detail {
+        out: String::new(),
+        images,
+        expand: options.expand,
+    };
+    detail.line(super::format_colored_notification(index, notification));
+    let thread = match notification.target {
+        NotificationTarget::Issue(ref issue) => Some((&issue.repo, issue.number)),
+        NotificationTarget::PullRequest(ref pr) => Some((&pr.repo, pr.number)),
+        _ => None,
+    };
+    if let (Some((repo, number)), Ok(drafts)) = (thread, Drafts::load()) {
+        if drafts.get(&Drafts::key(repo, number)).is_some() {
+            let hint = format!("Unsent comment draft, continue it with `comment {index}`");
+            detail.line(hint.dark_yellow());
+        }
+    }
+
+    let octo = octocrab::instance();
+    let comments_err = |err: Error| format!("Could not fetch comments: {}", err.report());
+    match notification.target {
+        NotificationTarget::Issue(ref issue) => {
+            detail.labels(&issue.labels);
+            detail.byline(&issue.author, &issue.created_at);
+            detail.markdown(&issue.body, "").await;
+            let events = backend
+                .issue_timeline(&issue.repo, issue.number)
+                .await
+                .map_err(comments_err)?;
+            for event in options
+                .timeline
+                .apply(events.as_deref().unwrap_or_default())
+            {
+                detail.event(event).await;
+            }
+        }
+        NotificationTarget::PullRequest(ref pr) => {
+            detail.labels(&pr.labels);
+            detail.byline(&pr.author, &pr.created_at);
+            detail.markdown(&pr.body, "").await;
+            let events = backend
+                .pr_timeline(&pr.repo, pr.number)
+                .await
+                .map_err(comments_err)?;
+            for event in options
+                .timeline
+                .apply(events.as_deref().unwrap_or_default())
+            {
+                detail.event(event).await;
+            }
+        }
+        NotificationTarget::Release(ref release) => {
+            if let Some(ref published_at) = release.published_at {
+                detail.byline(&User::new(release.author.as_str()), published_at);
+            }
+            detail.markdown(&release.body, "").await;
+            if !release.assets.is_empty() {
+                detail.line("");
+                detail.line("Assets".bold());
+                for asset in &release.assets {
+                    detail.line(format!("  {asset}"));
+                    detail.line(format!("    {}", asset.download_url.as_str().dark_grey()));
+                }
+            }
+        }
+        NotificationTarget::Commit(ref meta) => {
+            detail.markdown(&meta.message, "").await;
+            let commit = commit(&octo, meta.clone()).await.map_err(comments_err)?;
+            for comment in commit.comments {
+                detail.line("");
+                detail.byline(&comment.author, &comment.created_at);
+                if let (Some(path), Some(line)) = (comment.path, comment.line) {
+                    detail.line(format!("{path}:{line}").dark_grey());
+                }
+                detail.markdown(&comment.body, "  ").await;
+            }
+        }
+        NotificationTarget::Gist(ref meta) => {
+            detail.line(&meta.description);
+            let gist = gist(&octo, meta.clone()).await.map_err(comments_err)?;
+            for comment in gist.comments {
+                detail.line("");
+                detail.byline(&comment.author, &comment.created_at);
+                detail.markdown(&comment.body, "  ").await;
+            }
+        }
+        NotificationTarget::SecurityAlert(ref alert) => {
+            detail.line(format!("{} severity: {}", alert.severity, alert.summary));
+        }
+        NotificationTarget::CiBuild(ref build) => detail.line(build),
+        NotificationTarget::Discussion(ref meta) => {
+            let discussion = backend
+                .discussion(meta.clone())
+                .await
+                .map_err(comments_err)?
+                .ok_or("Could not find the discussion")?;
+            detail.byline(&discussion.author, &discussion.created_at);
+            detail.markdown(&discussion.body, "").await;
+            detail.line(format!("▲ {}", discussion.upvotes).dark_grey());
+            for answer in &discussion.suggested_answers {
+                detail.line("");
+                detail.byline(&answer.author, &answer.created_at);
+                if answer.is_answer {
+                    detail.line("  ✓ Answer".green());
+                }
+                detail.markdown(&answer.body, "  ").await;
+                detail.line(format!("  ▲ {}", answer.upvotes).dark_grey());
+                for reply in &answer.replies {
+                    detail.line("");
+                    detail.line(format!(
+                        "    {} {}",
+                        reply.author.to_string().bold(),
+                        format_date(&reply.created_at).dark_grey()
+                    ));
+                    detail.markdown(&reply.body, "    ").await;
+                }
+            }
+        }
+        NotificationTarget::Invitation(ref invitation) => {
+            detail.line(format!(
+                "{} invited you to {}/{} with {} access",
+                invitation.inviter.to_string().bold(),
+                invitation.repo.owner,
+                invitation.repo.name,
+                invitation.permissions
+            ));
+            match invitation.expired {
+                true => detail.line("The invitation has expired".dark_yellow()),
+                false => detail.line(
+                    format!("Respond with `accept {index}` or `decline {index}`").dark_grey(),
+                ),
+            }
+        }
+        _ => return Err("Notification has no text".to_string()),
+    }
+    Ok(detail.out)
+}
+
+/// Text of a notification being rendered by [`detail`].
+struct Detail {
+    out: String,
+    images: Option<Protocol>,
+    expand: bool,
+}
+
+impl Detail {
+    fn line(&mut self, line: impl std::fmt::Display) {
+        self.out += &format!("{line}\n");
+    }
+
+    fn labels(&mut self, labels: &[Label]) {
+        if !labels.is_empty() {
+            let labels: Vec<_> = labels.iter().map(Label::to_colored_string).collect();
+            self.line(labels.join(" "));
+        }
+    }
+
+    fn byline<Tz>(&mut self, author: &User, date: &chrono::DateTime<Tz>)
+    where
+        Tz: chrono::TimeZone,
+        Tz::Offset: std::fmt::Display,
+    {
+        self.line(format!(
+            "{} {}",
+            author.to_string().bold(),
+            format_date(date).dark_grey()
+        ));
+    }
+
+    /// A timeline event along with the text of comments and reviews.
+    /// Bot comments that should be collapsed only get the single line,
+    /// unless everything is expanded.
+    async fn event(&mut self, event: &Event) {
+        let (reactions, association) = match event.kind {
+            EventKind::Commented {
+                ref reactions,
+                association,
+                ..
+            }
+            | EventKind::Reviewed {
+                ref reactions,
+                association,
+                ..
+            } => (reactions.as_slice(), association.badge()),
+            _ => (&[][..], None),
+        };
+
+        self.line("");
+        let badge = association.map(|b| format!(" [{b}]")).unwrap_or_default();
+        self.line(format!(
+            "{}{} {}",
+            event.to_string().dark_grey(),
+            badge.dark_grey(),
+            format_date(&event.created_at).dark_grey()
+        ));
+        let hidden = matches!(
+            event.kind,
+            EventKind::Commented {
+                minimized: Some(_),
+                ..
+            }
+        );
+        if (event.is_collapsed_bot_comment() && !self.expand) || hidden {
+            return;
+        }
+        if let Some(body) = event.body() {
+            self.markdown(body, "  ").await;
+        }
+        if !reactions.is_empty() {
+            self.line(format!("  {}", format_reactions(reactions)));
+        }
+    }
+
+    async fn markdown(&mut self, markdown: &str, indent: &str) {
+        let options = RenderOptions {
+            expand_details: self.expand,
+            ..Default::default()
+        };
+        for line in crate::markdown::render_with(markdown, options) {
+            self.line(format!("{indent}{}", line.to_ansi_string()));
+            let (protocol, url) = match (self.images, &line.image) {
+                (Some(protocol), Some(url)) => (protocol, url),
+                _ => continue,
+            };
+            // The text placeholder has already been added, so failing to
+            // show the image is not an error.
+            if let Ok(image) = download_image(url).await {
+                if let Some(sequence) = protocol.escape_sequence(&image) {
+                    self.line(sequence);
+                }
+            }
+        }
+    }
+}
+
+/// Write a comment in `$EDITOR` and post it. The comment is kept as a
+/// draft if it can't be posted, and offered again on the next attempt.
+pub async fn comment(notifications: &mut [Notification], filter: &[usize]) -> Result<(), String> {
+    let index = match filter {
+        [i] => *i,
+        _ => return Err("comment takes a single notification".to_string()),
+    };
+    let (repo, number) = match notifications[index].target {
+        NotificationTarget::Issue(ref issue) => (&issue.repo, issue.number),
+        NotificationTarget::PullRequest(ref pr) => (&pr.repo, pr.number),
+        _ => return Err("Only issues and pull requests can be commented on".to_string()),
+    };
+
+    let mut drafts = Drafts::load().map_err(|err| err.report())?;
+    let key = Drafts::key(repo, number);
+    let mut text = String::new();
+    if let Some(draft) = drafts.get(&key) {
+        print!("Restore the unsent draft? [y/n] ");
+        super::flush_stdout()?;
+        let mut answer = String::new();
+        std::io::stdin()
+            .read_line(&mut answer)
+            .map_err(|_| "Couldn't read input")?;
+        if answer.trim() == "y" {
+            text = draft.to_string();
+        }
+    }
+
+    let save_draft = |drafts: &mut Drafts, text: &str| -> Result<(), String> {
+        drafts.set(key.clone(), text);
+        drafts.save().map_err(|err| err.report())
+    };
+    let path = draft_file("comment");
+    let text = match edit_text(&text, &path) {
+        Ok(text) if text.trim().is_empty() => {
+            drafts.remove(&key);
+            drafts.save().map_err(|err| err.report())?;
+            println!("Comment aborted");
+            return Ok(());
+        }
+        Ok(text) => text,
+        Err(err) => {
+            // Closing the editor with an error still keeps what was written
+            if let Ok(text) = std::fs::read_to_string(&path) {
+                let _ = std::fs::remove_file(&path);
+                save_draft(&mut drafts, &text)?;
+            }
+            return Err(err);
+        }
+    };
+
+    match comment_on_issue(&octocrab::instance(), repo, number, &text).await {
+        Ok(()) => {
+            drafts.remove(&key);
+            drafts.save().map_err(|err| err.report())?;
+            println!("Commented on {key}");
+            Ok(())
+        }
+        Err(err) => {
+            save_draft(&mut drafts, &text)?;
+            Err(format!(
+                "Could not post comment, saved it as a draft: {}",
+                err.report()
+            ))
+        }
+    }
+}
+
+/// Write an issue in `$EDITOR` and open it in `repo`, with the first line
+/// as the title and the rest as the body.
+pub async fn new_issue(repo: &str, labels: Vec<String>) -> Result<(), String> {
+    let repo = match repo.split_once('/') {
+        Some((owner, name)) if !owner.is_empty() && !name.is_empty() && !name.contains('/') => {
+            RepoMeta {
+                owner: owner.to_string(),
+                name: name.to_string(),
+            }
+        }
+        _ => return Err(format!("Invalid repository `{repo}`, expected owner/name")),
+    };
+
+    let path = draft_file("issue");
+    let text = edit_text("", &path);
+    // Unlike comments, issues aren't kept as drafts if the editor fails
+    let _ = std::fs::remove_file(&path);
+    let text = text?;
+    let (title, body) = text.split_once('\n').unwrap_or((&text, ""));
+    let (title, body) = (title.trim(), body.trim());
+    if title.is_empty() {
+        println!("Issue aborted");
+        return Ok(());
+    }
+    let url = create_issue(&octocrab::instance(), &repo, title, body, labels)
+        .await
+        .map_err(|err| format!("Could not open issue: {}", err.report()))?;
+    println!("{url}");
+    Ok(())
+}
+
+/// Temporary file for writing a `kind` of text, like a comment or an issue.
+fn draft_file(kind: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("octerm-{kind}-{}.md", std::process::id()))
+}
+
+/// Let the user edit `text` in `$VISUAL` or `$EDITOR`, falling back to vi,
+/// in the file at `path`. The file is left behind if the editor fails.
+fn edit_text(text: &str, path: &std::path::Path) -> Result<String, String> {
+    std::fs::write(path, text).map_err(|err| format!("Could not create draft: {err}"))?;
+    run_editor(&env_editor(), path, None)?;
+    let text = std::fs::read_to_string(path).map_err(|err| format!("Could not read draft: {err}"));
+    let _ = std::fs::remove_file(path);
+    text
+}
+
+/// The editor from `$VISUAL` or `$EDITOR`, falling back to vi.
+fn env_editor() -> String {
+    std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string())
+}
+
+/// Open `path` in `editor`, optionally from the directory `dir`, and wait
+/// for it to exit. The editor can have arguments, like `code --wait`.
+fn run_editor(
+    editor: &str,
+    path: &std::path::Path,
+    dir: Option<&std::path::Path>,
+) -> Result<(), String> {
+    let mut words = editor.split_whitespace();
+    let program = words.next().ok_or("The editor command is empty")?;
+    let mut command = std::process::Command::new(program);
+    command.args(words).arg(path);
+    if let Some(dir) = dir {
+        command.current_dir(dir);
+    }
+    let status = command
+        .status()
+        .map_err(|err| format!("Could not start {editor}: {err}"))?;
+    if !status.success() {
+        return Err(format!("{editor} exited with {status}"));
+    }
+    Ok(())
+}
+
+/// Open the local checkout of the repository of a notification in the
+/// editor from the config, or a file in the checkout with
+/// `edit <file> <index>`, like one changed by a pull request.
+pub async fn edit(
+    notifications: &mut [Notification],
+    filter: &[usize],
+    opts: &[String],
+) -> Result<(), String> {
+    let notification = match filter {
+        [i] => &notifications[*i],
+        _ => return Err("edit takes a single notification".to_string()),
+    };
+    let repo = RepoMeta::from(&notification.inner.repository);
+    let config = &crate::config::get().checkouts;
+    let checkout = config.path(&repo).ok_or_else(|| {
+        format!(
+            "No checkout of {}/{}, add it to [checkouts.repos] in the config",
+            repo.owner, repo.name
+        )
+    })?;
+    let path = match opts {
+        [] => checkout.clone(),
+        [file] => checkout.join(file),
+        _ => return Err("Usage: edit [file] <index>".to_string()),
+    };
+    if !path.exists() {
+        return Err(format!("{} does not exist", path.display()));
+    }
+
+    let editor = config.editor.clone().unwrap_or_else(env_editor);
+    run_editor(&editor, &path, Some(&checkout))
+}
+
+/// Copy the command that checks out a pull request, from
+/// [`crate::config::CheckoutConfig::pr_command`].
+pub async fn checkout(notifications: &mut [Notification], filter: &[usize]) -> Result<(), String> {
+    let pr = match filter {
+        [i] => match notifications[*i].target {
+            NotificationTarget::PullRequest(ref pr) => pr,
+            _ => return Err("Only pull requests can be checked out".to_string()),
+        },
+        _ => return Err("checkout takes a single notification".to_string()),
+    };
+    let command = crate::config::get().checkouts.pr_command(pr);
+    copy_to_clipboard(&command).map_err(|_| "Could not copy to clipboard")?;
+    println!("Copied `{command}`");
+    Ok(())
+}
+
+/// Comment `@dependabot rebase` on the dependabot PRs in the list.
+pub async fn rebase(notifications: &mut [Notification], filter: &[usize]) -> Result<(), String> {
+    let octo = octocrab::instance();
+    let prs = filter
+        .iter()
+        .filter_map(|i| match notifications[*i].target {
+            NotificationTarget::PullRequest(ref pr)
+                if pr.dependency_bot() == Some("dependabot") =>
+            {
+                Some(pr)
+            }
+            _ => None,
+        });
+    let futs = prs.map(|pr| comment_on_issue(&octo, &pr.repo, pr.number, "@dependabot rebase"));
+    futures::future::join_all(futs)
+        .await
+        .into_iter()
+        .collect::<Result<Vec<()>, Error>>()
+        .map_err(|err| format!("Could not comment on PR: {}", err.report()))?;
+
+    Ok(())
+}
+
+pub async fn unsubscribe(
+    notifications: &mut [Notification],
+    filter: &[usize],
+) -> Result<(), String> {
+    require_threads(notifications, filter, "unsubscribed from")?;
+    let octo = octocrab::instance();
+    let futs = filter
+        .iter()
+        .map(|i| unsubscribe_from_thread(&octo, notifications[*i].inner.id));
+    let results = futures::future::join_all(futs).await;
+    let failed = results.iter().filter(|r| r.is_err()).count();
+
+    println!("Unsubscribed from {} threads", results.len() - failed);
+    if failed > 0 {
+        return Err(format!("Could not unsubscribe from {failed} threads"));
+    }
+    Ok(())
+}
+
+/// Search results and other items that aren't notification threads
+/// can't be acted upon like notifications.
+fn require_threads(
+    notifications: &[Notification],
+    filter: &[usize],
+    action: &str,
+) -> Result<(), String> {
+    match filter.iter().all(|i| notifications[*i].is_thread()) {
+        true => Ok(()),
+        false => Err(format!("Only notifications can be {action}")),
+    }
+}
+
+/// Save a mute rule for each thread, or for its repository with
+/// `mute repo`, and hide everything the new rules match.
+pub async fn mute(
+    notifications: &mut Vec<Notification>,
+    filter: &[usize],
+    opts: &[String],
+) -> Result<(), String> {
+    let mute_repos = match opts {
+        [] => false,
+        [opt] if opt == "thread" => false,
+        [opt] if opt == "repo" => true,
+        _ => return Err("mute takes either thread or repo".to_string()),
+    };
+
+    if !mute_repos {
+        require_threads(notifications, filter, "muted")?;
+    }
+    let mut rules = MuteRules::load().map_err(|err| err.report())?;
+    for notification in filter.iter().map(|i| &notifications[*i]) {
+        match mute_repos {
+            true => {
+                let repo = RepoMeta::from(&notification.inner.repository);
+                rules.mute_repo(&format!("{}/{}", repo.owner, repo.name))
+            }
+            false => rules.mute_thread(notification.inner.id.0),
+        }
+    }
+    rules.save().map_err(|err| err.report())?;
+
+    let before = notifications.len();
+    notifications.retain(|n| !rules.is_muted(n));
+    println!("Muted {} notifications", before - notifications.len());
+    Ok(())
+}
+
+pub async fn done(
+    notifications: &mut Vec<Notification>,
+    filter: &[usize],
+    backend: &dyn GithubBackend,
+) -> Result<(), String> {
+    require_threads(notifications, filter, "marked as done")?;
+    let futs = filter.iter().map(|i| {
+        backend
+            .mark_notification_as_read(notifications[*i].inner.id)
+            .map(|res| (*i, res))
+    });
+    let results = futures::future::join_all(futs).await;
+    let mut marked = Vec::new();
+    let mut failed = Vec::new();
+    for (i, res) in results {
+        match res {
+            Ok(()) => marked.push(i),
+            Err(err) => failed.push((i, err)),
+        }
+    }
+    marked.sort_unstable();
+    // Removing an index twice would drop a notification that wasn't marked
+    marked.dedup();
+
+    // Failed notifications are kept, at their index after the successes
+    // have been removed.
+    let report: Vec<String> = failed
+        .into_iter()
+        .map(|(i, err)| {
+            let shift = marked.iter().filter(|m| **m < i).count();
+            let title = &notifications[i].inner.subject.title;
+            format!("  {}: {title}: {}", i - shift, err.report())
+        })
+        .collect();
+
+    for idx in marked.iter().rev() {
+        // Remove from the end so that indices stay stable as items are removed.
+        notifications.remove(*idx);
+    }
+
+    if !report.is_empty() {
+        return Err(format!(
+            "{} of {} notifications could not be marked as read:\n{}",
+            report.len(),
+            filter.len(),
+            report.join("\n")
+        ));
+    }
+
+    Ok(())
+}
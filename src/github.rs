@@ -5,9 +5,26 @@ use std::fmt::Display;
 use crossterm::style::Stylize;
 use serde::{Deserialize, Serialize};
 
-use self::events::{DateTimeUtc, Event};
+use self::events::{DateTimeUtc, Event, Label};
 
-#[derive(Clone)]
+/// Reasons GitHub gives for sending a notification.
+/// See https://docs.github.com/en/rest/activity/notifications#about-notification-reasons
+pub const NOTIFICATION_REASONS: [&str; 12] = [
+    "assign",
+    "author",
+    "ci_activity",
+    "comment",
+    "invitation",
+    "manual",
+    "mention",
+    "review_requested",
+    "security_alert",
+    "state_change",
+    "subscribed",
+    "team_mention",
+];
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Notification {
     pub inner: octocrab::models::activity::Notification,
     pub target: NotificationTarget,
@@ -20,6 +37,57 @@ impl PartialEq for Notification {
 }
 
 impl Notification {
+    /// Wrap something that isn't a notification thread, like a search
+    /// result, as a notification so that it can go through the same
+    /// pipelines. `kind` is the subject type, like `Issue`.
+    pub fn synthetic(
+        kind: &str,
+        updated_at: DateTimeUtc,
+        target: NotificationTarget,
+    ) -> crate::error::Result<Self> {
+        let (repo, number) = match target {
+            NotificationTarget::Issue(ref i) => (&i.repo, i.number),
+            NotificationTarget::PullRequest(ref p) => (&p.repo, p.number),
+            _ => return Err(crate::error::Error::SyntheticNotification),
+        };
+        let repo_url = format!("https://api.github.com/repos/{}/{}", repo.owner, repo.name);
+        let path = match kind {
+            "PullRequest" => "pulls",
+            _ => "issues",
+        };
+        let inner = serde_json::json!({
+            "id": "0",
+            "repository": {
+                "id": 0,
+                "name": repo.name,
+                "full_name": format!("{}/{}", repo.owner, repo.name),
+                "url": repo_url,
+            },
+            "subject": {
+                "title": target_title(&target),
+                "url": format!("{repo_url}/{path}/{number}"),
+                "latest_comment_url": null,
+                "type": kind,
+            },
+            "reason": "",
+            "unread": false,
+            "updated_at": updated_at,
+            "last_read_at": null,
+            "url": repo_url,
+        });
+        Ok(Self {
+            inner: serde_json::from_value(inner)
+                .map_err(|_| crate::error::Error::SyntheticNotification)?,
+            target,
+        })
+    }
+
+    /// Whether this is an actual notification thread, which can be marked
+    /// as read or unsubscribed from. See [`Notification::synthetic`].
+    pub fn is_thread(&self) -> bool {
+        self.inner.id.0 != 0
+    }
+
     pub fn to_colored_string(&self) -> String {
         let color = crate::util::notif_target_color(&self.target).into();
         let number = self
@@ -27,11 +95,16 @@ impl Notification {
             .number()
             .map(|n| format!("{}{}", "#".dark_grey(), n.to_string().dark_grey()))
             .unwrap_or_default();
+        let title = match crate::config::get().markdown.emoji {
+            true => crate::emoji::replace_shortcodes(&self.inner.subject.title),
+            false => self.inner.subject.title.as_str().into(),
+        };
         format!(
-            "{repo}{number}: {icon} {title}",
+            "{repo}{number}: {icon} {title} {updated}",
             repo = self.inner.repository.name,
             icon = self.target.icon().with(color),
-            title = self.inner.subject.title.as_str().with(color),
+            title = title.with(color),
+            updated = crate::util::format_date(&self.inner.updated_at).dark_grey(),
         )
     }
 
@@ -47,6 +120,8 @@ impl Notification {
                 state: PullRequestState::Merged,
                 ..
             }) => 90,
+            NotificationTarget::Commit(_) => 87,
+            NotificationTarget::Gist(_) => 86,
             NotificationTarget::Discussion(DiscussionMeta {
                 state: DiscussionState::Answered,
                 ..
@@ -75,22 +150,89 @@ impl Notification {
                 state: PullRequestState::Open,
                 ..
             }) => 40,
-            NotificationTarget::CiBuild => 30,
-            NotificationTarget::Unknown => 0,
+            NotificationTarget::Invitation(_) => 35,
+            NotificationTarget::CiBuild(_) => 30,
+            NotificationTarget::Unknown | NotificationTarget::Pending => 0,
+            // Vulnerabilities need attention before anything else
+            NotificationTarget::SecurityAlert(_) => -10,
         };
 
         (irrelavance, std::cmp::Reverse(self.inner.updated_at))
     }
 }
 
-#[derive(Clone)]
+fn target_title(target: &NotificationTarget) -> &str {
+    match target {
+        NotificationTarget::Issue(i) => &i.title,
+        NotificationTarget::PullRequest(p) => &p.title,
+        _ => "",
+    }
+}
+
+/// Flat summary of a notification, used for machine readable output.
+#[derive(Debug, Serialize)]
+pub struct NotificationRecord {
+    pub id: u64,
+    pub repo: String,
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub state: Option<&'static str>,
+    pub title: String,
+    pub url: Option<String>,
+    pub updated_at: DateTimeUtc,
+}
+
+impl NotificationRecord {
+    /// Names of the fields, in the order they are serialized.
+    pub const COLUMNS: [&'static str; 7] =
+        ["id", "repo", "type", "state", "title", "url", "updated_at"];
+
+    /// The value of a field as text, or `None` if there is no such field.
+    pub fn field(&self, column: &str) -> Option<String> {
+        let value = match column {
+            "id" => self.id.to_string(),
+            "repo" => self.repo.clone(),
+            "type" => self.kind.to_string(),
+            "state" => self.state.unwrap_or_default().to_string(),
+            "title" => self.title.clone(),
+            "url" => self.url.clone().unwrap_or_default(),
+            "updated_at" => self.updated_at.to_rfc3339(),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// `url` is the html url of the notification, which has to be resolved
+    /// over the network.
+    pub fn new(notification: &Notification, url: Option<String>) -> Self {
+        let repo = RepoMeta::from(&notification.inner.repository);
+        Self {
+            id: notification.inner.id.0,
+            repo: format!("{}/{}", repo.owner, repo.name),
+            kind: notification.target.kind(),
+            state: notification.target.state(),
+            title: notification.inner.subject.title.clone(),
+            url,
+            updated_at: notification.inner.updated_at,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub enum NotificationTarget {
     Issue(IssueMeta),
     PullRequest(PullRequestMeta),
     Release(ReleaseMeta),
     Discussion(DiscussionMeta),
-    CiBuild,
+    Commit(CommitMeta),
+    Gist(GistMeta),
+    Invitation(InvitationMeta),
+    SecurityAlert(SecurityAlertMeta),
+    CiBuild(CiBuildMeta),
     Unknown,
+    /// Details haven't been fetched yet, see
+    /// [`crate::config::ListConfig::lazy_details`].
+    Pending,
 }
 
 impl NotificationTarget {
@@ -100,8 +242,69 @@ impl NotificationTarget {
             NotificationTarget::PullRequest(ref p) => p.icon(),
             NotificationTarget::Release(ref r) => r.icon(),
             NotificationTarget::Discussion(ref d) => d.icon(),
-            NotificationTarget::CiBuild => "",
+            NotificationTarget::Commit(ref c) => c.icon(),
+            NotificationTarget::Gist(ref g) => g.icon(),
+            NotificationTarget::Invitation(ref i) => i.icon(),
+            NotificationTarget::SecurityAlert(ref a) => a.icon(),
+            NotificationTarget::CiBuild(ref c) => c.icon(),
             NotificationTarget::Unknown => "",
+            NotificationTarget::Pending => "",
+        }
+    }
+
+    /// Short name of the notification type, as used by `list`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            NotificationTarget::Issue(_) => "issue",
+            NotificationTarget::PullRequest(_) => "pr",
+            NotificationTarget::Release(_) => "release",
+            NotificationTarget::Discussion(_) => "discussion",
+            NotificationTarget::Commit(_) => "commit",
+            NotificationTarget::Gist(_) => "gist",
+            NotificationTarget::Invitation(_) => "invitation",
+            NotificationTarget::SecurityAlert(_) => "alert",
+            NotificationTarget::CiBuild(_) => "ci",
+            NotificationTarget::Unknown => "unknown",
+            NotificationTarget::Pending => "pending",
+        }
+    }
+
+    /// Short name of the state of the target, for targets that have one.
+    pub fn state(&self) -> Option<&'static str> {
+        match self {
+            NotificationTarget::Issue(i) => Some(match i.state {
+                IssueState::Open => "open",
+                IssueState::Closed(_) => "closed",
+            }),
+            NotificationTarget::PullRequest(p) => Some(match p.state {
+                PullRequestState::Open => "open",
+                PullRequestState::Closed => "closed",
+                PullRequestState::Merged => "merged",
+            }),
+            NotificationTarget::Discussion(d) => Some(match d.state {
+                DiscussionState::Answered => "answered",
+                DiscussionState::Unanswered => "unanswered",
+            }),
+            NotificationTarget::Invitation(i) => Some(match i.expired {
+                true => "expired",
+                false => "pending",
+            }),
+            NotificationTarget::CiBuild(c) => Some(match c.conclusion {
+                CiConclusion::Success => "success",
+                CiConclusion::Failure => "failure",
+                CiConclusion::Cancelled => "cancelled",
+                CiConclusion::Unknown => "unknown",
+            }),
+            _ => None,
+        }
+    }
+
+    /// Labels of issues and pull requests; empty for other targets.
+    pub fn labels(&self) -> &[Label] {
+        match self {
+            NotificationTarget::Issue(i) => &i.labels,
+            NotificationTarget::PullRequest(p) => &p.labels,
+            _ => &[],
         }
     }
 
@@ -111,13 +314,17 @@ impl NotificationTarget {
             NotificationTarget::PullRequest(p) => Some(p.number),
             NotificationTarget::Release(_) => None,
             NotificationTarget::Discussion(d) => Some(d.number),
-            NotificationTarget::CiBuild => None,
-            NotificationTarget::Unknown => None,
+            NotificationTarget::Commit(_) => None,
+            NotificationTarget::Gist(_) => None,
+            NotificationTarget::Invitation(_) => None,
+            NotificationTarget::SecurityAlert(_) => None,
+            NotificationTarget::CiBuild(_) => None,
+            NotificationTarget::Unknown | NotificationTarget::Pending => None,
         }
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct RepoMeta {
     pub name: String,
     pub owner: String,
@@ -127,10 +334,15 @@ impl From<&octocrab::models::Repository> for RepoMeta {
     fn from(r: &octocrab::models::Repository) -> Self {
         RepoMeta {
             name: r.name.clone(),
+            // Synthetic notifications only have the full name of the repo.
             owner: r
                 .owner
                 .as_ref()
                 .map(|u| u.login.clone())
+                .or_else(|| {
+                    let full_name = r.full_name.as_ref()?;
+                    Some(full_name.split_once('/')?.0.to_string())
+                })
                 .unwrap_or_default(),
         }
     }
@@ -147,9 +359,11 @@ pub struct IssueDeserModel {
     pub state: String,
     pub state_reason: Option<String>,
     pub created_at: DateTimeUtc,
+    #[serde(default)]
+    pub labels: Vec<Label>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct IssueMeta {
     pub repo: RepoMeta,
     pub title: String,
@@ -158,6 +372,7 @@ pub struct IssueMeta {
     pub author: User,
     pub state: IssueState,
     pub created_at: DateTimeUtc,
+    pub labels: Vec<Label>,
 }
 
 impl IssueMeta {
@@ -178,6 +393,7 @@ impl IssueMeta {
             author: issue.author,
             state,
             created_at: issue.created_at,
+            labels: issue.labels,
         }
     }
 }
@@ -191,7 +407,7 @@ impl IssueMeta {
     }
 }
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum IssueState {
     Open,
     Closed(IssueClosedReason),
@@ -207,7 +423,7 @@ impl IssueState {
     }
 }
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum IssueClosedReason {
     // Done, closed, fixed, resolved, etc.
     Completed,
@@ -239,7 +455,7 @@ impl Issue {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct PullRequestMeta {
     pub repo: RepoMeta,
     pub title: String,
@@ -248,6 +464,10 @@ pub struct PullRequestMeta {
     pub author: User,
     pub state: PullRequestState,
     pub created_at: DateTimeUtc,
+    pub labels: Vec<Label>,
+    /// Name of the branch with the changes.
+    #[serde(default)]
+    pub branch: String,
 }
 
 impl PullRequestMeta {
@@ -259,6 +479,15 @@ impl PullRequestMeta {
                 None => PullRequestState::Open,
             },
         };
+        let labels = pr
+            .labels
+            .iter()
+            .flatten()
+            .map(|l| Label {
+                name: l.name.clone(),
+                color: l.color.clone(),
+            })
+            .collect();
         Self {
             repo,
             title: pr.title.clone().unwrap_or_default(),
@@ -270,11 +499,23 @@ impl PullRequestMeta {
             author: pr.user.map(|u| User::from(*u)).unwrap_or_default(),
             state,
             created_at: pr.created_at.unwrap_or_default(),
+            labels,
+            branch: pr.head.ref_field,
         }
     }
 }
 
 impl PullRequestMeta {
+    /// Name of the bot that opened the PR, if it is an automated dependency
+    /// update.
+    pub fn dependency_bot(&self) -> Option<&'static str> {
+        match self.author.login() {
+            "dependabot" | "dependabot-preview" => Some("dependabot"),
+            "renovate" => Some("renovate"),
+            _ => None,
+        }
+    }
+
     pub fn icon(&self) -> &'static str {
         match self.state {
             PullRequestState::Open => "",
@@ -284,7 +525,7 @@ impl PullRequestMeta {
     }
 }
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PullRequestState {
     Open,
     Closed,
@@ -330,39 +571,488 @@ impl PullRequest {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ReleaseMeta {
+    pub repo: RepoMeta,
     pub title: String,
     pub body: String,
     pub author: String,
     pub tag_name: String,
+    pub published_at: Option<DateTimeUtc>,
+    pub assets: Vec<ReleaseAsset>,
+    pub html_url: String,
 }
 
 impl ReleaseMeta {
-    pub fn icon(&self) -> &'static str {
-        ""
-    }
-}
-
-impl From<octocrab::models::repos::Release> for ReleaseMeta {
-    fn from(release: octocrab::models::repos::Release) -> Self {
+    pub fn new(release: octocrab::models::repos::Release, repo: RepoMeta) -> Self {
         let title = release
             .name
             .clone()
             .unwrap_or_else(|| release.tag_name.clone());
         Self {
+            repo,
             title,
             body: release
                 .body
                 .clone()
                 .unwrap_or_else(|| "No description provided.".to_string()),
             author: release.author.login,
+            html_url: release.html_url.to_string(),
             tag_name: release.tag_name,
+            published_at: release.published_at.or(release.created_at),
+            assets: release
+                .assets
+                .into_iter()
+                .map(|asset| ReleaseAsset {
+                    name: asset.name,
+                    size: asset.size as u64,
+                    download_count: asset.download_count as u64,
+                    download_url: asset.browser_download_url.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn icon(&self) -> &'static str {
+        ""
+    }
+
+    /// The first line of text in the release notes, skipping headings
+    /// like `What's Changed`.
+    pub fn headline(&self) -> String {
+        crate::markdown::render(&self.body)
+            .iter()
+            .map(|line| line.plain())
+            .map(|line| line.trim_start_matches("• ").trim().to_string())
+            .find(|line| !line.is_empty() && !line.starts_with('#'))
+            .unwrap_or_default()
+    }
+}
+
+/// The changes between a release and the one before it.
+pub struct ReleaseComparison {
+    pub base_tag: String,
+    pub head_tag: String,
+    pub total_commits: usize,
+    /// Short sha and subject of each commit, oldest first.
+    pub commits: Vec<(String, String)>,
+    pub html_url: String,
+}
+
+impl ReleaseComparison {
+    /// Numbers of the PRs merged between the releases, as found in the
+    /// commit subjects of merge and squash commits.
+    pub fn merged_prs(&self) -> Vec<usize> {
+        self.commits
+            .iter()
+            .filter_map(|(_, subject)| {
+                let number = match subject.strip_prefix("Merge pull request #") {
+                    Some(rest) => rest.split(' ').next()?,
+                    None => subject.rsplit_once("(#")?.1.strip_suffix(')')?,
+                };
+                number.parse().ok()
+            })
+            .collect()
+    }
+}
+
+/// A file uploaded to a release.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ReleaseAsset {
+    pub name: String,
+    /// Size in bytes.
+    pub size: u64,
+    pub download_count: u64,
+    pub download_url: String,
+}
+
+impl Display for ReleaseAsset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({}, {} downloads)",
+            self.name,
+            crate::util::format_size(self.size),
+            self.download_count
+        )
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CommitMeta {
+    pub repo: RepoMeta,
+    pub sha: String,
+    pub message: String,
+    pub author: User,
+    pub html_url: String,
+}
+
+impl CommitMeta {
+    pub fn new(commit: CommitDeserModel, repo: RepoMeta) -> Self {
+        Self {
+            repo,
+            sha: commit.sha,
+            message: commit.commit.message,
+            author: commit.author.unwrap_or_default(),
+            html_url: commit.html_url,
+        }
+    }
+
+    pub fn icon(&self) -> &'static str {
+        ""
+    }
+
+    /// The sha shortened to the length git uses by default.
+    pub fn short_sha(&self) -> &str {
+        self.sha.get(..7).unwrap_or(&self.sha)
+    }
+
+    /// The first line of the commit message.
+    pub fn subject(&self) -> &str {
+        self.message.lines().next().unwrap_or_default()
+    }
+}
+
+/// A struct used solely for deserializing json from calling the commit API.
+#[derive(Deserialize)]
+pub struct CommitDeserModel {
+    pub sha: String,
+    pub html_url: String,
+    pub commit: CommitDetailsDeserModel,
+    /// The GitHub account of the author, if the commit email is linked to one.
+    pub author: Option<User>,
+}
+
+#[derive(Deserialize)]
+pub struct CommitDetailsDeserModel {
+    pub message: String,
+}
+
+pub struct Commit {
+    pub meta: CommitMeta,
+    pub comments: Vec<CommitComment>,
+}
+
+#[derive(Deserialize)]
+pub struct CommitComment {
+    #[serde(rename = "user")]
+    pub author: User,
+    pub body: String,
+    /// File the comment was made on, if it is a line comment.
+    pub path: Option<String>,
+    pub line: Option<usize>,
+    pub created_at: DateTimeUtc,
+    pub html_url: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GistMeta {
+    pub id: String,
+    pub description: String,
+    pub owner: User,
+    pub files: Vec<String>,
+    pub html_url: String,
+}
+
+impl GistMeta {
+    pub fn icon(&self) -> &'static str {
+        ""
+    }
+}
+
+impl From<GistDeserModel> for GistMeta {
+    fn from(gist: GistDeserModel) -> Self {
+        Self {
+            id: gist.id,
+            description: gist.description.unwrap_or_default(),
+            owner: gist.owner.unwrap_or_default(),
+            files: gist.files.into_keys().collect(),
+            html_url: gist.html_url,
+        }
+    }
+}
+
+/// A struct used solely for deserializing json from calling the gist API.
+#[derive(Deserialize)]
+pub struct GistDeserModel {
+    pub id: String,
+    pub description: Option<String>,
+    pub owner: Option<User>,
+    /// Files of the gist keyed by their filename.
+    pub files: std::collections::BTreeMap<String, serde::de::IgnoredAny>,
+    pub html_url: String,
+}
+
+pub struct Gist {
+    pub meta: GistMeta,
+    pub comments: Vec<GistComment>,
+}
+
+#[derive(Deserialize)]
+pub struct GistComment {
+    pub id: u64,
+    #[serde(rename = "user")]
+    pub author: User,
+    pub body: String,
+    pub created_at: DateTimeUtc,
+}
+
+/// An invitation to collaborate on a repository.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct InvitationMeta {
+    pub repo: RepoMeta,
+    pub id: u64,
+    pub inviter: User,
+    /// Permission granted on accepting, like `write` or `admin`.
+    pub permissions: String,
+    pub expired: bool,
+    pub html_url: String,
+}
+
+impl InvitationMeta {
+    pub fn new(invitation: InvitationDeserModel, repo: RepoMeta) -> Self {
+        Self {
+            repo,
+            id: invitation.id,
+            inviter: invitation.inviter,
+            permissions: invitation.permissions,
+            expired: invitation.expired,
+            html_url: invitation.html_url,
+        }
+    }
+
+    pub fn icon(&self) -> &'static str {
+        ""
+    }
+}
+
+/// A struct used solely for deserializing json from calling the repository
+/// invitations API.
+#[derive(Deserialize)]
+pub struct InvitationDeserModel {
+    pub id: u64,
+    pub inviter: User,
+    pub permissions: String,
+    #[serde(default)]
+    pub expired: bool,
+    pub html_url: String,
+    pub repository: InvitationRepoDeserModel,
+}
+
+#[derive(Deserialize)]
+pub struct InvitationRepoDeserModel {
+    pub full_name: String,
+}
+
+/// A Dependabot alert for a vulnerable dependency of a repository.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SecurityAlertMeta {
+    pub repo: RepoMeta,
+    pub number: usize,
+    pub package: String,
+    pub severity: Severity,
+    /// Summary of the security advisory.
+    pub summary: String,
+    pub ghsa_id: String,
+    pub vulnerable_range: String,
+    pub patched_version: Option<String>,
+    /// Path of the manifest file that pulls in the package.
+    pub manifest: String,
+}
+
+impl SecurityAlertMeta {
+    pub fn icon(&self) -> &'static str {
+        ""
+    }
+
+    pub fn html_url(&self) -> String {
+        format!(
+            "https://github.com/{}/{}/security/dependabot/{}",
+            self.repo.owner, self.repo.name, self.number
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Severity {
+    Low,
+    Moderate,
+    High,
+    Critical,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Low => "low",
+            Self::Moderate => "moderate",
+            Self::High => "high",
+            Self::Critical => "critical",
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CiBuildMeta {
+    pub repo: RepoMeta,
+    /// Name of the workflow that was run.
+    pub workflow: String,
+    pub branch: Option<String>,
+    pub conclusion: CiConclusion,
+    /// The workflow run behind the notification, if it could be found.
+    pub run: Option<WorkflowRun>,
+}
+
+impl CiBuildMeta {
+    /// Build the metadata from the title of a CheckSuite notification, which
+    /// looks like `CI workflow run failed for main branch`.
+    pub fn from_title(title: &str, repo: RepoMeta) -> Self {
+        let (workflow, rest) = title.split_once(" workflow run ").unwrap_or((title, ""));
+        let (conclusion, branch) = rest.split_once(" for ").unwrap_or((rest, ""));
+        let branch = branch
+            .strip_suffix(" branch")
+            .filter(|b| !b.is_empty())
+            .map(String::from);
+
+        Self {
+            repo,
+            workflow: workflow.to_string(),
+            branch,
+            conclusion: CiConclusion::from(conclusion),
+            run: None,
+        }
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self.conclusion {
+            CiConclusion::Success => "",
+            CiConclusion::Failure | CiConclusion::Cancelled | CiConclusion::Unknown => "",
+        }
+    }
+}
+
+impl Display for CiBuildMeta {
+    /// A summary of the run along with the jobs that failed.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.workflow, self.conclusion)?;
+        if let Some(ref branch) = self.branch {
+            write!(f, " on {branch}")?;
+        }
+        let run = match self.run {
+            Some(ref run) => run,
+            None => return Ok(()),
+        };
+        write!(f, " (run #{}, {})", run.run_number, run.event)?;
+        for job in &run.failed_jobs {
+            write!(f, "\n  ✗ {}", job.name)?;
+            if let Some(ref step) = job.failed_step {
+                write!(f, " → {step}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CiConclusion {
+    Success,
+    Failure,
+    Cancelled,
+    Unknown,
+}
+
+impl From<&str> for CiConclusion {
+    /// Parse the conclusion from either the notification title or the
+    /// `conclusion` field of a workflow run.
+    fn from(s: &str) -> Self {
+        match s {
+            "succeeded" | "success" => Self::Success,
+            "failed" | "failure" | "timed_out" => Self::Failure,
+            "cancelled" => Self::Cancelled,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+impl Display for CiConclusion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Success => "succeeded",
+            Self::Failure => "failed",
+            Self::Cancelled => "was cancelled",
+            Self::Unknown => "finished",
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WorkflowRun {
+    pub id: u64,
+    pub run_number: u64,
+    /// The event that triggered the run, like `push` or `pull_request`.
+    pub event: String,
+    pub head_sha: String,
+    pub html_url: String,
+    pub failed_jobs: Vec<WorkflowJob>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WorkflowJob {
+    pub id: u64,
+    pub name: String,
+    pub html_url: String,
+    /// Name of the first step that failed.
+    pub failed_step: Option<String>,
+}
+
+/// A struct used solely for deserializing json from calling the workflow
+/// runs API.
+#[derive(Deserialize)]
+pub struct WorkflowRunDeserModel {
+    pub id: u64,
+    pub name: Option<String>,
+    pub run_number: u64,
+    pub event: String,
+    pub head_sha: String,
+    pub conclusion: Option<String>,
+    pub html_url: String,
+    pub created_at: DateTimeUtc,
+}
+
+/// A struct used solely for deserializing json from calling the workflow
+/// jobs API.
+#[derive(Deserialize)]
+pub struct WorkflowJobDeserModel {
+    pub id: u64,
+    pub name: String,
+    pub conclusion: Option<String>,
+    pub html_url: Option<String>,
+    #[serde(default)]
+    pub steps: Vec<WorkflowStepDeserModel>,
+}
+
+#[derive(Deserialize)]
+pub struct WorkflowStepDeserModel {
+    pub name: String,
+    pub conclusion: Option<String>,
+}
+
+impl From<WorkflowJobDeserModel> for WorkflowJob {
+    fn from(job: WorkflowJobDeserModel) -> Self {
+        Self {
+            id: job.id,
+            name: job.name,
+            html_url: job.html_url.unwrap_or_default(),
+            failed_step: job
+                .steps
+                .into_iter()
+                .find(|s| s.conclusion.as_deref() == Some("failure"))
+                .map(|s| s.name),
         }
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct DiscussionMeta {
     pub repo: RepoMeta,
     pub title: String,
@@ -376,12 +1066,13 @@ impl DiscussionMeta {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum DiscussionState {
     Answered,
     Unanswered,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Discussion {
     pub meta: DiscussionMeta,
     pub author: User,
@@ -391,6 +1082,7 @@ pub struct Discussion {
     pub suggested_answers: Vec<DiscussionSuggestedAnswer>,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct DiscussionSuggestedAnswer {
     pub author: User,
     pub is_answer: bool,
@@ -400,6 +1092,7 @@ pub struct DiscussionSuggestedAnswer {
     pub replies: Vec<DiscussionReplyToSuggestedAnswer>,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct DiscussionReplyToSuggestedAnswer {
     pub author: User,
     pub body: String,
@@ -411,11 +1104,31 @@ pub struct User {
     /// The username with which the user logs in; the @ name.
     #[serde(rename = "login")]
     pub name: String,
+    /// Set when the account is known to be a bot. Use [`User::is_bot`] instead
+    /// of checking this directly. Not part of the user objects of the REST
+    /// API, which only tell bots apart by their `type`.
+    #[serde(default)]
+    pub bot: bool,
 }
 
 impl User {
     pub fn new(name: impl Into<String>) -> Self {
-        Self { name: name.into() }
+        Self {
+            name: name.into(),
+            bot: false,
+        }
+    }
+
+    /// Whether the account is a bot (dependabot, CI bots, etc). Bot logins
+    /// returned by the REST API end with `[bot]`, whereas the GraphQL API
+    /// returns them without the suffix.
+    pub fn is_bot(&self) -> bool {
+        self.bot || self.name.ends_with("[bot]")
+    }
+
+    /// The login without the `[bot]` suffix used by the REST API.
+    pub fn login(&self) -> &str {
+        self.name.trim_end_matches("[bot]")
     }
 }
 
@@ -428,12 +1141,153 @@ impl Display for User {
 
 impl From<octocrab::models::User> for User {
     fn from(u: octocrab::models::User) -> Self {
-        Self { name: u.login }
+        Self {
+            bot: u.r#type == "Bot",
+            name: u.login,
+        }
     }
 }
 
 impl From<String> for User {
     fn from(name: String) -> Self {
-        Self { name }
+        Self::new(name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_synthetic_notification() {
+        let issue = IssueMeta {
+            repo: RepoMeta {
+                name: "helix".to_string(),
+                owner: "helix-editor".to_string(),
+            },
+            title: "Crash on startup".to_string(),
+            body: String::new(),
+            number: 12,
+            author: User::default(),
+            state: IssueState::Open,
+            created_at: DateTimeUtc::default(),
+            labels: Vec::new(),
+        };
+        let notification = Notification::synthetic(
+            "Issue",
+            DateTimeUtc::default(),
+            NotificationTarget::Issue(issue),
+        )
+        .unwrap();
+        assert!(!notification.is_thread());
+        assert_eq!(notification.inner.subject.title, "Crash on startup");
+        assert_eq!(
+            notification.inner.subject.url.as_ref().unwrap().as_str(),
+            "https://api.github.com/repos/helix-editor/helix/issues/12"
+        );
+        let repo = RepoMeta::from(&notification.inner.repository);
+        assert_eq!(
+            (repo.owner.as_str(), repo.name.as_str()),
+            ("helix-editor", "helix")
+        );
+
+        let json = serde_json::to_string(&notification).unwrap();
+        let restored: Notification = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.inner, notification.inner);
+        match restored.target {
+            NotificationTarget::Issue(issue) => {
+                assert_eq!(issue.number, 12);
+                assert!(issue.state.is_open());
+            }
+            _ => panic!("expected an issue"),
+        }
+
+        assert!(Notification::synthetic(
+            "Release",
+            DateTimeUtc::default(),
+            NotificationTarget::Unknown
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_ci_build_from_title() {
+        let repo = || RepoMeta {
+            name: "octerm".to_string(),
+            owner: "sudormrfbin".to_string(),
+        };
+        let build = CiBuildMeta::from_title("CI workflow run failed for master branch", repo());
+        assert_eq!(build.workflow, "CI");
+        assert_eq!(build.branch.as_deref(), Some("master"));
+        assert_eq!(build.conclusion, CiConclusion::Failure);
+        assert_eq!(build.to_string(), "CI failed on master");
+
+        let build = CiBuildMeta::from_title(
+            "Release Tests workflow run cancelled for feat/ci branch",
+            repo(),
+        );
+        assert_eq!(build.workflow, "Release Tests");
+        assert_eq!(build.branch.as_deref(), Some("feat/ci"));
+        assert_eq!(build.conclusion, CiConclusion::Cancelled);
+
+        let build = CiBuildMeta::from_title("Something else", repo());
+        assert_eq!(build.workflow, "Something else");
+        assert_eq!(build.branch, None);
+        assert_eq!(build.conclusion, CiConclusion::Unknown);
+    }
+
+    #[test]
+    fn test_commit_meta() {
+        let commit = CommitMeta {
+            repo: RepoMeta {
+                name: "octerm".to_string(),
+                owner: "sudormrfbin".to_string(),
+            },
+            sha: "50e8566a1b2c3d4e".to_string(),
+            message: "Fix the thing\n\nLonger description".to_string(),
+            author: User::new("octocat"),
+            html_url: String::new(),
+        };
+        assert_eq!(commit.short_sha(), "50e8566");
+        assert_eq!(commit.subject(), "Fix the thing");
+    }
+
+    #[test]
+    fn test_release_headline() {
+        let release = |body: &str| ReleaseMeta {
+            repo: RepoMeta {
+                name: "octerm".to_string(),
+                owner: "sudormrfbin".to_string(),
+            },
+            title: "v0.2.0".to_string(),
+            body: body.to_string(),
+            author: "octocat".to_string(),
+            tag_name: "v0.2.0".to_string(),
+            published_at: None,
+            assets: Vec::new(),
+            html_url: String::new(),
+        };
+        assert_eq!(
+            release("## What's Changed\n\n* Add **dates** by @a in #1\n* Fix").headline(),
+            "Add dates by @a in #1"
+        );
+        assert_eq!(release("Bug fix release").headline(), "Bug fix release");
+        assert_eq!(release("").headline(), "");
+    }
+
+    #[test]
+    fn test_merged_prs() {
+        let comparison = ReleaseComparison {
+            base_tag: "v0.1.0".to_string(),
+            head_tag: "v0.2.0".to_string(),
+            total_commits: 3,
+            commits: vec![
+                ("a".into(), "Merge pull request #12 from a/b".into()),
+                ("b".into(), "Add dates (#15)".into()),
+                ("c".into(), "Fix typo".into()),
+            ],
+            html_url: String::new(),
+        };
+        assert_eq!(comparison.merged_prs(), [12, 15]);
     }
 }
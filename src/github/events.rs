@@ -1,8 +1,14 @@
+use std::fmt::Display;
+
+use crossterm::style::{Color, Stylize};
+use serde::{Deserialize, Serialize};
+
 use super::User;
 
 pub type DateTimeLocal = chrono::DateTime<chrono::Local>;
 pub type DateTimeUtc = chrono::DateTime<chrono::Utc>;
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Event {
     pub actor: User,
     pub created_at: DateTimeLocal,
@@ -10,21 +16,56 @@ pub struct Event {
 }
 
 impl Event {
-    pub fn unknown(ev: &'static str) -> Self {
+    pub fn unknown(ev: &str) -> Self {
         Event {
-            actor: User { name: "".into() },
+            actor: User::new(""),
             created_at: DateTimeLocal::default(),
-            kind: EventKind::Unknown(ev),
+            kind: EventKind::Unknown(ev.to_string()),
+        }
+    }
+    /// Whether this is a comment or review by a bot that should be shown
+    /// collapsed to a single line, see [`crate::config::TimelineConfig`].
+    pub fn is_collapsed_bot_comment(&self) -> bool {
+        matches!(
+            self.kind,
+            EventKind::Commented { .. } | EventKind::Reviewed { .. }
+        ) && crate::config::get().timeline.should_collapse(&self.actor)
+    }
+
+    /// The markdown text of comments and reviews.
+    pub fn body(&self) -> Option<&str> {
+        match self.kind {
+            EventKind::Commented { ref body, .. } => Some(body),
+            EventKind::Reviewed { ref body, .. } => body.as_deref(),
+            _ => None,
         }
     }
 }
 
+/// A one line, human readable description of the event, eg. "@user added
+/// the bug label". Bodies of comments and reviews are not included.
+impl Display for Event {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.actor.name.is_empty() {
+            write!(f, "{} ", self.actor)?;
+        }
+        write!(f, "{}", self.kind)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub enum EventKind {
     Assigned {
         assignee: User,
     },
     Commented {
         body: String,
+        reactions: Vec<ReactionGroup>,
+        /// Reason the comment was hidden by a maintainer (spam, outdated,
+        /// resolved, etc), if it was hidden.
+        minimized: Option<String>,
+        edited: bool,
+        association: AuthorAssociation,
     },
     Merged {
         /// The branch into which the PR was merged (main,master, etc)
@@ -62,6 +103,22 @@ pub enum EventKind {
         /// Deleted branch
         branch: String,
     },
+    /// The PR will be merged automatically once all requirements are met.
+    AutoMergeEnabled {
+        method: MergeMethod,
+    },
+    AutoMergeDisabled {
+        reason: Option<String>,
+    },
+    /// The PR was deployed, usually to a preview environment.
+    Deployed {
+        environment: Option<String>,
+        state: Option<DeploymentState>,
+    },
+    DeploymentEnvironmentChanged {
+        environment: Option<String>,
+        state: DeploymentState,
+    },
     MarkedAsDraft,
     MarkedAsReadyForReview,
     ReviewRequested {
@@ -70,6 +127,9 @@ pub enum EventKind {
     Reviewed {
         state: ReviewState,
         body: Option<String>,
+        reactions: Vec<ReactionGroup>,
+        edited: bool,
+        association: AuthorAssociation,
     },
     /// The issue/PR was linked to another issue/PR for automatic closing.
     Connected {
@@ -77,6 +137,15 @@ pub enum EventKind {
         source: IssueOrPullRequest,
     },
     Reopened,
+    /// The issue was transferred to another repository.
+    Transferred {
+        /// Repository the issue was transferred from, if still accessible.
+        from: Option<Repository>,
+        to: Repository,
+    },
+    ConvertedToDiscussion {
+        discussion: Option<DiscussionRef>,
+    },
     Renamed {
         from: String,
         to: String,
@@ -89,6 +158,11 @@ pub enum EventKind {
     },
     Pinned,
     Unpinned,
+    // The project and column names of project events are only exposed by
+    // a preview of the GraphQL API, which is not part of our schema.
+    AddedToProject,
+    MovedColumnsInProject,
+    RemovedFromProject,
     /// This issue/PR was referenced by a commit
     Referenced {
         commit_msg_summary: String,
@@ -102,7 +176,7 @@ pub enum EventKind {
     },
     Unlocked,
     /// Unhandled event, with name of the event
-    Unknown(&'static str),
+    Unknown(String),
 }
 
 impl EventKind {
@@ -128,12 +202,343 @@ impl EventKind {
     }
 }
 
+/// Filters applied to a timeline to show only a subset of the events,
+/// without having to refetch them.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TimelineFilter {
+    #[default]
+    All,
+    /// Comments and reviews only.
+    Conversation,
+    /// Events that change the state of the issue/PR (closed, merged, etc).
+    StatusChanges,
+}
+
+impl TimelineFilter {
+    /// The filter by the name given to `show` and `view`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "all" => Some(TimelineFilter::All),
+            "conversation" => Some(TimelineFilter::Conversation),
+            "status" => Some(TimelineFilter::StatusChanges),
+            _ => None,
+        }
+    }
+
+    /// The filter to switch to when cycling through filters.
+    pub fn next(self) -> Self {
+        match self {
+            TimelineFilter::All => TimelineFilter::Conversation,
+            TimelineFilter::Conversation => TimelineFilter::StatusChanges,
+            TimelineFilter::StatusChanges => TimelineFilter::All,
+        }
+    }
+
+    pub fn matches(&self, kind: &EventKind) -> bool {
+        match self {
+            TimelineFilter::All => true,
+            TimelineFilter::Conversation => {
+                matches!(
+                    kind,
+                    EventKind::Commented { .. } | EventKind::Reviewed { .. }
+                )
+            }
+            TimelineFilter::StatusChanges => matches!(
+                kind,
+                EventKind::Closed { .. }
+                    | EventKind::Reopened
+                    | EventKind::Merged { .. }
+                    | EventKind::MarkedAsDraft
+                    | EventKind::MarkedAsReadyForReview
+                    | EventKind::AutoMergeEnabled { .. }
+                    | EventKind::AutoMergeDisabled { .. }
+                    | EventKind::Locked { .. }
+                    | EventKind::Unlocked
+                    | EventKind::MarkedAsDuplicate { .. }
+                    | EventKind::UnmarkedAsDuplicate
+                    | EventKind::Transferred { .. }
+                    | EventKind::ConvertedToDiscussion { .. }
+            ),
+        }
+    }
+
+    /// The events of the timeline that pass the filter, in order.
+    pub fn apply<'a>(&self, events: &'a [Event]) -> impl Iterator<Item = &'a Event> {
+        let filter = *self;
+        events.iter().filter(move |e| filter.matches(&e.kind))
+    }
+}
+
+impl Display for TimelineFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            TimelineFilter::All => "everything",
+            TimelineFilter::Conversation => "comments and reviews",
+            TimelineFilter::StatusChanges => "status changes",
+        })
+    }
+}
+
+impl Display for EventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EventKind::Assigned { assignee } => write!(f, "assigned {assignee}"),
+            EventKind::Commented {
+                minimized, edited, ..
+            } => {
+                write!(f, "commented")?;
+                if let Some(reason) = minimized {
+                    write!(f, " (hidden as {reason})")?;
+                }
+                if *edited {
+                    write!(f, " (edited)")?;
+                }
+                Ok(())
+            }
+            EventKind::Merged { base_branch } => write!(f, "merged this into {base_branch}"),
+            EventKind::Closed { closer } => match closer {
+                Some(IssueCloser::Commit { abbr_oid }) => write!(f, "closed this in {abbr_oid}"),
+                Some(IssueCloser::PullRequest { number }) => write!(f, "closed this in #{number}"),
+                None => write!(f, "closed this"),
+            },
+            EventKind::Committed {
+                message_headline,
+                abbreviated_oid,
+            } => write!(f, "committed {abbreviated_oid} {message_headline}"),
+            EventKind::Labeled { label } => write!(f, "added the {} label", label.name),
+            EventKind::Unlabeled { label } => write!(f, "removed the {} label", label.name),
+            EventKind::MarkedAsDuplicate { original } => match original {
+                Some(original) => write!(
+                    f,
+                    "marked this as a duplicate of #{} {}",
+                    original.number(),
+                    original.title()
+                ),
+                None => write!(f, "marked this as a duplicate"),
+            },
+            EventKind::UnmarkedAsDuplicate => write!(f, "marked this as not a duplicate"),
+            EventKind::CrossReferenced {
+                source,
+                cross_repository,
+            } => {
+                write!(f, "mentioned this in ")?;
+                if let Some(repo) = cross_repository {
+                    write!(f, "{repo}")?;
+                }
+                write!(f, "#{} {}", source.number(), source.title())
+            }
+            EventKind::HeadRefForcePushed {
+                before_commit_abbr_oid,
+                after_commit_abbr_oid,
+            } => write!(
+                f,
+                "force-pushed from {before_commit_abbr_oid} to {after_commit_abbr_oid}"
+            ),
+            EventKind::HeadRefDeleted { branch } => write!(f, "deleted the {branch} branch"),
+            EventKind::AutoMergeEnabled { method } => write!(f, "enabled auto-merge ({method})"),
+            EventKind::AutoMergeDisabled { reason } => match reason {
+                Some(reason) => write!(f, "disabled auto-merge: {reason}"),
+                None => write!(f, "disabled auto-merge"),
+            },
+            EventKind::Deployed { environment, state } => {
+                write!(f, "deployed to ")?;
+                match environment {
+                    Some(env) => write!(f, "{env}")?,
+                    None => write!(f, "an environment")?,
+                }
+                match state {
+                    Some(state) => write!(f, " ({state})"),
+                    None => Ok(()),
+                }
+            }
+            EventKind::DeploymentEnvironmentChanged { environment, state } => match environment {
+                Some(env) => write!(f, "changed the {env} deployment status to {state}"),
+                None => write!(f, "changed the deployment status to {state}"),
+            },
+            EventKind::MarkedAsDraft => write!(f, "marked this as a draft"),
+            EventKind::MarkedAsReadyForReview => write!(f, "marked this as ready for review"),
+            EventKind::ReviewRequested { requested_reviewer } => {
+                write!(f, "requested a review from {requested_reviewer}")
+            }
+            EventKind::Reviewed { state, edited, .. } => {
+                match state {
+                    ReviewState::Commented => write!(f, "reviewed"),
+                    ReviewState::ChangesRequested => write!(f, "requested changes"),
+                    ReviewState::Approved => write!(f, "approved these changes"),
+                    ReviewState::Dismissed => write!(f, "reviewed (dismissed)"),
+                    ReviewState::Pending => write!(f, "started a review"),
+                    ReviewState::Other(state) => write!(f, "reviewed ({state})"),
+                }?;
+                if *edited {
+                    write!(f, " (edited)")?;
+                }
+                Ok(())
+            }
+            EventKind::Connected { source } => {
+                write!(f, "linked #{} {}", source.number(), source.title())
+            }
+            EventKind::Reopened => write!(f, "reopened this"),
+            EventKind::Transferred { from, to } => match from {
+                Some(from) => write!(f, "transferred this issue from {from} to {to}"),
+                None => write!(f, "transferred this issue to {to}"),
+            },
+            EventKind::ConvertedToDiscussion { discussion } => match discussion {
+                Some(d) => write!(f, "converted this to discussion #{} {}", d.number, d.title),
+                None => write!(f, "converted this to a discussion"),
+            },
+            EventKind::Renamed { from, to } => {
+                write!(f, "changed the title from {from} to {to}")
+            }
+            EventKind::Locked { reason } => match reason {
+                Some(reason) => write!(f, "locked this as {reason}"),
+                None => write!(f, "locked this"),
+            },
+            EventKind::Milestoned { title } => {
+                write!(f, "added this to the {title} milestone")
+            }
+            EventKind::Pinned => write!(f, "pinned this"),
+            EventKind::Unpinned => write!(f, "unpinned this"),
+            EventKind::AddedToProject => write!(f, "added this to a project"),
+            EventKind::MovedColumnsInProject => write!(f, "moved this to another project column"),
+            EventKind::RemovedFromProject => write!(f, "removed this from a project"),
+            EventKind::Referenced {
+                commit_msg_summary,
+                cross_repository,
+            } => match cross_repository {
+                Some(repo) => write!(
+                    f,
+                    "referenced this in a commit in {repo}: {commit_msg_summary}"
+                ),
+                None => write!(f, "referenced this in a commit: {commit_msg_summary}"),
+            },
+            EventKind::Mentioned => write!(f, "you were mentioned"),
+            EventKind::Subscribed => write!(f, "you subscribed"),
+            EventKind::Unassigned { assignee } => write!(f, "unassigned {assignee}"),
+            EventKind::Unlocked => write!(f, "unlocked this"),
+            EventKind::Unknown(ev) => write!(f, "unhandled event {ev}"),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Label {
     pub name: String,
     // Hex color, eg. `FBCA04`
-    // pub color: String,
+    pub color: String,
+}
+
+impl Label {
+    /// The label color as RGB, or None if the hex color is malformed.
+    pub fn rgb(&self) -> Option<(u8, u8, u8)> {
+        let hex = self.color.trim_start_matches('#');
+        if hex.len() != 6 {
+            return None;
+        }
+        let component = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+        Some((component(0)?, component(2)?, component(4)?))
+    }
+
+    /// The label name on its background color, with a black or white
+    /// foreground depending on which is more readable on the background.
+    pub fn to_colored_string(&self) -> String {
+        let (r, g, b) = match self.rgb() {
+            Some(rgb) => rgb,
+            None => return self.name.clone(),
+        };
+        // Perceived brightness, see https://www.w3.org/TR/AERT/#color-contrast
+        let brightness = (299 * r as u32 + 587 * g as u32 + 114 * b as u32) / 1000;
+        let fg = if brightness > 125 {
+            Color::Black
+        } else {
+            Color::White
+        };
+        format!(" {} ", self.name)
+            .with(fg)
+            .on(Color::Rgb { r, g, b })
+            .to_string()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Reaction {
+    ThumbsUp,
+    ThumbsDown,
+    Laugh,
+    Hooray,
+    Confused,
+    Heart,
+    Rocket,
+    Eyes,
+}
+
+impl Reaction {
+    pub fn emoji(&self) -> &'static str {
+        match self {
+            Reaction::ThumbsUp => "👍",
+            Reaction::ThumbsDown => "👎",
+            Reaction::Laugh => "😄",
+            Reaction::Hooray => "🎉",
+            Reaction::Confused => "😕",
+            Reaction::Heart => "❤️",
+            Reaction::Rocket => "🚀",
+            Reaction::Eyes => "👀",
+        }
+    }
 }
 
+/// Number of users who reacted to a comment with a particular reaction.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ReactionGroup {
+    pub reaction: Reaction,
+    pub count: usize,
+}
+
+impl Display for ReactionGroup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.reaction.emoji(), self.count)
+    }
+}
+
+/// Format reactions compactly on a single line, eg. `👍 12  🎉 3`.
+pub fn format_reactions(reactions: &[ReactionGroup]) -> String {
+    reactions
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("  ")
+}
+
+/// Relationship of the author of a comment with the repository.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuthorAssociation {
+    Owner,
+    Member,
+    Collaborator,
+    Contributor,
+    /// Has previously committed to GitHub, but not to this repository.
+    FirstTimeContributor,
+    /// Has never committed to GitHub before.
+    FirstTimer,
+    Mannequin,
+    None,
+}
+
+impl AuthorAssociation {
+    /// Short badge shown next to the author of a comment, if any.
+    pub fn badge(&self) -> Option<&'static str> {
+        match self {
+            AuthorAssociation::Owner => Some("Owner"),
+            AuthorAssociation::Member => Some("Member"),
+            AuthorAssociation::Collaborator => Some("Collaborator"),
+            AuthorAssociation::Contributor => Some("Contributor"),
+            AuthorAssociation::FirstTimeContributor => Some("First-time contributor"),
+            AuthorAssociation::FirstTimer => Some("First-timer"),
+            AuthorAssociation::Mannequin | AuthorAssociation::None => None,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub enum ReviewState {
     Commented,
     ChangesRequested,
@@ -143,6 +548,59 @@ pub enum ReviewState {
     Other(String),
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+pub enum MergeMethod {
+    Merge,
+    Squash,
+    Rebase,
+}
+
+impl Display for MergeMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            MergeMethod::Merge => "merge",
+            MergeMethod::Squash => "squash",
+            MergeMethod::Rebase => "rebase",
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum DeploymentState {
+    Abandoned,
+    Active,
+    Destroyed,
+    Error,
+    Failure,
+    Inactive,
+    InProgress,
+    Pending,
+    Queued,
+    Success,
+    Waiting,
+    Other(String),
+}
+
+impl Display for DeploymentState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            DeploymentState::Abandoned => "abandoned",
+            DeploymentState::Active => "active",
+            DeploymentState::Destroyed => "destroyed",
+            DeploymentState::Error => "error",
+            DeploymentState::Failure => "failure",
+            DeploymentState::Inactive => "inactive",
+            DeploymentState::InProgress => "in progress",
+            DeploymentState::Pending => "pending",
+            DeploymentState::Queued => "queued",
+            DeploymentState::Success => "success",
+            DeploymentState::Waiting => "waiting",
+            DeploymentState::Other(state) => state,
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub enum IssueCloser {
     Commit { abbr_oid: String },
     PullRequest { number: usize },
@@ -162,6 +620,7 @@ impl From<String> for IssueCloser {
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub enum IssueOrPullRequest {
     PullRequest { number: usize, title: String },
     Issue { number: usize, title: String },
@@ -183,6 +642,13 @@ impl IssueOrPullRequest {
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DiscussionRef {
+    pub number: usize,
+    pub title: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub enum LockReason {
     OffTopic,
     Resolved,
@@ -191,7 +657,232 @@ pub enum LockReason {
     Other(String),
 }
 
+impl Display for LockReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LockReason::OffTopic => f.write_str("off-topic"),
+            LockReason::Resolved => f.write_str("resolved"),
+            LockReason::Spam => f.write_str("spam"),
+            LockReason::TooHeated => f.write_str("too heated"),
+            LockReason::Other(reason) => f.write_str(reason),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Repository {
     pub name: String,
     pub owner: User,
 }
+
+impl Display for Repository {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.owner.name, self.name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_event_display() {
+        let event = |kind: EventKind| kind.with(User::new("octocat"), DateTimeUtc::default());
+
+        assert_eq!(
+            event(EventKind::AutoMergeEnabled {
+                method: MergeMethod::Squash
+            })
+            .to_string(),
+            "@octocat enabled auto-merge (squash)"
+        );
+        assert_eq!(
+            event(EventKind::AutoMergeDisabled { reason: None }).to_string(),
+            "@octocat disabled auto-merge"
+        );
+        assert_eq!(
+            event(EventKind::Commented {
+                body: "+1".to_string(),
+                reactions: Vec::new(),
+                minimized: Some("off-topic".to_string()),
+                edited: true,
+                association: AuthorAssociation::None,
+            })
+            .to_string(),
+            "@octocat commented (hidden as off-topic) (edited)"
+        );
+        assert_eq!(
+            EventKind::Mentioned.anonymous().to_string(),
+            "you were mentioned"
+        );
+    }
+
+    #[test]
+    fn test_format_reactions() {
+        let reactions = [
+            ReactionGroup {
+                reaction: Reaction::ThumbsUp,
+                count: 12,
+            },
+            ReactionGroup {
+                reaction: Reaction::Hooray,
+                count: 3,
+            },
+        ];
+        assert_eq!(format_reactions(&reactions), "👍 12  🎉 3");
+        assert_eq!(format_reactions(&[]), "");
+    }
+
+    #[test]
+    fn test_label_color() {
+        let label = |color: &str| Label {
+            name: "bug".to_string(),
+            color: color.to_string(),
+        };
+        assert_eq!(label("d73a4a").rgb(), Some((0xd7, 0x3a, 0x4a)));
+        assert_eq!(label("#FBCA04").rgb(), Some((0xfb, 0xca, 0x04)));
+        assert_eq!(label("fff").rgb(), None);
+        assert_eq!(label("zzzzzz").rgb(), None);
+        assert_eq!(label("").to_colored_string(), "bug");
+    }
+
+    #[test]
+    fn test_timeline_filter() {
+        let events = vec![
+            EventKind::Commented {
+                body: "LGTM".to_string(),
+                reactions: Vec::new(),
+                minimized: None,
+                edited: false,
+                association: AuthorAssociation::None,
+            }
+            .anonymous(),
+            EventKind::Labeled {
+                label: Label {
+                    name: "bug".to_string(),
+                    color: "d73a4a".to_string(),
+                },
+            }
+            .anonymous(),
+            EventKind::Closed { closer: None }.anonymous(),
+        ];
+        let filtered = |filter: TimelineFilter| {
+            filter
+                .apply(&events)
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(filtered(TimelineFilter::All).len(), 3);
+        assert_eq!(filtered(TimelineFilter::Conversation), ["commented"]);
+        assert_eq!(filtered(TimelineFilter::StatusChanges), ["closed this"]);
+        assert_eq!(TimelineFilter::StatusChanges.next(), TimelineFilter::All);
+        for name in ["all", "conversation", "status"] {
+            assert!(TimelineFilter::from_name(name).is_some());
+        }
+        assert_eq!(TimelineFilter::from_name("comments"), None);
+    }
+
+    #[test]
+    fn test_event_serde() {
+        let mut bot = User::new("dependabot");
+        bot.bot = true;
+        let events = [
+            EventKind::Reviewed {
+                state: ReviewState::Approved,
+                body: Some("LGTM".to_string()),
+                reactions: vec![ReactionGroup {
+                    reaction: Reaction::Rocket,
+                    count: 2,
+                }],
+                edited: false,
+                association: AuthorAssociation::Member,
+            }
+            .with(bot, DateTimeUtc::default()),
+            Event::unknown("UserBlockedEvent"),
+        ];
+        let json = serde_json::to_string(&events).unwrap();
+        let restored: Vec<Event> = serde_json::from_str(&json).unwrap();
+        let lines = |events: &[Event]| events.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(lines(&restored), lines(&events));
+        assert!(restored[0].actor.bot);
+        assert_eq!(restored[0].body(), Some("LGTM"));
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let label = Label {
+            name: "bug".to_string(),
+            color: "d73a4a".to_string(),
+        };
+        let repo = Repository {
+            name: "helix".to_string(),
+            owner: User::new("helix-editor"),
+        };
+        let kinds = [
+            EventKind::Assigned {
+                assignee: User::new("archseer"),
+            },
+            EventKind::Labeled {
+                label: label.clone(),
+            },
+            EventKind::Unlabeled { label },
+            EventKind::Committed {
+                message_headline: "Fix crash on startup".to_string(),
+                abbreviated_oid: "50e8566".to_string(),
+            },
+            EventKind::HeadRefForcePushed {
+                before_commit_abbr_oid: "50e8566".to_string(),
+                after_commit_abbr_oid: "a1b2c3d".to_string(),
+            },
+            EventKind::ReviewRequested {
+                requested_reviewer: User::new("archseer"),
+            },
+            EventKind::Reviewed {
+                state: ReviewState::ChangesRequested,
+                body: None,
+                reactions: Vec::new(),
+                edited: false,
+                association: AuthorAssociation::Member,
+            },
+            EventKind::CrossReferenced {
+                source: IssueOrPullRequest::PullRequest {
+                    number: 15,
+                    title: "Add a pager".to_string(),
+                },
+                cross_repository: Some(repo.clone()),
+            },
+            EventKind::Transferred {
+                from: Some(repo),
+                to: Repository {
+                    name: "octerm".to_string(),
+                    owner: User::new("sudormrfbin"),
+                },
+            },
+            EventKind::Locked {
+                reason: Some(LockReason::TooHeated),
+            },
+            EventKind::Deployed {
+                environment: Some("preview".to_string()),
+                state: Some(DeploymentState::Success),
+            },
+            EventKind::Merged {
+                base_branch: "master".to_string(),
+            },
+            EventKind::Closed {
+                closer: Some(IssueCloser::PullRequest { number: 15 }),
+            },
+            EventKind::Renamed {
+                from: "Crash".to_string(),
+                to: "Crash on startup".to_string(),
+            },
+            EventKind::Unknown("UserBlockedEvent".to_string()),
+        ];
+        let lines: Vec<_> = kinds
+            .into_iter()
+            .map(|kind| kind.with(User::new("octocat"), DateTimeUtc::default()))
+            .map(|event| format!("{event}\n"))
+            .collect();
+        crate::snapshot::assert_snapshot("events", &lines.concat());
+    }
+}
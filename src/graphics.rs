@@ -0,0 +1,84 @@
+//! Displays images inline in terminals that support a graphics protocol.
+//! Sixel is not supported since it requires decoding the image; terminals
+//! without kitty or iTerm2 support get a text placeholder instead.
+
+/// Maximum size of a chunk of image data sent at once with the kitty protocol.
+const KITTY_CHUNK_SIZE: usize = 4096;
+
+const PNG_MAGIC: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    Kitty,
+    /// The iTerm2 inline images protocol, also supported by WezTerm.
+    ITerm2,
+}
+
+impl Protocol {
+    /// Detect the graphics protocol supported by the terminal from the
+    /// environment.
+    pub fn detect() -> Option<Self> {
+        let var = |name| std::env::var(name).unwrap_or_default();
+        if var("TERM").contains("kitty") || std::env::var_os("KITTY_WINDOW_ID").is_some() {
+            return Some(Self::Kitty);
+        }
+        match var("TERM_PROGRAM").as_str() {
+            "iTerm.app" | "WezTerm" => Some(Self::ITerm2),
+            _ => None,
+        }
+    }
+
+    /// The escape sequence that displays the image, or `None` if the format
+    /// of the image isn't supported by the protocol.
+    pub fn escape_sequence(&self, image: &[u8]) -> Option<String> {
+        let encoded = base64::encode(image);
+        match self {
+            Self::Kitty => {
+                // Kitty can only decode PNGs by itself.
+                if !image.starts_with(PNG_MAGIC) {
+                    return None;
+                }
+                let chunks: Vec<&[u8]> = encoded.as_bytes().chunks(KITTY_CHUNK_SIZE).collect();
+                let mut sequence = String::new();
+                for (i, chunk) in chunks.iter().enumerate() {
+                    let more = (i + 1 < chunks.len()) as u8;
+                    let chunk = std::str::from_utf8(chunk).ok()?;
+                    match i {
+                        0 => sequence += &format!("\x1b_Gf=100,a=T,m={more};{chunk}\x1b\\"),
+                        _ => sequence += &format!("\x1b_Gm={more};{chunk}\x1b\\"),
+                    }
+                }
+                Some(sequence)
+            }
+            Self::ITerm2 => Some(format!(
+                "\x1b]1337;File=inline=1;size={};preserveAspectRatio=1:{encoded}\x07",
+                image.len()
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_escape_sequence() {
+        let png = [PNG_MAGIC, b"data"].concat();
+        let kitty = Protocol::Kitty.escape_sequence(&png).unwrap();
+        assert!(kitty.starts_with("\x1b_Gf=100,a=T,m=0;"));
+        assert!(kitty.ends_with("\x1b\\"));
+        assert_eq!(Protocol::Kitty.escape_sequence(b"GIF89a"), None);
+
+        let large = [PNG_MAGIC, &[0; 4000]].concat();
+        let kitty = Protocol::Kitty.escape_sequence(&large).unwrap();
+        assert_eq!(kitty.matches("\x1b_G").count(), 2);
+        assert!(kitty.contains("\x1b_Gm=0;"));
+
+        let iterm = Protocol::ITerm2.escape_sequence(b"GIF89a").unwrap();
+        assert_eq!(
+            iterm,
+            "\x1b]1337;File=inline=1;size=6;preserveAspectRatio=1:R0lGODlh\x07"
+        );
+    }
+}
@@ -0,0 +1,181 @@
+//! Text for the `help` command, built from the same name tables used by
+//! the parser and the completer.
+
+use crate::parser::types::{Adapter, Command, Consumer, Producer};
+
+/// Accepted arguments and a one line description of a command, producer,
+/// adapter or consumer.
+pub fn usage(name: &str) -> Option<(&'static str, &'static str)> {
+    let usage = match name {
+        "help" => (
+            "[name]",
+            "Show the available commands, or details about one",
+        ),
+        "new" => (
+            "issue <owner/name> [label]...",
+            "Write an issue in $EDITOR and open it, title on the first line",
+        ),
+        "reload" => ("", "Sync notifications from GitHub again"),
+        "dates" => ("", "Toggle between relative and absolute dates"),
+        "releases" => ("", "Show a digest of all release notifications"),
+        "digest" => (
+            "",
+            "Print a markdown digest of the past week's notifications by repository",
+        ),
+        "stats" => ("", "Show request counts, timings and the rate limit"),
+        "error" => ("", "Show everything known about the last error"),
+        "participating" => (
+            "",
+            "Toggle between syncing all notifications and only participating ones",
+        ),
+
+        "list" => (
+            "[pr|issue|discussion|release|ci|commit|invitation|alert|gist|deps] \
+             [open|closed|merged] [participating] [repo:owner/name] [org:owner] [label:name] \
+             [reason:name] [updated:>3d|updated:<1w] [!filter]...",
+            "List notifications matching all the filters, participating also switches the sync mode",
+        ),
+        "search" => ("<query>...", "Search issues and pull requests on GitHub"),
+        "repo" => (
+            "<owner/name> [issues|prs] [open|closed|merged]",
+            "List issues and pull requests of a repository",
+        ),
+        "assigned" => (
+            "[query]...",
+            "Open issues and pull requests assigned to you",
+        ),
+        "reviews" => ("[query]...", "Open pull requests waiting for your review"),
+        "created" => ("[query]...", "Open pull requests opened by you"),
+
+        "confirm" => ("", "Ask before passing on each notification"),
+        "sort" => (
+            "[updated|repo|relevance]",
+            "Reorder the notifications by a key",
+        ),
+        "limit" | "head" => ("<n>", "Keep only the first n notifications"),
+        "tail" => ("<n>", "Keep only the last n notifications"),
+        "reverse" => ("", "Flip the order of the notifications"),
+        "grep" => (
+            "<text>|</regex/>",
+            "Keep notifications whose title or repository match",
+        ),
+        "tee" => ("", "Print the notifications and pass them on unchanged"),
+
+        "open" => ("<index>...", "Open notifications in the browser"),
+        "done" => ("<index>...", "Mark notifications as done"),
+        "count" => (
+            "[repo|type|reason] <index>...",
+            "Count notifications, optionally grouped",
+        ),
+        "logs" => (
+            "<index>...",
+            "Print the logs of the failed jobs of CI builds",
+        ),
+        "accept" => ("<index>...", "Accept repository invitations"),
+        "decline" => ("<index>...", "Decline repository invitations"),
+        "rebase" => ("<index>...", "Ask dependabot to rebase its pull requests"),
+        "assets" => (
+            "[copy|download <dir>] <index>...",
+            "List the downloadable files of releases, or copy the url of one or download it",
+        ),
+        "compare" => ("<index>...", "Show the changes since the previous release"),
+        "links" => ("<index>", "Pick a link in a notification to open"),
+        "copy" => ("<index>", "Pick a code block in a notification to copy"),
+        "unsubscribe" => ("<index>...", "Stop receiving notifications for threads"),
+        "mute" => (
+            "[thread|repo] <index>...",
+            "Hide threads or their repositories from future syncs",
+        ),
+        "show" => (
+            "[all|conversation|status] [expand] <index>",
+            "Print the text and comments of a notification",
+        ),
+        "view" => (
+            "[all|conversation|status] [expand] <index>",
+            "Show a notification in a full screen view",
+        ),
+        "browse" => (
+            "[issues|pulls|releases|actions] <index>...",
+            "Open the repository pages of notifications",
+        ),
+        "json" => ("<index>...", "Print notifications as JSON lines"),
+        "export" => (
+            "csv <file> [column]... <index>...",
+            "Write notifications to a file",
+        ),
+        "comment" => (
+            "<index>",
+            "Comment on an issue or pull request in your editor",
+        ),
+        "edit" => (
+            "[file] <index>",
+            "Open the local checkout of the repository in your editor",
+        ),
+        "checkout" => (
+            "<index>",
+            "Copy the command that checks out a pull request locally",
+        ),
+        _ => return None,
+    };
+    Some(usage)
+}
+
+/// All names grouped by kind, with their descriptions.
+pub fn overview() -> String {
+    let commands: Vec<_> = ["help", "new"].into_iter().chain(Command::all()).collect();
+    let sections: [(&str, &[&str]); 4] = [
+        ("Commands", &commands),
+        ("Producers", &Producer::all()),
+        ("Adapters", &Adapter::all()),
+        ("Consumers", &Consumer::all()),
+    ];
+
+    let width = sections
+        .iter()
+        .flat_map(|(_, names)| names.iter())
+        .map(|name| name.len())
+        .max()
+        .unwrap_or_default();
+    let mut text = String::new();
+    for (title, names) in sections {
+        text += &format!("{title}:\n");
+        for name in names {
+            let (_, description) = usage(name).unwrap_or_default();
+            text += &format!("  {name:width$}  {description}\n");
+        }
+    }
+    text + "\nRun `help <name>` for the arguments of a name."
+}
+
+/// Usage line and description of a single name.
+pub fn topic(name: &str) -> Option<String> {
+    let (args, description) = usage(name)?;
+    let synopsis = match args {
+        "" => name.to_string(),
+        args => format!("{name} {args}"),
+    };
+    Some(format!("{synopsis}\n  {description}"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_usage() {
+        let names = Command::all()
+            .into_iter()
+            .chain(Producer::all())
+            .chain(Adapter::all())
+            .chain(Consumer::all());
+        for name in names {
+            assert!(usage(name).is_some(), "no usage for `{name}`");
+        }
+        assert_eq!(
+            topic("reload").unwrap(),
+            "reload\n  Sync notifications from GitHub again"
+        );
+        assert!(topic("tail").unwrap().starts_with("tail <n>\n"));
+        assert!(topic("nope").is_none());
+    }
+}
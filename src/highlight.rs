@@ -0,0 +1,186 @@
+//! Syntax highlighting of the command line while it is being typed.
+
+use nu_ansi_term::{Color, Style};
+use reedline::{Highlighter, StyledText};
+
+use crate::{
+    completion::options,
+    parser::{
+        is_list_filter,
+        types::{Adapter, Command, Consumer, Producer},
+    },
+};
+
+pub fn highlighter() -> impl Highlighter {
+    ReplHighlighter
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Command,
+    Producer,
+    Adapter,
+    Consumer,
+    /// Filters of `list` and known options of other names.
+    Filter,
+    Index,
+    /// Whitespace, pipes, redirections and other arguments.
+    Text,
+    /// Unknown names and filters, and unterminated quotes.
+    Invalid,
+}
+
+impl Kind {
+    fn style(self) -> Style {
+        match self {
+            Kind::Command => Color::White.bold(),
+            Kind::Producer => Color::Green.bold(),
+            Kind::Adapter => Color::Yellow.normal(),
+            Kind::Consumer => Color::Blue.bold(),
+            Kind::Filter => Color::Cyan.normal(),
+            Kind::Index => Color::Purple.normal(),
+            Kind::Text => Style::default(),
+            Kind::Invalid => Color::Red.normal(),
+        }
+    }
+}
+
+struct ReplHighlighter;
+
+impl Highlighter for ReplHighlighter {
+    fn highlight(&self, line: &str, _cursor: usize) -> StyledText {
+        let mut styled = StyledText::new();
+        for (kind, token) in classify(line) {
+            styled.push((kind.style(), token.to_string()));
+        }
+        styled
+    }
+}
+
+/// Length of the word at the start of `input`, and whether its quotes are
+/// all closed. Words end at whitespace or a pipe outside of quotes.
+fn word_len(input: &str) -> (usize, bool) {
+    let mut quoted = false;
+    let mut chars = input.char_indices();
+    while let Some((i, ch)) = chars.next() {
+        match ch {
+            '\\' => {
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ch if !quoted && (ch.is_whitespace() || ch == '|') => return (i, true),
+            _ => {}
+        }
+    }
+    (input.len(), !quoted)
+}
+
+/// Splits `line` into tokens covering all of it, each with the kind it is
+/// highlighted as.
+fn classify(line: &str) -> Vec<(Kind, &str)> {
+    let mut tokens = Vec::new();
+    // Index of the current part of the pipeline and its first word
+    let mut segment = 0;
+    let mut name = None;
+    let mut redirect = false;
+
+    let mut rest = line;
+    while let Some(ch) = rest.chars().next() {
+        let (len, terminated) = match ch {
+            ch if ch.is_whitespace() => (
+                rest.find(|ch: char| !ch.is_whitespace())
+                    .unwrap_or(rest.len()),
+                true,
+            ),
+            '|' | '>' => (1, true),
+            _ => word_len(rest),
+        };
+        let (token, rem) = rest.split_at(len);
+        let kind = match ch {
+            ch if ch.is_whitespace() => Kind::Text,
+            '|' => {
+                segment += 1;
+                name = None;
+                Kind::Text
+            }
+            '>' => {
+                redirect = true;
+                Kind::Text
+            }
+            _ if !terminated => Kind::Invalid,
+            _ if redirect => Kind::Text,
+            _ => match name {
+                None => {
+                    name = Some(token);
+                    name_kind(token, segment)
+                }
+                Some(name) => arg_kind(name, segment, token),
+            },
+        };
+        tokens.push((kind, token));
+        rest = rem;
+    }
+    tokens
+}
+
+fn name_kind(name: &str, segment: usize) -> Kind {
+    if segment == 0 {
+        if name == "help" || name == "new" || Command::all().contains(&name) {
+            return Kind::Command;
+        }
+        if Producer::all().contains(&name) {
+            return Kind::Producer;
+        }
+    } else if Adapter::all().contains(&name) {
+        return Kind::Adapter;
+    }
+    match Consumer::all().contains(&name) {
+        true => Kind::Consumer,
+        false => Kind::Invalid,
+    }
+}
+
+fn arg_kind(name: &str, segment: usize, arg: &str) -> Kind {
+    let is_index = |arg: &str| {
+        let mut bounds = arg.splitn(2, '-');
+        bounds.all(|n| !n.is_empty() && n.chars().all(|ch| ch.is_ascii_digit()))
+    };
+    match name {
+        _ if arg == "$_" => Kind::Index,
+        "list" if segment == 0 && is_list_filter(arg) => Kind::Filter,
+        "list" if segment == 0 => Kind::Invalid,
+        _ if segment == 0 && Consumer::all().contains(&name) && is_index(arg) => Kind::Index,
+        _ if options(name).contains(&arg) => Kind::Filter,
+        _ => Kind::Text,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        let kinds = |line| -> Vec<_> {
+            classify(line)
+                .into_iter()
+                .filter(|(_, token)| !token.trim().is_empty() && *token != "|")
+                .map(|(kind, _)| kind)
+                .collect()
+        };
+        use Kind::*;
+        assert_eq!(
+            kinds("list pr !repo:a/b | sort updated|done > out.txt"),
+            [Producer, Filter, Filter, Adapter, Filter, Consumer, Text, Text]
+        );
+        assert_eq!(kinds("list prs | bogus"), [Producer, Invalid, Invalid]);
+        assert_eq!(kinds("done 1 3-5 $_"), [Consumer, Index, Index, Index]);
+        assert_eq!(kinds("help grep"), [Command, Text]);
+        assert_eq!(kinds("search \"build failed\" x"), [Producer, Text, Text]);
+        assert_eq!(kinds("search \"build | done"), [Producer, Invalid]);
+
+        let line = "list  pr|grep \"a b\"";
+        let tokens: String = classify(line).into_iter().map(|(_, t)| t).collect();
+        assert_eq!(tokens, line);
+    }
+}
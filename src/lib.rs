@@ -1,8 +1,23 @@
+pub mod client;
 pub mod completion;
+pub mod config;
+pub mod drafts;
+pub mod emoji;
 pub mod error;
+pub mod exec;
 pub mod github;
+pub mod graphics;
+pub mod help;
+pub mod highlight;
 pub mod line_editor;
+pub mod markdown;
+pub mod mutes;
 pub mod network;
+pub mod pager;
 pub mod parsec;
 pub mod parser;
+pub mod shell_completion;
+#[cfg(test)]
+mod snapshot;
+pub mod state;
 pub mod util;
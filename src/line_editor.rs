@@ -1,13 +1,27 @@
-use std::fmt::Display;
+use std::{
+    borrow::Cow,
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
+use nu_ansi_term::Color;
 use reedline::{
-    default_emacs_keybindings, ColumnarMenu, DefaultPrompt, DefaultPromptSegment, Emacs, KeyCode,
-    KeyModifiers, Prompt, Reedline, ReedlineEvent,
+    default_emacs_keybindings, ColumnarMenu, DefaultHinter, DefaultPrompt, DefaultPromptSegment,
+    Emacs, ExternalPrinter, FileBackedHistory, KeyCode, KeyModifiers, Prompt, PromptEditMode,
+    PromptHistorySearch, Reedline, ReedlineEvent, HISTORY_SIZE,
 };
 
-use crate::completion::completer;
+use crate::{
+    completion::{completer, SharedCompletionData},
+    highlight::highlighter,
+};
 
-pub fn line_editor() -> Reedline {
+/// Create the REPL line editor. Lines sent to `printer` are printed above
+/// the prompt while it waits for input.
+pub fn line_editor(
+    completion_data: SharedCompletionData,
+    printer: ExternalPrinter<String>,
+) -> Reedline {
     let completion_menu = Box::new(ColumnarMenu::default().with_name("completion_menu"));
     // Set up the required keybindings
     let mut keybindings = default_emacs_keybindings();
@@ -22,15 +36,77 @@ pub fn line_editor() -> Reedline {
 
     let edit_mode = Box::new(Emacs::new(keybindings));
 
-    Reedline::create()
-        .with_completer(Box::new(completer()))
+    let mut line_editor = Reedline::create().with_completer(Box::new(completer(completion_data)));
+    if crate::util::colors_enabled() {
+        line_editor = line_editor.with_highlighter(Box::new(highlighter()));
+    }
+    let line_editor = line_editor
+        // Suggest the rest of the line from history, accepted with the right arrow
+        .with_hinter(Box::new(
+            DefaultHinter::default().with_style(Color::DarkGray.italic()),
+        ))
         .with_edit_mode(edit_mode)
-        .with_menu(reedline::ReedlineMenu::EngineCompleter(completion_menu))
+        .with_external_printer(printer)
+        .with_menu(reedline::ReedlineMenu::EngineCompleter(completion_menu));
+
+    // Ctrl-R searches the history backwards with the default keybindings.
+    // If the history file can't be used, history is only kept in memory.
+    let history =
+        history_path().and_then(|path| FileBackedHistory::with_file(HISTORY_SIZE, path).ok());
+    match history {
+        Some(history) => line_editor.with_history(Box::new(history)),
+        None => line_editor,
+    }
+}
+
+/// File the REPL history is saved in, shared by all sessions.
+pub fn history_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|d| d.join("octerm").join("history"))
+}
+
+/// Prompt showing the number of loaded notifications, followed by the
+/// number of new ones on GitHub that have not been synced yet, as `12 (+3)`.
+/// `new` is read every time the prompt is drawn.
+pub fn prompt(count: usize, new: &AtomicUsize) -> impl Prompt + '_ {
+    CountPrompt {
+        count,
+        new,
+        default: DefaultPrompt::new(DefaultPromptSegment::Empty, DefaultPromptSegment::Empty),
+    }
 }
 
-pub fn prompt<T: Display>(p: T) -> impl Prompt {
-    DefaultPrompt::new(
-        DefaultPromptSegment::Basic(p.to_string()),
-        DefaultPromptSegment::Empty,
-    )
+struct CountPrompt<'a> {
+    count: usize,
+    new: &'a AtomicUsize,
+    /// Used for everything but the left prompt.
+    default: DefaultPrompt,
+}
+
+impl Prompt for CountPrompt<'_> {
+    fn render_prompt_left(&self) -> Cow<'_, str> {
+        match self.new.load(Ordering::Relaxed) {
+            0 => self.count.to_string().into(),
+            new => format!("{} (+{new})", self.count).into(),
+        }
+    }
+
+    fn render_prompt_right(&self) -> Cow<'_, str> {
+        self.default.render_prompt_right()
+    }
+
+    fn render_prompt_indicator(&self, prompt_mode: PromptEditMode) -> Cow<'_, str> {
+        self.default.render_prompt_indicator(prompt_mode)
+    }
+
+    fn render_prompt_multiline_indicator(&self) -> Cow<'_, str> {
+        self.default.render_prompt_multiline_indicator()
+    }
+
+    fn render_prompt_history_search_indicator(
+        &self,
+        history_search: PromptHistorySearch,
+    ) -> Cow<'_, str> {
+        self.default
+            .render_prompt_history_search_indicator(history_search)
+    }
 }
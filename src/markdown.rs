@@ -0,0 +1,1005 @@
+//! Renders the markdown bodies of issues, pull requests and comments into
+//! styled lines that can be printed to the terminal.
+
+use crossterm::style::{Color, ContentStyle, StyledContent, Stylize};
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag};
+
+/// A piece of text with a single style.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub content: String,
+    pub style: ContentStyle,
+}
+
+impl Span {
+    pub fn new(content: impl Into<String>, style: ContentStyle) -> Self {
+        Self {
+            content: content.into(),
+            style,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Line {
+    pub spans: Vec<Span>,
+    /// Url of an image on this line, which can be displayed below it.
+    pub image: Option<String>,
+}
+
+impl Line {
+    /// The text of the line without any styling.
+    pub fn plain(&self) -> String {
+        self.spans.iter().map(|s| s.content.as_str()).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spans.iter().all(|s| s.content.is_empty())
+    }
+
+    /// The text of the line with its styles as ANSI escape sequences.
+    pub fn to_ansi_string(&self) -> String {
+        self.spans
+            .iter()
+            .map(|span| StyledContent::new(span.style, &span.content).to_string())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RenderOptions {
+    /// Render the contents of `<details>` blocks instead of only their
+    /// summary.
+    pub expand_details: bool,
+    /// Convert `:shortcode:`s to emoji.
+    pub emoji: bool,
+    /// Show the language of code blocks above them.
+    pub code_headers: bool,
+    /// Number the lines of fenced code blocks.
+    pub code_line_numbers: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        let config = &crate::config::get().markdown;
+        Self {
+            expand_details: false,
+            emoji: config.emoji,
+            code_headers: config.code_headers,
+            code_line_numbers: config.code_line_numbers,
+        }
+    }
+}
+
+/// Render a markdown string into styled lines.
+pub fn render(markdown: &str) -> Vec<Line> {
+    render_with(markdown, RenderOptions::default())
+}
+
+/// Render a markdown string into ANSI colored text, with a newline after
+/// every line. Images are only shown by their placeholder text.
+pub fn render_ansi(markdown: &str) -> String {
+    render_ansi_with(markdown, RenderOptions::default())
+}
+
+pub fn render_ansi_with(markdown: &str, render_options: RenderOptions) -> String {
+    render_with(markdown, render_options)
+        .iter()
+        .map(|line| line.to_ansi_string() + "\n")
+        .collect()
+}
+
+pub fn render_with(markdown: &str, render_options: RenderOptions) -> Vec<Line> {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TASKLISTS);
+
+    let mut renderer = Renderer {
+        options: render_options,
+        ..Default::default()
+    };
+    for event in Parser::new_ext(markdown, options) {
+        renderer.handle(event);
+    }
+    renderer.finish()
+}
+
+/// A link found in a markdown string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    pub text: String,
+    pub url: String,
+}
+
+/// Collect the links in a markdown string: markdown links, images, bare urls
+/// and `#123` references, which are resolved against `repo_url` (like
+/// `https://github.com/owner/repo`). Each url is included only once.
+pub fn links(markdown: &str, repo_url: &str) -> Vec<Link> {
+    let mut links: Vec<Link> = Vec::new();
+    let mut push = |link: Link| {
+        if !links.iter().any(|l| l.url == link.url) {
+            links.push(link);
+        }
+    };
+    // Text and url of the link whose text is being read.
+    let mut current: Option<Link> = None;
+    let mut in_code_block = false;
+
+    for event in Parser::new_ext(markdown, Options::ENABLE_STRIKETHROUGH) {
+        match event {
+            Event::Start(Tag::Link(_, url, _) | Tag::Image(_, url, _)) => {
+                current = Some(Link {
+                    text: String::new(),
+                    url: url.to_string(),
+                })
+            }
+            Event::End(Tag::Link(..) | Tag::Image(..)) => {
+                if let Some(mut link) = current.take() {
+                    if link.text.is_empty() {
+                        link.text = link.url.clone();
+                    }
+                    push(link);
+                }
+            }
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(Tag::CodeBlock(_)) => in_code_block = false,
+            Event::Text(text) | Event::Code(text) if current.is_some() => {
+                if let Some(ref mut link) = current {
+                    link.text.push_str(&text);
+                }
+            }
+            Event::Text(text) if !in_code_block => {
+                for word in text.split_whitespace().map(trim_word) {
+                    if word.starts_with("https://") || word.starts_with("http://") {
+                        push(Link {
+                            text: word.to_string(),
+                            url: word.to_string(),
+                        });
+                    } else if let Some(reference) = Reference::parse(word) {
+                        push(Link {
+                            text: word.to_string(),
+                            url: reference.url(repo_url),
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    links
+}
+
+/// A fenced or indented code block in a markdown string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeBlock {
+    /// The language of the block, empty if not given.
+    pub lang: String,
+    pub code: String,
+}
+
+/// Collect the code blocks in a markdown string.
+pub fn code_blocks(markdown: &str) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    let mut current: Option<CodeBlock> = None;
+    for event in Parser::new_ext(markdown, Options::empty()) {
+        match event {
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let lang = match kind {
+                    CodeBlockKind::Fenced(lang) => lang.to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                current = Some(CodeBlock {
+                    lang,
+                    code: String::new(),
+                });
+            }
+            Event::Text(text) => {
+                if let Some(ref mut block) = current {
+                    block.code.push_str(&text);
+                }
+            }
+            Event::End(Tag::CodeBlock(_)) => blocks.extend(current.take()),
+            _ => {}
+        }
+    }
+    blocks
+}
+
+/// A mention of a user or a reference to an issue or PR, like `@octocat`,
+/// `#123` or `owner/repo#123`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reference<'a> {
+    Mention(&'a str),
+    Issue {
+        /// The `owner/repo` if the issue is in another repo.
+        repo: Option<&'a str>,
+        number: usize,
+    },
+}
+
+impl<'a> Reference<'a> {
+    pub fn parse(word: &'a str) -> Option<Self> {
+        let is_name = |s: &str| {
+            !s.is_empty()
+                && s.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        };
+
+        if let Some(user) = word.strip_prefix('@') {
+            return is_name(user).then_some(Self::Mention(user));
+        }
+        let (repo, number) = word.split_once('#')?;
+        if !number.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let number = number.parse().ok()?;
+        let repo = match repo.split_once('/') {
+            None if repo.is_empty() => None,
+            Some((owner, name)) if is_name(owner) && is_name(name) => Some(repo),
+            _ => return None,
+        };
+        Some(Self::Issue { repo, number })
+    }
+
+    /// Url of the user or issue. `repo_url` is used for issues in the same
+    /// repo.
+    pub fn url(&self, repo_url: &str) -> String {
+        match self {
+            Self::Mention(user) => format!("https://github.com/{user}"),
+            Self::Issue {
+                repo: Some(repo),
+                number,
+            } => format!("https://github.com/{repo}/issues/{number}"),
+            Self::Issue { repo: None, number } => format!("{repo_url}/issues/{number}"),
+        }
+    }
+}
+
+/// Strip the punctuation around a word in a sentence.
+fn trim_word(word: &str) -> &str {
+    word.trim_start_matches('(')
+        .trim_end_matches(&['.', ',', ')', ';', ':', '!', '?'][..])
+}
+
+/// The value of an attribute of an HTML tag.
+fn html_attr<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = tag;
+    loop {
+        let start = rest.find(name)?;
+        let before = rest[..start].chars().last();
+        rest = &rest[start + name.len()..];
+        let value = match rest.trim_start().strip_prefix('=') {
+            Some(value) if before.is_some_and(char::is_whitespace) => value.trim_start(),
+            _ => continue,
+        };
+        let quote = value.chars().next()?;
+        return match quote {
+            '"' | '\'' => value[1..].split(quote).next(),
+            _ => value.split(|c: char| c.is_whitespace() || c == '>').next(),
+        };
+    }
+}
+
+fn code_style() -> ContentStyle {
+    ContentStyle::new().with(Color::Yellow)
+}
+
+fn dim_style() -> ContentStyle {
+    ContentStyle::new().with(Color::DarkGrey)
+}
+
+/// Language tag used by GitHub for suggested changes in reviews.
+///
+/// Suggestions are rendered as added lines only. The lines they replace
+/// aren't in the markdown, and can't be recovered from the `diff_hunk` of
+/// the review comment either: the hunk ends at the commented line but
+/// doesn't say where a multi-line comment starts.
+const SUGGESTION_LANG: &str = "suggestion";
+
+#[derive(Default)]
+struct Renderer {
+    options: RenderOptions,
+    lines: Vec<Line>,
+    current: Line,
+    /// Stack of styles; the top is combined with inline styles as they
+    /// are encountered.
+    styles: Vec<ContentStyle>,
+    /// Nesting of lists, with the number of the next item for ordered lists.
+    lists: Vec<Option<u64>>,
+    /// Marker of the list item that has been started but has no content yet.
+    pending_marker: Option<Span>,
+    quote_depth: usize,
+    /// Language of the fenced code block being rendered, if any.
+    code_block: Option<String>,
+    /// Number of the last line rendered in the code block.
+    code_line: usize,
+    /// Nesting of `<details>` blocks.
+    details_depth: usize,
+    /// Depth of the collapsed `<details>` block whose contents are being
+    /// skipped.
+    hidden_depth: Option<usize>,
+    /// Text of the `<summary>` being read.
+    summary: Option<String>,
+    in_link: bool,
+    in_html_comment: bool,
+    /// Names of the open HTML tags that pushed a style.
+    html_styles: Vec<String>,
+}
+
+impl Renderer {
+    fn style(&self) -> ContentStyle {
+        self.styles.last().copied().unwrap_or_default()
+    }
+
+    fn push_style(&mut self, f: impl FnOnce(ContentStyle) -> ContentStyle) {
+        let style = f(self.style());
+        self.styles.push(style);
+    }
+
+    /// Push the quote bars and list indentation that every line inside
+    /// those blocks starts with.
+    fn start_line(&mut self) {
+        for _ in 0..self.quote_depth {
+            self.current.spans.push(Span::new("│ ", dim_style()));
+        }
+        match self.pending_marker.take() {
+            Some(marker) => {
+                let indent = "  ".repeat(self.lists.len().saturating_sub(1));
+                self.current
+                    .spans
+                    .push(Span::new(indent + &marker.content, marker.style));
+            }
+            None if !self.lists.is_empty() => {
+                let indent = "  ".repeat(self.lists.len());
+                self.current.spans.push(Span::new(indent, self.style()));
+            }
+            None => {}
+        }
+    }
+
+    fn push_span(&mut self, content: impl Into<String>, style: ContentStyle) {
+        if self.current.spans.is_empty() {
+            self.start_line();
+        }
+        self.current.spans.push(Span::new(content, style));
+    }
+
+    fn push_text(&mut self, text: &str) {
+        let style = self.style();
+        self.push_span(text, style);
+    }
+
+    /// Push text, highlighting mentions and issue references.
+    fn push_text_with_references(&mut self, text: &str) {
+        let text = match self.options.emoji {
+            true => crate::emoji::replace_shortcodes(text),
+            false => text.into(),
+        };
+        let text = text.as_ref();
+        let mut plain_start = 0;
+        for word in text.split_whitespace().map(trim_word) {
+            let style = match Reference::parse(word) {
+                Some(Reference::Mention(_)) => self.style().with(Color::Cyan).bold(),
+                Some(Reference::Issue { .. }) => self.style().with(Color::Blue),
+                None => continue,
+            };
+            let start = word.as_ptr() as usize - text.as_ptr() as usize;
+            if plain_start < start {
+                self.push_text(&text[plain_start..start]);
+            }
+            self.push_span(word, style);
+            plain_start = start + word.len();
+        }
+        if plain_start < text.len() {
+            self.push_text(&text[plain_start..]);
+        }
+    }
+
+    /// End the current line and start a new one.
+    fn flush(&mut self) {
+        let line = std::mem::take(&mut self.current);
+        self.lines.push(line);
+    }
+
+    /// Flush the current line if it has content.
+    fn flush_nonempty(&mut self) {
+        if !self.current.spans.is_empty() {
+            self.flush();
+        }
+    }
+
+    /// Separate blocks with an empty line.
+    fn blank_line(&mut self) {
+        self.flush_nonempty();
+        if self.lines.last().is_some_and(|l| !l.is_empty()) {
+            self.lines.push(Line::default());
+        }
+    }
+
+    fn handle(&mut self, event: Event) {
+        if let Event::Html(ref html) = event {
+            return self.handle_html(html);
+        }
+        if let Some(ref mut summary) = self.summary {
+            if let Event::Text(ref text) | Event::Code(ref text) = event {
+                summary.push_str(text);
+            }
+            return;
+        }
+        if self.hidden_depth.is_some() {
+            return;
+        }
+
+        match event {
+            Event::Start(tag) => self.start_tag(tag),
+            Event::End(tag) => self.end_tag(tag),
+            Event::Text(text) => match self.code_block {
+                Some(ref lang) => {
+                    let is_suggestion = lang == SUGGESTION_LANG;
+                    for line in text.lines() {
+                        if is_suggestion {
+                            let style = ContentStyle::new().with(Color::Green);
+                            self.push_span(format!("+ {line}"), style);
+                        } else if self.options.code_line_numbers {
+                            self.code_line += 1;
+                            self.push_span(format!("{:>3} │ ", self.code_line), dim_style());
+                            self.push_span(line, code_style());
+                        } else {
+                            self.push_span(format!("  {line}"), code_style());
+                        }
+                        self.flush();
+                    }
+                }
+                None if self.in_link => self.push_text(&text),
+                None => self.push_text_with_references(&text),
+            },
+            Event::Code(code) => self.push_span(code.to_string(), code_style()),
+            // Handled before skipping collapsed blocks.
+            Event::Html(_) => {}
+            // GitHub renders newlines in comments as line breaks.
+            Event::SoftBreak | Event::HardBreak => self.flush(),
+            Event::Rule => {
+                self.flush_nonempty();
+                self.push_span("─".repeat(40), dim_style());
+                self.blank_line();
+            }
+            Event::FootnoteReference(name) => self.push_text(&format!("[^{name}]")),
+            Event::TaskListMarker(checked) => {
+                // Replaces the bullet of the item.
+                self.pending_marker = Some(match checked {
+                    true => Span::new("☑ ", ContentStyle::new().with(Color::Green)),
+                    false => Span::new("☐ ", dim_style()),
+                });
+            }
+        }
+    }
+
+    /// Split raw HTML into tags and text. `<details>` blocks are collapsed to
+    /// their summary, comments are hidden and a few common tags are styled.
+    fn handle_html(&mut self, html: &str) {
+        let mut rest = html;
+        if self.in_html_comment {
+            match rest.find("-->") {
+                Some(end) => rest = &rest[end + 3..],
+                None => return,
+            }
+            self.in_html_comment = false;
+        }
+        while let Some(start) = rest.find('<') {
+            self.html_text(&rest[..start]);
+            rest = &rest[start..];
+            if rest.starts_with("<!--") {
+                match rest.find("-->") {
+                    Some(end) => rest = &rest[end + 3..],
+                    None => {
+                        self.in_html_comment = true;
+                        return;
+                    }
+                }
+                continue;
+            }
+            let end = rest.find('>').map_or(rest.len(), |i| i + 1);
+            self.html_tag(&rest[..end]);
+            rest = &rest[end..];
+        }
+        self.html_text(rest);
+    }
+
+    fn html_tag(&mut self, tag: &str) {
+        let name = tag
+            .trim_start_matches('<')
+            .trim_end_matches('>')
+            .trim_end_matches('/')
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match name.as_str() {
+            "details" => self.details_depth += 1,
+            "/details" => {
+                if self.hidden_depth == Some(self.details_depth) {
+                    self.hidden_depth = None;
+                }
+                self.details_depth = self.details_depth.saturating_sub(1);
+            }
+            "summary" => self.summary = Some(String::new()),
+            "/summary" => {
+                let summary = self.summary.take().unwrap_or_default();
+                if self.hidden_depth.is_some() {
+                    return;
+                }
+                self.flush_nonempty();
+                let marker = match self.options.expand_details {
+                    true => "▼ ",
+                    false => "▶ ",
+                };
+                self.push_span(marker, dim_style());
+                let style = self.style().bold();
+                self.push_span(summary.trim(), style);
+                self.flush();
+                if !self.options.expand_details {
+                    self.hidden_depth = Some(self.details_depth);
+                }
+            }
+            // Markup inside the summary and collapsed blocks is dropped.
+            _ if self.summary.is_some() || self.hidden_depth.is_some() => {}
+            "b" | "strong" => self.push_html_style(name, |s| s.bold()),
+            "i" | "em" => self.push_html_style(name, |s| s.italic()),
+            "code" => self.push_html_style(name, |_| code_style()),
+            "kbd" => self.push_html_style(name, |s| s.reverse()),
+            "a" => self.push_html_style(name, |s| s.with(Color::Blue).underlined()),
+            "th" => {
+                self.push_table_cell();
+                self.push_html_style(name, |s| s.bold());
+            }
+            "td" => self.push_table_cell(),
+            "br" => self.flush(),
+            "p" | "/p" | "div" | "/div" | "tr" | "/table" | "ul" | "/ul" | "ol" | "/ol" => {
+                self.flush_nonempty()
+            }
+            "li" => {
+                self.flush_nonempty();
+                self.push_span("• ", dim_style());
+            }
+            "hr" => {
+                self.flush_nonempty();
+                self.push_span("─".repeat(40), dim_style());
+                self.flush();
+            }
+            "img" => {
+                let alt = html_attr(tag, "alt").unwrap_or_default();
+                let src = html_attr(tag, "src").unwrap_or_default();
+                self.push_span(format!("[image: {alt}]({src})"), dim_style());
+                self.current.image = Some(src.to_string());
+            }
+            _ => match name.strip_prefix('/') {
+                Some(name) if self.html_styles.last().map(String::as_str) == Some(name) => {
+                    self.html_styles.pop();
+                    self.styles.pop();
+                }
+                // Other tags are dropped and only their text is shown.
+                _ => {}
+            },
+        }
+    }
+
+    /// Push the style of an HTML tag, to be popped when the tag is closed.
+    fn push_html_style(&mut self, tag: String, f: impl FnOnce(ContentStyle) -> ContentStyle) {
+        self.html_styles.push(tag);
+        self.push_style(f);
+    }
+
+    /// Pop the styles of HTML tags left open, so that a missing `</b>`
+    /// doesn't style the rest of the text.
+    fn close_html_styles(&mut self) {
+        for _ in self.html_styles.drain(..) {
+            self.styles.pop();
+        }
+    }
+
+    fn push_table_cell(&mut self) {
+        if !self.current.spans.is_empty() {
+            self.push_span(" │ ", dim_style());
+        }
+    }
+
+    fn html_text(&mut self, text: &str) {
+        if let Some(ref mut summary) = self.summary {
+            summary.push_str(text);
+            return;
+        }
+        if self.hidden_depth.is_some() {
+            return;
+        }
+        for (i, line) in text.split('\n').enumerate() {
+            if i > 0 {
+                self.flush_nonempty();
+            }
+            if !line.trim().is_empty() {
+                self.push_text(line.trim());
+            }
+        }
+    }
+
+    fn start_tag(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => {}
+            Tag::Heading(level, ..) => {
+                self.flush_nonempty();
+                let level = match level {
+                    HeadingLevel::H1 => 1,
+                    HeadingLevel::H2 => 2,
+                    HeadingLevel::H3 => 3,
+                    HeadingLevel::H4 => 4,
+                    HeadingLevel::H5 => 5,
+                    HeadingLevel::H6 => 6,
+                };
+                self.push_style(|s| s.with(Color::Magenta).bold());
+                self.push_text(&format!("{} ", "#".repeat(level)));
+            }
+            Tag::BlockQuote => {
+                self.flush_nonempty();
+                self.quote_depth += 1;
+            }
+            Tag::CodeBlock(kind) => {
+                self.flush_nonempty();
+                let lang = match kind {
+                    CodeBlockKind::Fenced(lang) => lang.to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                if lang == SUGGESTION_LANG {
+                    // Only the new lines are part of the comment; the lines
+                    // being replaced are in the diff of the review thread.
+                    let style = ContentStyle::new().with(Color::Green).bold();
+                    self.push_span("Suggested change:", style);
+                    self.flush();
+                } else if self.options.code_headers && !lang.is_empty() {
+                    self.push_span(format!("── {lang} ──"), dim_style());
+                    self.flush();
+                }
+                self.code_line = 0;
+                self.code_block = Some(lang);
+            }
+            Tag::List(start) => {
+                self.flush_nonempty();
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                self.flush_nonempty();
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}. ", *number - 1)
+                    }
+                    _ => "• ".to_string(),
+                };
+                self.pending_marker = Some(Span::new(marker, dim_style()));
+            }
+            Tag::Emphasis => self.push_style(|s| s.italic()),
+            Tag::Strong => self.push_style(|s| s.bold()),
+            Tag::Strikethrough => self.push_style(|s| s.crossed_out()),
+            Tag::Link(..) => {
+                self.in_link = true;
+                self.push_style(|s| s.with(Color::Blue).underlined());
+            }
+            Tag::Image(..) => {
+                self.push_style(|_| dim_style());
+                self.push_text("[image: ");
+            }
+            Tag::FootnoteDefinition(name) => {
+                self.flush_nonempty();
+                self.push_text(&format!("[^{name}]: "));
+            }
+            Tag::Table(_) | Tag::TableHead | Tag::TableRow | Tag::TableCell => {}
+        }
+    }
+
+    fn end_tag(&mut self, tag: Tag) {
+        if let Tag::Paragraph | Tag::Heading(..) | Tag::Item | Tag::TableCell = tag {
+            self.close_html_styles();
+        }
+        match tag {
+            Tag::Paragraph => self.blank_line(),
+            Tag::Heading(..) => {
+                self.styles.pop();
+                self.blank_line();
+            }
+            Tag::BlockQuote => {
+                self.flush_nonempty();
+                self.quote_depth -= 1;
+                self.blank_line();
+            }
+            Tag::CodeBlock(_) => {
+                self.code_block = None;
+                self.blank_line();
+            }
+            Tag::List(_) => {
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.blank_line();
+                }
+            }
+            Tag::Item => {
+                // Items with no content still have their marker rendered.
+                if self.pending_marker.is_some() {
+                    self.start_line();
+                }
+                self.flush_nonempty();
+            }
+            Tag::Image(_, url, _) => {
+                self.push_text(&format!("]({url})"));
+                self.current.image = Some(url.to_string());
+                self.styles.pop();
+            }
+            Tag::Link(..) => {
+                self.in_link = false;
+                self.styles.pop();
+            }
+            Tag::Emphasis | Tag::Strong | Tag::Strikethrough => {
+                self.styles.pop();
+            }
+            Tag::FootnoteDefinition(_) => self.blank_line(),
+            Tag::Table(_) | Tag::TableHead | Tag::TableRow | Tag::TableCell => {}
+        }
+    }
+
+    fn finish(mut self) -> Vec<Line> {
+        self.flush_nonempty();
+        while self.lines.last().is_some_and(Line::is_empty) {
+            self.lines.pop();
+        }
+        self.lines
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn plain(markdown: &str) -> Vec<String> {
+        render(markdown).iter().map(Line::plain).collect()
+    }
+
+    #[test]
+    fn test_paragraphs() {
+        assert_eq!(
+            plain("Hello *world*\nnext line\n\nSecond paragraph"),
+            ["Hello world", "next line", "", "Second paragraph"]
+        );
+        assert_eq!(plain("# Title\nbody"), ["# Title", "", "body"]);
+        assert_eq!(plain("Done :tada: `:tada:`"), ["Done 🎉 :tada:"]);
+    }
+
+    #[test]
+    fn test_lists_and_quotes() {
+        assert_eq!(
+            plain("- one\n- two\n  - nested\n\nafter"),
+            ["• one", "• two", "  • nested", "", "after"]
+        );
+        assert_eq!(plain("> quoted\n> text"), ["│ quoted", "│ text"]);
+        assert_eq!(
+            plain("3. three\n4. four\n   1. nested\n   2. again\n5. five"),
+            [
+                "3. three",
+                "4. four",
+                "  1. nested",
+                "  2. again",
+                "5. five"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_task_lists() {
+        let lines = render("- [ ] todo\n- [x] done");
+        assert_eq!(
+            lines.iter().map(Line::plain).collect::<Vec<_>>(),
+            ["☐ todo", "☑ done"]
+        );
+        assert_eq!(lines[1].spans[0].style.foreground_color, Some(Color::Green));
+    }
+
+    #[test]
+    fn test_details() {
+        let markdown = "<details>\n<summary>Full <b>log</b></summary>\n\n```\nerror\n```\n\n</details>\n\nafter";
+        assert_eq!(plain(markdown), ["▶ Full log", "after"]);
+
+        let expanded = render_with(
+            markdown,
+            RenderOptions {
+                expand_details: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            expanded.iter().map(Line::plain).collect::<Vec<_>>(),
+            ["▼ Full log", "  error", "", "after"]
+        );
+    }
+
+    #[test]
+    fn test_unclosed_html_style() {
+        let lines = render("Some <b>bold\n\n## Heading <i>tilted\n\nplain text");
+        assert_eq!(
+            lines.iter().map(Line::plain).collect::<Vec<_>>(),
+            ["Some bold", "", "## Heading tilted", "", "plain text"]
+        );
+        let bold = crossterm::style::Attribute::Bold;
+        assert!(lines[0].spans[1].style.attributes.has(bold));
+        assert_eq!(lines[4].spans[0].style, ContentStyle::default());
+    }
+
+    #[test]
+    fn test_html() {
+        let lines = render("Press <kbd>Ctrl</kbd> and <b>hold</b><br>done");
+        assert_eq!(
+            lines.iter().map(Line::plain).collect::<Vec<_>>(),
+            ["Press Ctrl and hold", "done"]
+        );
+        assert!(lines[0].spans[1]
+            .style
+            .attributes
+            .has(crossterm::style::Attribute::Reverse));
+        assert!(!lines[0].spans[2]
+            .style
+            .attributes
+            .has(crossterm::style::Attribute::Reverse));
+
+        assert_eq!(
+            plain("<!-- Describe\nyour change -->\nThe change"),
+            ["The change"]
+        );
+        assert_eq!(
+            plain("<img width=\"10\" alt=\"screenshot\" src='a.png'>"),
+            ["[image: screenshot](a.png)"]
+        );
+        assert_eq!(
+            plain("<table>\n<tr><th>Name</th><th>Size</th></tr>\n<tr><td>a</td><td>1</td></tr>\n</table>"),
+            ["Name │ Size", "a │ 1"]
+        );
+        assert_eq!(plain("<p align=\"center\">Logo</p>"), ["Logo"]);
+    }
+
+    #[test]
+    fn test_images() {
+        let lines = render("see ![a cat](https://example.com/cat.png)");
+        assert_eq!(
+            lines[0].plain(),
+            "see [image: a cat](https://example.com/cat.png)"
+        );
+        assert_eq!(
+            lines[0].image.as_deref(),
+            Some("https://example.com/cat.png")
+        );
+    }
+
+    #[test]
+    fn test_links() {
+        let markdown = "See [the docs](https://docs.rs) and https://example.com/a.\n\
+                        Fixes #12 by @ab, see `#13` and ![logo](https://x.com/l.png)\n\
+                        ```\nhttps://in.code\n```\n[again](https://docs.rs)";
+        let links: Vec<_> = links(markdown, "https://github.com/o/r")
+            .into_iter()
+            .map(|l| (l.text, l.url))
+            .collect();
+        assert_eq!(
+            links,
+            [
+                ("the docs".into(), "https://docs.rs".into()),
+                (
+                    "https://example.com/a".into(),
+                    "https://example.com/a".into()
+                ),
+                ("#12".into(), "https://github.com/o/r/issues/12".into()),
+                ("@ab".into(), "https://github.com/ab".into()),
+                ("logo".into(), "https://x.com/l.png".into()),
+            ] as [(String, String); 5]
+        );
+    }
+
+    #[test]
+    fn test_references() {
+        assert_eq!(
+            Reference::parse("@octocat"),
+            Some(Reference::Mention("octocat"))
+        );
+        assert_eq!(
+            Reference::parse("#12"),
+            Some(Reference::Issue {
+                repo: None,
+                number: 12
+            })
+        );
+        assert_eq!(
+            Reference::parse("rust-lang/rust#1"),
+            Some(Reference::Issue {
+                repo: Some("rust-lang/rust"),
+                number: 1
+            })
+        );
+        assert_eq!(Reference::parse("#"), None);
+        assert_eq!(Reference::parse("#+1"), None);
+        assert_eq!(Reference::parse("a#1"), None);
+        assert_eq!(Reference::parse("@"), None);
+        assert_eq!(Reference::parse("email@example.com"), None);
+
+        let lines = render("cc @octocat, see #12.");
+        let spans: Vec<_> = lines[0].spans.iter().map(|s| s.content.as_str()).collect();
+        assert_eq!(spans, ["cc ", "@octocat", ", see ", "#12", "."]);
+        assert_eq!(lines[0].spans[1].style.foreground_color, Some(Color::Cyan));
+        assert_eq!(lines[0].spans[3].style.foreground_color, Some(Color::Blue));
+    }
+
+    #[test]
+    fn test_code_blocks() {
+        assert_eq!(
+            plain("```rust\nfn main() {}\n```\ntext"),
+            ["  fn main() {}", "", "text"]
+        );
+    }
+
+    #[test]
+    fn test_collect_code_blocks() {
+        let blocks = code_blocks("text `inline`\n```toml\n[a]\nb = 1\n```\n\n    indented\n");
+        assert_eq!(
+            blocks,
+            [
+                CodeBlock {
+                    lang: "toml".to_string(),
+                    code: "[a]\nb = 1\n".to_string()
+                },
+                CodeBlock {
+                    lang: String::new(),
+                    code: "indented\n".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_code_block_decorations() {
+        let lines = render_with(
+            "```rust\nfn main() {\n}\n```",
+            RenderOptions {
+                code_headers: true,
+                code_line_numbers: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            lines.iter().map(Line::plain).collect::<Vec<_>>(),
+            ["── rust ──", "  1 │ fn main() {", "  2 │ }"]
+        );
+    }
+
+    #[test]
+    fn test_suggestion_block() {
+        let lines = render("```suggestion\nlet x = 1;\n```");
+        assert_eq!(
+            lines.iter().map(Line::plain).collect::<Vec<_>>(),
+            ["Suggested change:", "+ let x = 1;"]
+        );
+        assert_eq!(lines[1].spans[0].style.foreground_color, Some(Color::Green));
+    }
+
+    #[test]
+    fn test_render_ansi() {
+        let text = render_ansi("Hello **world**\n\nbye");
+        assert_eq!(crate::util::strip_ansi(&text), "Hello world\n\nbye\n");
+        assert!(text.contains(&"world".bold().to_string()));
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let markdown = "## Summary\n\
+            Fixes **the crash** in `helix-term` reported in #12 :tada:\n\n\
+            - [x] Add a test\n\
+            - [ ] Update the [docs](https://docs.helix-editor.com)\n  1. book\n  2. changelog\n\n\
+            > Tested on *Linux* only\n\n\
+            ```rust\n\
+            fn main() {}\n\
+            ```\n\n\
+            <details><summary>Logs</summary>\n\npanicked at 'index out of bounds'\n</details>\n";
+        crate::snapshot::assert_snapshot("markdown", &render_ansi(markdown));
+    }
+}
@@ -0,0 +1,107 @@
+//! Persistent mute rules, stored in `$XDG_DATA_HOME/octerm/mutes.toml`.
+//! Notifications matched by a rule are hidden after every sync.
+
+use std::path::PathBuf;
+
+use octocrab::models::activity::Notification as OctoNotification;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{Error, Result},
+    github::Notification,
+};
+
+#[derive(Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MuteRules {
+    /// Full names of muted repositories, like `helix-editor/helix`.
+    pub repos: Vec<String>,
+    /// Ids of muted notification threads.
+    pub threads: Vec<u64>,
+}
+
+impl MuteRules {
+    pub fn path() -> Option<PathBuf> {
+        dirs::data_dir().map(|d| d.join("octerm").join("mutes.toml"))
+    }
+
+    /// Read the saved rules, returning no rules if none have been saved.
+    pub fn load() -> Result<Self> {
+        let path = match Self::path() {
+            Some(path) => path,
+            None => return Ok(Self::default()),
+        };
+        match std::fs::read_to_string(path) {
+            Ok(contents) => toml::from_str(&contents).map_err(Error::StateParse),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(Error::StateRead(err)),
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path().ok_or_else(|| {
+            Error::StateWrite(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "no data directory",
+            ))
+        })?;
+        let contents = toml::to_string(self).map_err(|err| {
+            Error::StateWrite(std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+        })?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(Error::StateWrite)?;
+        }
+        std::fs::write(path, contents).map_err(Error::StateWrite)
+    }
+
+    pub fn mute_repo(&mut self, full_name: &str) {
+        if !self.repos.iter().any(|r| r.eq_ignore_ascii_case(full_name)) {
+            self.repos.push(full_name.to_string());
+        }
+    }
+
+    pub fn mute_thread(&mut self, id: u64) {
+        if !self.threads.contains(&id) {
+            self.threads.push(id);
+        }
+    }
+
+    pub fn is_muted(&self, notification: &Notification) -> bool {
+        self.is_thread_muted(&notification.inner)
+    }
+
+    /// Like [`Self::is_muted`] for a thread fetched directly from GitHub.
+    pub fn is_thread_muted(&self, thread: &OctoNotification) -> bool {
+        let repo = &thread.repository;
+        let full_name = repo.full_name.as_ref().unwrap_or(&repo.name);
+        self.matches(full_name, thread.id.0)
+    }
+
+    fn matches(&self, repo: &str, thread: u64) -> bool {
+        self.threads.contains(&thread) || self.repos.iter().any(|r| r.eq_ignore_ascii_case(repo))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_mute_rules() {
+        let mut rules = MuteRules::default();
+        rules.mute_repo("helix-editor/helix");
+        rules.mute_repo("Helix-Editor/Helix");
+        rules.mute_thread(42);
+        rules.mute_thread(42);
+        assert_eq!(rules.repos.len(), 1);
+        assert_eq!(rules.threads, vec![42]);
+
+        assert!(rules.matches("helix-editor/helix", 1));
+        assert!(rules.matches("sudormrfbin/octerm", 42));
+        assert!(!rules.matches("sudormrfbin/octerm", 1));
+
+        let saved = toml::to_string(&rules).unwrap();
+        assert_eq!(toml::from_str::<MuteRules>(&saved).unwrap(), rules);
+        assert!(toml::from_str::<MuteRules>("users = []").is_err());
+    }
+}
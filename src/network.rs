@@ -1,7 +1,12 @@
+pub mod backend;
+pub mod cache;
+pub mod fixture;
 pub mod graphql;
 pub mod methods;
+pub mod stats;
 
 /// Helper struct used to send the parameters for a issues timeline api call.
+#[allow(dead_code)]
 #[derive(serde::Serialize)]
 struct TimelineParams {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -0,0 +1,182 @@
+//! The GitHub API calls made while running pipelines, behind a trait so
+//! that they can be answered by [`MockBackend`] in tests.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use futures::{future::BoxFuture, FutureExt};
+use octocrab::models::NotificationId;
+
+use super::{fixture::Fixture, methods};
+use crate::{
+    client::NotificationClient,
+    error::{Error, Result},
+    github::{
+        events::Event, Discussion, DiscussionMeta, Notification, NotificationTarget, RepoMeta,
+    },
+};
+
+pub trait GithubBackend: Send + Sync {
+    /// All notifications with their targets, newest first.
+    fn notifications(&self) -> BoxFuture<'_, Result<Vec<Notification>>>;
+
+    /// All notifications, newest first, with their targets left
+    /// [`NotificationTarget::Pending`] for [`Self::details`] to fetch.
+    fn threads(&self) -> BoxFuture<'_, Result<Vec<Notification>>> {
+        self.notifications()
+    }
+
+    /// Fill in the target of a notification if it is pending.
+    fn details(&self, notification: Notification) -> BoxFuture<'_, Result<Notification>> {
+        futures::future::ready(Ok(notification)).boxed()
+    }
+
+    fn issue_timeline<'a>(
+        &'a self,
+        repo: &'a RepoMeta,
+        number: usize,
+    ) -> BoxFuture<'a, Result<Option<Vec<Event>>>>;
+
+    fn pr_timeline<'a>(
+        &'a self,
+        repo: &'a RepoMeta,
+        number: usize,
+    ) -> BoxFuture<'a, Result<Option<Vec<Event>>>>;
+
+    fn discussion(&self, meta: DiscussionMeta) -> BoxFuture<'_, Result<Option<Discussion>>>;
+
+    fn mark_notification_as_read(&self, id: NotificationId) -> BoxFuture<'_, Result<()>>;
+}
+
+/// Calls the GitHub API through octocrab.
+impl GithubBackend for NotificationClient {
+    fn notifications(&self) -> BoxFuture<'_, Result<Vec<Notification>>> {
+        self.fetch_all().boxed()
+    }
+
+    fn threads(&self) -> BoxFuture<'_, Result<Vec<Notification>>> {
+        self.fetch_threads().boxed()
+    }
+
+    fn details(&self, notification: Notification) -> BoxFuture<'_, Result<Notification>> {
+        NotificationClient::details(self, notification).boxed()
+    }
+
+    fn issue_timeline<'a>(
+        &'a self,
+        repo: &'a RepoMeta,
+        number: usize,
+    ) -> BoxFuture<'a, Result<Option<Vec<Event>>>> {
+        methods::issue_timeline(self.octocrab(), &repo.owner, &repo.name, number).boxed()
+    }
+
+    fn pr_timeline<'a>(
+        &'a self,
+        repo: &'a RepoMeta,
+        number: usize,
+    ) -> BoxFuture<'a, Result<Option<Vec<Event>>>> {
+        methods::pr_timeline(self.octocrab(), &repo.owner, &repo.name, number).boxed()
+    }
+
+    fn discussion(&self, meta: DiscussionMeta) -> BoxFuture<'_, Result<Option<Discussion>>> {
+        methods::discussion(self.octocrab(), meta).boxed()
+    }
+
+    fn mark_notification_as_read(&self, id: NotificationId) -> BoxFuture<'_, Result<()>> {
+        methods::mark_notification_as_read(self.octocrab(), id).boxed()
+    }
+}
+
+/// Answers every call with canned data instead of calling GitHub.
+#[derive(Default)]
+pub struct MockBackend {
+    pub notifications: Vec<Notification>,
+    /// Timelines of issues and pull requests, keyed by [`Fixture::key`].
+    /// Threads without a timeline have no events.
+    pub timelines: HashMap<String, Vec<Event>>,
+    /// Discussions, keyed by [`Fixture::key`].
+    pub discussions: HashMap<String, Discussion>,
+    /// Return notifications without their targets from
+    /// [`GithubBackend::threads`].
+    pub lazy: bool,
+    /// Notifications whose targets were fetched with
+    /// [`GithubBackend::details`].
+    pub detailed: Mutex<Vec<NotificationId>>,
+    /// Threads that fail to be marked as read.
+    pub failing: Vec<NotificationId>,
+    /// Threads marked as read so far.
+    pub marked: Mutex<Vec<NotificationId>>,
+}
+
+impl MockBackend {
+    pub fn new(notifications: Vec<Notification>) -> Self {
+        Self {
+            notifications,
+            ..Self::default()
+        }
+    }
+
+    fn timeline(&self, repo: &RepoMeta, number: usize) -> Option<Vec<Event>> {
+        let key = Fixture::key(repo, number);
+        Some(self.timelines.get(&key).cloned().unwrap_or_default())
+    }
+}
+
+impl GithubBackend for MockBackend {
+    fn notifications(&self) -> BoxFuture<'_, Result<Vec<Notification>>> {
+        futures::future::ready(Ok(self.notifications.clone())).boxed()
+    }
+
+    fn threads(&self) -> BoxFuture<'_, Result<Vec<Notification>>> {
+        let mut notifications = self.notifications.clone();
+        if self.lazy {
+            for n in &mut notifications {
+                n.target = NotificationTarget::Pending;
+            }
+        }
+        futures::future::ready(Ok(notifications)).boxed()
+    }
+
+    fn details(&self, notification: Notification) -> BoxFuture<'_, Result<Notification>> {
+        let id = notification.inner.id;
+        let result = match self.notifications.iter().find(|n| n.inner.id == id) {
+            Some(n) if matches!(notification.target, NotificationTarget::Pending) => {
+                self.detailed.lock().unwrap().push(id);
+                n.clone()
+            }
+            _ => notification,
+        };
+        futures::future::ready(Ok(result)).boxed()
+    }
+
+    fn issue_timeline<'a>(
+        &'a self,
+        repo: &'a RepoMeta,
+        number: usize,
+    ) -> BoxFuture<'a, Result<Option<Vec<Event>>>> {
+        futures::future::ready(Ok(self.timeline(repo, number))).boxed()
+    }
+
+    fn pr_timeline<'a>(
+        &'a self,
+        repo: &'a RepoMeta,
+        number: usize,
+    ) -> BoxFuture<'a, Result<Option<Vec<Event>>>> {
+        futures::future::ready(Ok(self.timeline(repo, number))).boxed()
+    }
+
+    fn discussion(&self, meta: DiscussionMeta) -> BoxFuture<'_, Result<Option<Discussion>>> {
+        let key = Fixture::key(&meta.repo, meta.number);
+        futures::future::ready(Ok(self.discussions.get(&key).cloned())).boxed()
+    }
+
+    fn mark_notification_as_read(&self, id: NotificationId) -> BoxFuture<'_, Result<()>> {
+        let result = match self.failing.contains(&id) {
+            true => Err(Error::NetworkTask),
+            false => {
+                self.marked.lock().unwrap().push(id);
+                Ok(())
+            }
+        };
+        futures::future::ready(result).boxed()
+    }
+}
@@ -0,0 +1,125 @@
+//! REST responses kept with their ETags for the rest of the session. A
+//! cached response is revalidated with `If-None-Match`, and GitHub answers
+//! with an empty `304 Not Modified` that doesn't count against the rate
+//! limit if it is unchanged, so refreshing an unchanged inbox is nearly free.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use octocrab::Octocrab;
+use once_cell::sync::Lazy;
+use reqwest::{
+    header::{ETAG, IF_NONE_MATCH},
+    Method, StatusCode, Url,
+};
+use serde::de::DeserializeOwned;
+
+use super::stats;
+use crate::error::{Error, Result};
+
+#[derive(Clone)]
+struct Cached {
+    etag: String,
+    body: String,
+}
+
+/// Responses keyed by their url.
+static CACHE: Lazy<Mutex<HashMap<String, Cached>>> = Lazy::new(Mutex::default);
+
+/// Like [`Octocrab::get`] for a url from the API, but served from the cache
+/// if it hasn't changed since it was last fetched.
+pub async fn get<R: DeserializeOwned>(octo: &Octocrab, url: &Url) -> Result<R> {
+    let cached = CACHE.lock().unwrap().get(url.as_str()).cloned();
+    let mut request = octo.request_builder(url.clone(), Method::GET);
+    if let Some(ref cached) = cached {
+        request = request.header(IF_NONE_MATCH, &cached.etag);
+    }
+    let response = stats::rest(octo.execute(request)).await?;
+
+    let body = match cached {
+        Some(cached) if response.status() == StatusCode::NOT_MODIFIED => cached.body,
+        _ => {
+            let response = octocrab::map_github_error(response).await?;
+            let etag = response
+                .headers()
+                .get(ETAG)
+                .and_then(|etag| etag.to_str().ok())
+                .map(str::to_string);
+            let body = response.text().await.map_err(Error::GitHubResponse)?;
+            if let Some(etag) = etag {
+                let cached = Cached {
+                    etag,
+                    body: body.clone(),
+                };
+                CACHE.lock().unwrap().insert(url.to_string(), cached);
+            }
+            body
+        }
+    };
+    serde_json::from_str(&body).map_err(Error::GitHubJson)
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+    };
+
+    use super::*;
+
+    /// Answer requests like GitHub would for a resource with the ETag
+    /// `"v1"`, returning the `If-None-Match` header of each request.
+    fn serve(listener: TcpListener, requests: usize) -> std::thread::JoinHandle<Vec<String>> {
+        std::thread::spawn(move || {
+            let mut seen = Vec::new();
+            for stream in listener.incoming().take(requests) {
+                let mut stream = stream.unwrap();
+                let mut if_none_match = String::new();
+                for line in BufReader::new(&stream).lines() {
+                    let line = line.unwrap();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(": ") {
+                        if name.eq_ignore_ascii_case("if-none-match") {
+                            if_none_match = value.to_string();
+                        }
+                    }
+                }
+                let response = match if_none_match.as_str() {
+                    "\"v1\"" => {
+                        "HTTP/1.1 304 Not Modified\r\nconnection: close\r\n\r\n".to_string()
+                    }
+                    _ => {
+                        let body = r#"{"number":12}"#;
+                        format!(
+                            "HTTP/1.1 200 OK\r\netag: \"v1\"\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                            body.len()
+                        )
+                    }
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+                seen.push(if_none_match);
+            }
+            seen
+        })
+    }
+
+    #[tokio::test]
+    async fn test_revalidate() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!(
+            "http://{}/repos/helix-editor/helix/issues/12",
+            listener.local_addr().unwrap()
+        ))
+        .unwrap();
+        let server = serve(listener, 2);
+
+        let octo = Octocrab::default();
+        for _ in 0..2 {
+            let issue: serde_json::Value = get(&octo, &url).await.unwrap();
+            assert_eq!(issue["number"], 12);
+        }
+        assert_eq!(server.join().unwrap(), ["", "\"v1\""]);
+    }
+}
@@ -0,0 +1,220 @@
+//! Notifications, timelines and discussions saved to a JSON file, so that
+//! octerm can be run offline with `--fixture`. Fixtures are written by
+//! running with `--record`, or by hand from the serialized models.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use futures::{future::BoxFuture, FutureExt};
+use octocrab::models::NotificationId;
+use serde::{Deserialize, Serialize};
+
+use super::backend::{GithubBackend, MockBackend};
+use crate::{
+    error::{Error, Result},
+    github::{events::Event, Discussion, DiscussionMeta, Notification, RepoMeta},
+};
+
+#[derive(Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Fixture {
+    pub notifications: Vec<Notification>,
+    /// Timelines of issues and pull requests, keyed like
+    /// `helix-editor/helix#1234`.
+    pub timelines: BTreeMap<String, Vec<Event>>,
+    /// Discussions, keyed like timelines.
+    pub discussions: BTreeMap<String, Discussion>,
+}
+
+impl Fixture {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path).map_err(Error::FixtureRead)?;
+        serde_json::from_str(&contents).map_err(Error::FixtureParse)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = serde_json::to_string_pretty(self).map_err(|err| {
+            Error::FixtureWrite(std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+        })?;
+        std::fs::write(path, contents).map_err(Error::FixtureWrite)
+    }
+
+    pub fn key(repo: &RepoMeta, number: usize) -> String {
+        format!("{}/{}#{number}", repo.owner, repo.name)
+    }
+}
+
+impl From<Fixture> for MockBackend {
+    fn from(fixture: Fixture) -> Self {
+        Self {
+            notifications: fixture.notifications,
+            timelines: fixture.timelines.into_iter().collect(),
+            discussions: fixture.discussions.into_iter().collect(),
+            ..Self::default()
+        }
+    }
+}
+
+/// Passes every call on to another backend, saving what it returns to a
+/// fixture file as it goes.
+pub struct Recorder {
+    inner: Arc<dyn GithubBackend>,
+    path: PathBuf,
+    fixture: Mutex<Fixture>,
+}
+
+impl Recorder {
+    pub fn new(inner: Arc<dyn GithubBackend>, path: PathBuf) -> Self {
+        Self {
+            inner,
+            path,
+            fixture: Mutex::default(),
+        }
+    }
+
+    fn record(&self, update: impl FnOnce(&mut Fixture)) -> Result<()> {
+        let mut fixture = self.fixture.lock().unwrap();
+        update(&mut fixture);
+        fixture.save(&self.path)
+    }
+
+    fn timeline<'a>(
+        &'a self,
+        timeline: BoxFuture<'a, Result<Option<Vec<Event>>>>,
+        key: String,
+    ) -> BoxFuture<'a, Result<Option<Vec<Event>>>> {
+        async move {
+            let events = timeline.await?;
+            if let Some(ref events) = events {
+                self.record(|f| {
+                    f.timelines.insert(key, events.clone());
+                })?;
+            }
+            Ok(events)
+        }
+        .boxed()
+    }
+}
+
+impl GithubBackend for Recorder {
+    fn notifications(&self) -> BoxFuture<'_, Result<Vec<Notification>>> {
+        async move {
+            let notifications = self.inner.notifications().await?;
+            self.record(|f| f.notifications = notifications.clone())?;
+            Ok(notifications)
+        }
+        .boxed()
+    }
+
+    fn threads(&self) -> BoxFuture<'_, Result<Vec<Notification>>> {
+        async move {
+            let notifications = self.inner.threads().await?;
+            self.record(|f| f.notifications = notifications.clone())?;
+            Ok(notifications)
+        }
+        .boxed()
+    }
+
+    fn details(&self, notification: Notification) -> BoxFuture<'_, Result<Notification>> {
+        async move {
+            let notification = self.inner.details(notification).await?;
+            self.record(|f| {
+                let id = notification.inner.id;
+                if let Some(n) = f.notifications.iter_mut().find(|n| n.inner.id == id) {
+                    *n = notification.clone();
+                }
+            })?;
+            Ok(notification)
+        }
+        .boxed()
+    }
+
+    fn issue_timeline<'a>(
+        &'a self,
+        repo: &'a RepoMeta,
+        number: usize,
+    ) -> BoxFuture<'a, Result<Option<Vec<Event>>>> {
+        let key = Fixture::key(repo, number);
+        self.timeline(self.inner.issue_timeline(repo, number), key)
+    }
+
+    fn pr_timeline<'a>(
+        &'a self,
+        repo: &'a RepoMeta,
+        number: usize,
+    ) -> BoxFuture<'a, Result<Option<Vec<Event>>>> {
+        let key = Fixture::key(repo, number);
+        self.timeline(self.inner.pr_timeline(repo, number), key)
+    }
+
+    fn discussion(&self, meta: DiscussionMeta) -> BoxFuture<'_, Result<Option<Discussion>>> {
+        let key = Fixture::key(&meta.repo, meta.number);
+        async move {
+            let discussion = self.inner.discussion(meta).await?;
+            if let Some(ref discussion) = discussion {
+                self.record(|f| {
+                    f.discussions.insert(key, discussion.clone());
+                })?;
+            }
+            Ok(discussion)
+        }
+        .boxed()
+    }
+
+    /// Marking as read is passed on but not recorded, since fixtures are
+    /// replayed with every notification unread.
+    fn mark_notification_as_read(&self, id: NotificationId) -> BoxFuture<'_, Result<()>> {
+        self.inner.mark_notification_as_read(id)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::github::{events::EventKind, IssueMeta, IssueState, NotificationTarget, User};
+
+    #[tokio::test]
+    async fn test_record_and_replay() {
+        let repo = RepoMeta {
+            name: "helix".to_string(),
+            owner: "helix-editor".to_string(),
+        };
+        let issue = IssueMeta {
+            repo: repo.clone(),
+            title: "Crash on startup".to_string(),
+            body: String::new(),
+            number: 12,
+            author: User::default(),
+            state: IssueState::Open,
+            created_at: Default::default(),
+            labels: Vec::new(),
+        };
+        let notification = Notification::synthetic(
+            "Issue",
+            Default::default(),
+            NotificationTarget::Issue(issue),
+        )
+        .unwrap();
+        let mut mock = MockBackend::new(vec![notification]);
+        mock.timelines.insert(
+            Fixture::key(&repo, 12),
+            vec![EventKind::Reopened.with(User::new("octocat"), Default::default())],
+        );
+
+        let path = std::env::temp_dir().join(format!("octerm-fixture-{}.json", std::process::id()));
+        let recorder = Recorder::new(Arc::new(mock), path.clone());
+        assert_eq!(recorder.notifications().await.unwrap().len(), 1);
+        recorder.issue_timeline(&repo, 12).await.unwrap();
+
+        let replay = MockBackend::from(Fixture::load(&path).unwrap());
+        std::fs::remove_file(&path).unwrap();
+        let notifications = replay.notifications().await.unwrap();
+        assert_eq!(notifications[0].inner.subject.title, "Crash on startup");
+        let events = replay.issue_timeline(&repo, 12).await.unwrap().unwrap();
+        assert_eq!(events[0].to_string(), "@octocat reopened this");
+        assert!(Fixture::load(&path).is_err());
+    }
+}
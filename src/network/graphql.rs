@@ -1,15 +1,79 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use super::stats;
 use crate::error::{Error, Result};
-use graphql_client::{GraphQLQuery, Response};
+use graphql_client::{GraphQLQuery, QueryBody, Response};
+use once_cell::sync::OnceCell;
+
+/// Where queries and responses are dumped, see [`enable_dumps`].
+struct Dumps {
+    dir: PathBuf,
+    /// Removed from everything that is dumped.
+    token: Option<String>,
+    count: AtomicUsize,
+}
+
+static DUMPS: OnceCell<Dumps> = OnceCell::new();
+
+/// Write every query, its variables and the raw response to a numbered
+/// JSON file in `dir`, like `003-IssueTimelineQuery.json`. Occurrences of
+/// `token` are redacted.
+pub fn enable_dumps(dir: PathBuf, token: Option<String>) -> Result<()> {
+    std::fs::create_dir_all(&dir).map_err(Error::GraphqlDump)?;
+    let _ = DUMPS.set(Dumps {
+        dir,
+        token,
+        count: AtomicUsize::new(0),
+    });
+    Ok(())
+}
 
 pub async fn query<Q: GraphQLQuery>(
     vars: Q::Variables,
     octo: &octocrab::Octocrab,
 ) -> Result<Option<Q::ResponseData>> {
     let query = Q::build_query(vars);
-    let response = octo.post("graphql", Some(&query)).await?;
+    let dumps = match DUMPS.get() {
+        Some(dumps) => dumps,
+        None => {
+            let response = stats::graphql(octo.post("graphql", Some(&query))).await?;
+            return response_to_result::<Q::ResponseData>(response);
+        }
+    };
+    // Keep the raw response so that it can be dumped even if it doesn't
+    // deserialize
+    let raw: serde_json::Value = stats::graphql(octo.post("graphql", Some(&query))).await?;
+    let index = dumps.count.fetch_add(1, Ordering::Relaxed);
+    write_dump(&dumps.dir, index, &query, &raw, dumps.token.as_deref())?;
+    let response = serde_json::from_value(raw).map_err(Error::GraphqlResponse)?;
     response_to_result::<Q::ResponseData>(response)
 }
 
+fn write_dump<V: serde::Serialize>(
+    dir: &Path,
+    index: usize,
+    query: &QueryBody<V>,
+    response: &serde_json::Value,
+    token: Option<&str>,
+) -> Result<PathBuf> {
+    let dump = serde_json::json!({
+        "operation": query.operation_name,
+        "query": query.query,
+        "variables": query.variables,
+        "response": response,
+    });
+    let mut contents = serde_json::to_string_pretty(&dump).unwrap_or_default();
+    if let Some(token) = token.filter(|t| !t.is_empty()) {
+        contents = contents.replace(token, "[REDACTED]");
+    }
+    let path = dir.join(format!("{index:03}-{}.json", query.operation_name));
+    std::fs::write(&path, contents).map_err(Error::GraphqlDump)?;
+    Ok(path)
+}
+
 pub fn response_to_result<Data>(resp: Response<Data>) -> Result<Option<Data>> {
     if let Some(err) = resp.errors {
         return Err(Error::Graphql(err));
@@ -50,3 +114,44 @@ pub struct DiscussionQuery;
     response_derives = "Debug"
 )]
 pub struct DiscussionSearchQuery;
+
+#[derive(graphql_client::GraphQLQuery)]
+#[graphql(
+    schema_path = "schema.graphql",
+    query_path = "src/queries/vulnerability_alerts.graphql",
+    response_derives = "Debug"
+)]
+pub struct VulnerabilityAlertsQuery;
+
+#[derive(graphql_client::GraphQLQuery)]
+#[graphql(
+    schema_path = "schema.graphql",
+    query_path = "src/queries/search.graphql",
+    response_derives = "Debug"
+)]
+pub struct SearchQuery;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_write_dump() {
+        let dir = std::env::temp_dir().join(format!("octerm-dumps-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let query = QueryBody {
+            variables: serde_json::json!({ "owner": "helix-editor", "number": 12 }),
+            query: "query IssueTimelineQuery { viewer { login } }",
+            operation_name: "IssueTimelineQuery",
+        };
+        let response = serde_json::json!({ "data": { "viewer": { "login": "ghp_secret" } } });
+        let path = write_dump(&dir, 3, &query, &response, Some("ghp_secret")).unwrap();
+        let dump = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(path.ends_with("003-IssueTimelineQuery.json"));
+        let dump: serde_json::Value = serde_json::from_str(&dump).unwrap();
+        assert_eq!(dump["variables"]["number"], 12);
+        assert_eq!(dump["response"]["data"]["viewer"]["login"], "[REDACTED]");
+    }
+}
@@ -1,22 +1,36 @@
 use std::ops::Not;
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
 
 use std::result::Result as StdResult;
 
-use octocrab::models::NotificationId;
+use chrono::{DateTime, TimeZone, Utc};
+
+use octocrab::activity::notifications::{ListNotificationsBuilder, NotificationsHandler};
+use octocrab::models::{NotificationId, ThreadId};
 use octocrab::Octocrab;
 use octocrab::{models::activity::Notification as OctoNotification, Page};
+use once_cell::sync::{Lazy, OnceCell};
 use tokio::task::JoinHandle;
 
 use crate::error::{Error, Result};
-use crate::github::{self, events::Event};
 use crate::github::{
-    events, Discussion, DiscussionMeta, DiscussionReplyToSuggestedAnswer, DiscussionState,
-    DiscussionSuggestedAnswer, IssueDeserModel, IssueMeta, Notification, NotificationTarget,
-    PullRequestMeta, RepoMeta,
+    self,
+    events::{Event, Label},
+};
+use crate::github::{
+    events, CiBuildMeta, CiConclusion, Commit, CommitComment, CommitDeserModel,
+    CommitDetailsDeserModel, CommitMeta, Discussion, DiscussionMeta,
+    DiscussionReplyToSuggestedAnswer, DiscussionState, DiscussionSuggestedAnswer, Gist,
+    GistComment, GistDeserModel, GistMeta, InvitationDeserModel, InvitationMeta, IssueClosedReason,
+    IssueDeserModel, IssueMeta, IssueState, Notification, NotificationTarget, PullRequestMeta,
+    PullRequestState, ReleaseComparison, ReleaseMeta, RepoMeta, SecurityAlertMeta, Severity,
+    WorkflowJob, WorkflowJobDeserModel, WorkflowRun, WorkflowRunDeserModel,
 };
 
-use super::graphql;
+use super::{cache, graphql, stats};
 
 macro_rules! actor {
     ($root:expr) => {
@@ -27,6 +41,68 @@ macro_rules! actor {
     };
 }
 
+/// Like [`actor!`] for the `author` field, but also marks the user as a bot
+/// if the `__typename` of the author is `Bot`. `$on_type` is the generated
+/// enum for the `__typename` of the author.
+macro_rules! author {
+    ($root:expr, $on_type:ident) => {
+        match $root.author {
+            Some(author) => $crate::github::User {
+                bot: matches!(author.on, $on_type::Bot),
+                name: author.login,
+            },
+            None => $crate::github::User::default(),
+        }
+    };
+}
+
+/// Convert the `reactionGroups` of a comment into [`events::ReactionGroup`]s,
+/// skipping reactions that nobody used.
+macro_rules! reactions {
+    ($groups:expr) => {
+        $groups
+            .into_iter()
+            .flatten()
+            .filter_map(|group| {
+                let reaction = match group.content {
+                    ReactionContent::THUMBS_UP => events::Reaction::ThumbsUp,
+                    ReactionContent::THUMBS_DOWN => events::Reaction::ThumbsDown,
+                    ReactionContent::LAUGH => events::Reaction::Laugh,
+                    ReactionContent::HOORAY => events::Reaction::Hooray,
+                    ReactionContent::CONFUSED => events::Reaction::Confused,
+                    ReactionContent::HEART => events::Reaction::Heart,
+                    ReactionContent::ROCKET => events::Reaction::Rocket,
+                    ReactionContent::EYES => events::Reaction::Eyes,
+                    ReactionContent::Other(_) => return None,
+                };
+                let count = group.reactors.total_count as usize;
+                (count > 0).then_some(events::ReactionGroup { reaction, count })
+            })
+            .collect()
+    };
+}
+
+/// Convert the generated `CommentAuthorAssociation` enum of a query into
+/// [`events::AuthorAssociation`].
+macro_rules! association {
+    ($assoc:expr) => {
+        match $assoc {
+            CommentAuthorAssociation::OWNER => events::AuthorAssociation::Owner,
+            CommentAuthorAssociation::MEMBER => events::AuthorAssociation::Member,
+            CommentAuthorAssociation::COLLABORATOR => events::AuthorAssociation::Collaborator,
+            CommentAuthorAssociation::CONTRIBUTOR => events::AuthorAssociation::Contributor,
+            CommentAuthorAssociation::FIRST_TIME_CONTRIBUTOR => {
+                events::AuthorAssociation::FirstTimeContributor
+            }
+            CommentAuthorAssociation::FIRST_TIMER => events::AuthorAssociation::FirstTimer,
+            CommentAuthorAssociation::MANNEQUIN => events::AuthorAssociation::Mannequin,
+            CommentAuthorAssociation::NONE | CommentAuthorAssociation::Other(_) => {
+                events::AuthorAssociation::None
+            }
+        }
+    };
+}
+
 macro_rules! issue_or_pr {
     ($var:expr, $gql_type:ident) => {
         match $var {
@@ -64,7 +140,9 @@ pub async fn pr_timeline(
         use PullRequestTimelineQueryRepositoryPullRequestTimelineItemsEdgesNodeOnClosedEventCloser as Closer;
         use PullRequestTimelineQueryRepositoryPullRequestTimelineItemsEdgesNodeOnConnectedEventSource as ConnectedSource;
         use PullRequestTimelineQueryRepositoryPullRequestTimelineItemsEdgesNodeOnCrossReferencedEventSource as CrossRefSource;
+        use PullRequestTimelineQueryRepositoryPullRequestTimelineItemsEdgesNodeOnIssueCommentAuthorOn as CommentAuthorOn;
         use PullRequestTimelineQueryRepositoryPullRequestTimelineItemsEdgesNodeOnMarkedAsDuplicateEventCanonical as DuplicateCanonical;
+        use PullRequestTimelineQueryRepositoryPullRequestTimelineItemsEdgesNodeOnPullRequestReviewAuthorOn as ReviewAuthorOn;
         use PullRequestTimelineQueryRepositoryPullRequestTimelineItemsEdgesNodeOnReviewRequestedEventRequestedReviewer as Reviewer;
         use PullRequestTimelineQueryRepositoryPullRequestTimelineItemsEdgesNodeOnUnassignedEventAssignee as Unassignee;
 
@@ -76,11 +154,6 @@ pub async fn pr_timeline(
             .into_iter()
             .filter_map(|e| e?.node)
             .map(|node| match node {
-                TimelineEvent::AddedToProjectEvent => Event::unknown("AddedToProjectEvent"),
-                TimelineEvent::AutoMergeDisabledEvent => Event::unknown("AutoMergeDisabledEvent"),
-                TimelineEvent::AutoMergeEnabledEvent => Event::unknown("AutoMergeEnabledEvent"),
-                TimelineEvent::AutoRebaseEnabledEvent => Event::unknown("AutoRebaseEnabledEvent"),
-                TimelineEvent::AutoSquashEnabledEvent => Event::unknown("AutoSquashEnabledEvent"),
                 TimelineEvent::AutomaticBaseChangeFailedEvent => {
                     Event::unknown("AutomaticBaseChangeFailedEvent")
                 }
@@ -94,19 +167,9 @@ pub async fn pr_timeline(
                 TimelineEvent::ConvertedNoteToIssueEvent => {
                     Event::unknown("ConvertedNoteToIssueEvent")
                 }
-                TimelineEvent::ConvertedToDiscussionEvent(_) => {
-                    Event::unknown("ConvertedToDiscussionEvent")
-                }
                 TimelineEvent::DemilestonedEvent(_) => Event::unknown("DemilestonedEvent"),
-                TimelineEvent::DeployedEvent => Event::unknown("DeployedEvent"),
-                TimelineEvent::DeploymentEnvironmentChangedEvent => {
-                    Event::unknown("DeploymentEnvironmentChangedEvent")
-                }
                 TimelineEvent::DisconnectedEvent => Event::unknown("DisconnectedEvent"),
                 TimelineEvent::HeadRefRestoredEvent => Event::unknown("HeadRefRestoredEvent"),
-                TimelineEvent::MovedColumnsInProjectEvent => {
-                    Event::unknown("MovedColumnsInProjectEvent")
-                }
                 TimelineEvent::PullRequestCommitCommentThread => {
                     Event::unknown("PullRequestCommitCommentThread")
                 }
@@ -116,12 +179,10 @@ pub async fn pr_timeline(
                 TimelineEvent::PullRequestRevisionMarker => {
                     Event::unknown("PullRequestRevisionMarker")
                 }
-                TimelineEvent::RemovedFromProjectEvent => Event::unknown("RemovedFromProjectEvent"),
                 TimelineEvent::ReviewDismissedEvent => Event::unknown("ReviewDismissedEvent"),
                 TimelineEvent::ReviewRequestRemovedEvent(_) => {
                     Event::unknown("ReviewRequestRemovedEvent")
                 }
-                TimelineEvent::TransferredEvent => Event::unknown("TransferredEvent"),
                 TimelineEvent::UnsubscribedEvent => Event::unknown("UnsubscribedEvent"),
                 TimelineEvent::UserBlockedEvent => Event::unknown("UserBlockedEvent"),
 
@@ -168,11 +229,23 @@ pub async fn pr_timeline(
                     source: issue_or_pr!(cross.source, CrossRefSource),
                 }
                 .with(actor!(cross), cross.created_at),
-                TimelineEvent::IssueComment(comment) => EventKind::Commented { body: comment.body }
-                    .with(actor!(comment, author), comment.created_at),
+                TimelineEvent::IssueComment(comment) => EventKind::Commented {
+                    body: comment.body,
+                    reactions: reactions!(comment.reaction_groups),
+                    minimized: comment.is_minimized.then(|| {
+                        comment
+                            .minimized_reason
+                            .map(|r| r.to_lowercase().replace('_', "-"))
+                            .unwrap_or_else(|| "hidden".to_string())
+                    }),
+                    edited: comment.last_edited_at.is_some(),
+                    association: association!(comment.author_association),
+                }
+                .with(author!(comment, CommentAuthorOn), comment.created_at),
                 TimelineEvent::LabeledEvent(labeled) => EventKind::Labeled {
                     label: events::Label {
                         name: labeled.label.name,
+                        color: labeled.label.color,
                     },
                 }
                 .with(actor!(labeled), labeled.created_at),
@@ -242,6 +315,7 @@ pub async fn pr_timeline(
                 TimelineEvent::UnlabeledEvent(unlabeled) => EventKind::Unlabeled {
                     label: events::Label {
                         name: unlabeled.label.name,
+                        color: unlabeled.label.color,
                     },
                 }
                 .with(actor!(unlabeled), unlabeled.created_at),
@@ -254,9 +328,91 @@ pub async fn pr_timeline(
                 TimelineEvent::UnpinnedEvent(unpin) => {
                     EventKind::Unpinned {}.with(actor!(unpin), unpin.created_at)
                 }
+                TimelineEvent::AddedToProjectEvent(added) => {
+                    EventKind::AddedToProject.with(actor!(added), added.created_at)
+                }
+                TimelineEvent::MovedColumnsInProjectEvent(moved) => {
+                    EventKind::MovedColumnsInProject.with(actor!(moved), moved.created_at)
+                }
+                TimelineEvent::RemovedFromProjectEvent(removed) => {
+                    EventKind::RemovedFromProject.with(actor!(removed), removed.created_at)
+                }
+                TimelineEvent::TransferredEvent(transferred) => EventKind::Transferred {
+                    from: transferred.from_repository.map(|r| events::Repository {
+                        name: r.name,
+                        owner: r.owner.login.into(),
+                    }),
+                    to: events::Repository {
+                        name: transferred.issue.repository.name,
+                        owner: transferred.issue.repository.owner.login.into(),
+                    },
+                }
+                .with(actor!(transferred), transferred.created_at),
+                TimelineEvent::ConvertedToDiscussionEvent(converted) => {
+                    EventKind::ConvertedToDiscussion {
+                        discussion: converted.discussion.map(|d| events::DiscussionRef {
+                            number: d.number as usize,
+                            title: d.title,
+                        }),
+                    }
+                    .with(actor!(converted), converted.created_at)
+                }
                 TimelineEvent::SubscribedEvent => EventKind::Subscribed.anonymous(),
                 TimelineEvent::MentionedEvent => EventKind::Mentioned.anonymous(),
 
+                TimelineEvent::AutoMergeEnabledEvent(enabled) => EventKind::AutoMergeEnabled {
+                    method: events::MergeMethod::Merge,
+                }
+                .with(actor!(enabled), enabled.created_at),
+                TimelineEvent::AutoSquashEnabledEvent(enabled) => EventKind::AutoMergeEnabled {
+                    method: events::MergeMethod::Squash,
+                }
+                .with(actor!(enabled), enabled.created_at),
+                TimelineEvent::AutoRebaseEnabledEvent(enabled) => EventKind::AutoMergeEnabled {
+                    method: events::MergeMethod::Rebase,
+                }
+                .with(actor!(enabled), enabled.created_at),
+                TimelineEvent::AutoMergeDisabledEvent(disabled) => EventKind::AutoMergeDisabled {
+                    reason: disabled.reason,
+                }
+                .with(actor!(disabled), disabled.created_at),
+                TimelineEvent::DeployedEvent(deployed) => EventKind::Deployed {
+                    environment: deployed.deployment.environment,
+                    state: deployed.deployment.state.map(|state| match state {
+                        DeploymentState::ABANDONED => events::DeploymentState::Abandoned,
+                        DeploymentState::ACTIVE => events::DeploymentState::Active,
+                        DeploymentState::DESTROYED => events::DeploymentState::Destroyed,
+                        DeploymentState::ERROR => events::DeploymentState::Error,
+                        DeploymentState::FAILURE => events::DeploymentState::Failure,
+                        DeploymentState::INACTIVE => events::DeploymentState::Inactive,
+                        DeploymentState::IN_PROGRESS => events::DeploymentState::InProgress,
+                        DeploymentState::PENDING => events::DeploymentState::Pending,
+                        DeploymentState::QUEUED => events::DeploymentState::Queued,
+                        DeploymentState::WAITING => events::DeploymentState::Waiting,
+                        DeploymentState::Other(s) => events::DeploymentState::Other(s),
+                    }),
+                }
+                .with(actor!(deployed), deployed.created_at),
+                TimelineEvent::DeploymentEnvironmentChangedEvent(changed) => {
+                    let status = changed.deployment_status;
+                    EventKind::DeploymentEnvironmentChanged {
+                        environment: status.deployment.environment,
+                        state: match status.state {
+                            DeploymentStatusState::ERROR => events::DeploymentState::Error,
+                            DeploymentStatusState::FAILURE => events::DeploymentState::Failure,
+                            DeploymentStatusState::INACTIVE => events::DeploymentState::Inactive,
+                            DeploymentStatusState::IN_PROGRESS => {
+                                events::DeploymentState::InProgress
+                            }
+                            DeploymentStatusState::PENDING => events::DeploymentState::Pending,
+                            DeploymentStatusState::QUEUED => events::DeploymentState::Queued,
+                            DeploymentStatusState::SUCCESS => events::DeploymentState::Success,
+                            DeploymentStatusState::WAITING => events::DeploymentState::Waiting,
+                            DeploymentStatusState::Other(s) => events::DeploymentState::Other(s),
+                        },
+                    }
+                    .with(actor!(changed), changed.created_at)
+                }
                 TimelineEvent::ConvertToDraftEvent(draft) => {
                     EventKind::MarkedAsDraft {}.with(actor!(draft), draft.created_at)
                 }
@@ -306,8 +462,11 @@ pub async fn pr_timeline(
                     },
 
                     body: review.body.is_empty().not().then_some(review.body),
+                    reactions: reactions!(review.reaction_groups),
+                    edited: review.last_edited_at.is_some(),
+                    association: association!(review.author_association),
                 }
-                .with(actor!(review, author), review.created_at),
+                .with(author!(review, ReviewAuthorOn), review.created_at),
                 TimelineEvent::ReadyForReviewEvent(ready) => {
                     EventKind::MarkedAsReadyForReview {}.with(actor!(ready), ready.created_at)
                 }
@@ -354,6 +513,7 @@ pub async fn issue_timeline(
         use IssueTimelineQueryRepositoryIssueTimelineItemsEdgesNodeOnClosedEventCloser as Closer;
         use IssueTimelineQueryRepositoryIssueTimelineItemsEdgesNodeOnConnectedEventSource as ConnectedSource;
         use IssueTimelineQueryRepositoryIssueTimelineItemsEdgesNodeOnCrossReferencedEventSource as CrossRefSource;
+        use IssueTimelineQueryRepositoryIssueTimelineItemsEdgesNodeOnIssueCommentAuthorOn as CommentAuthorOn;
         use IssueTimelineQueryRepositoryIssueTimelineItemsEdgesNodeOnMarkedAsDuplicateEventCanonical as DuplicateCanonical;
         use IssueTimelineQueryRepositoryIssueTimelineItemsEdgesNodeOnUnassignedEventAssignee as Unassignee;
 
@@ -365,22 +525,13 @@ pub async fn issue_timeline(
             .into_iter()
             .filter_map(|e| e?.node)
             .map(|node| match node {
-                TimelineEvent::AddedToProjectEvent => Event::unknown("AddedToProjectEvent"),
                 TimelineEvent::CommentDeletedEvent => Event::unknown("CommentDeletedEvent"),
                 TimelineEvent::ConvertedNoteToIssueEvent => {
                     Event::unknown("ConvertedNoteToIssueEvent")
                 }
-                TimelineEvent::ConvertedToDiscussionEvent(_) => {
-                    Event::unknown("ConvertedToDiscussionEvent")
-                }
                 TimelineEvent::DemilestonedEvent(_) => Event::unknown("DemilestonedEvent"),
                 TimelineEvent::UnsubscribedEvent => Event::unknown("UnsubscribedEvent"),
                 TimelineEvent::UserBlockedEvent => Event::unknown("UserBlockedEvent"),
-                TimelineEvent::TransferredEvent => Event::unknown("TransferredEvent"),
-                TimelineEvent::RemovedFromProjectEvent => Event::unknown("RemovedFromProjectEvent"),
-                TimelineEvent::MovedColumnsInProjectEvent => {
-                    Event::unknown("MovedColumnsInProjectEvent")
-                }
                 TimelineEvent::DisconnectedEvent => Event::unknown("DisconnectedEvent"),
 
                 TimelineEvent::AssignedEvent(assigned) => {
@@ -426,11 +577,23 @@ pub async fn issue_timeline(
                     source: issue_or_pr!(cross.source, CrossRefSource),
                 }
                 .with(actor!(cross), cross.created_at),
-                TimelineEvent::IssueComment(comment) => EventKind::Commented { body: comment.body }
-                    .with(actor!(comment, author), comment.created_at),
+                TimelineEvent::IssueComment(comment) => EventKind::Commented {
+                    body: comment.body,
+                    reactions: reactions!(comment.reaction_groups),
+                    minimized: comment.is_minimized.then(|| {
+                        comment
+                            .minimized_reason
+                            .map(|r| r.to_lowercase().replace('_', "-"))
+                            .unwrap_or_else(|| "hidden".to_string())
+                    }),
+                    edited: comment.last_edited_at.is_some(),
+                    association: association!(comment.author_association),
+                }
+                .with(author!(comment, CommentAuthorOn), comment.created_at),
                 TimelineEvent::LabeledEvent(labeled) => EventKind::Labeled {
                     label: events::Label {
                         name: labeled.label.name,
+                        color: labeled.label.color,
                     },
                 }
                 .with(actor!(labeled), labeled.created_at),
@@ -500,6 +663,7 @@ pub async fn issue_timeline(
                 TimelineEvent::UnlabeledEvent(unlabeled) => EventKind::Unlabeled {
                     label: events::Label {
                         name: unlabeled.label.name,
+                        color: unlabeled.label.color,
                     },
                 }
                 .with(actor!(unlabeled), unlabeled.created_at),
@@ -512,6 +676,35 @@ pub async fn issue_timeline(
                 TimelineEvent::UnpinnedEvent(unpin) => {
                     EventKind::Unpinned {}.with(actor!(unpin), unpin.created_at)
                 }
+                TimelineEvent::AddedToProjectEvent(added) => {
+                    EventKind::AddedToProject.with(actor!(added), added.created_at)
+                }
+                TimelineEvent::MovedColumnsInProjectEvent(moved) => {
+                    EventKind::MovedColumnsInProject.with(actor!(moved), moved.created_at)
+                }
+                TimelineEvent::RemovedFromProjectEvent(removed) => {
+                    EventKind::RemovedFromProject.with(actor!(removed), removed.created_at)
+                }
+                TimelineEvent::TransferredEvent(transferred) => EventKind::Transferred {
+                    from: transferred.from_repository.map(|r| events::Repository {
+                        name: r.name,
+                        owner: r.owner.login.into(),
+                    }),
+                    to: events::Repository {
+                        name: transferred.issue.repository.name,
+                        owner: transferred.issue.repository.owner.login.into(),
+                    },
+                }
+                .with(actor!(transferred), transferred.created_at),
+                TimelineEvent::ConvertedToDiscussionEvent(converted) => {
+                    EventKind::ConvertedToDiscussion {
+                        discussion: converted.discussion.map(|d| events::DiscussionRef {
+                            number: d.number as usize,
+                            title: d.title,
+                        }),
+                    }
+                    .with(actor!(converted), converted.created_at)
+                }
                 TimelineEvent::SubscribedEvent => EventKind::Subscribed.anonymous(),
                 TimelineEvent::MentionedEvent => EventKind::Mentioned.anonymous(),
             })
@@ -574,9 +767,263 @@ pub async fn discussion(octo: &Octocrab, meta: DiscussionMeta) -> Result<Option<
     Ok(convert_to_discussion())
 }
 
+/// Find the open Dependabot alert behind a notification. The notification
+/// doesn't link to the alert, so the alert for the package named in the title
+/// is used, falling back to the most severe open alert.
+async fn security_alert(
+    octo: &Octocrab,
+    notif: &OctoNotification,
+) -> Result<Option<SecurityAlertMeta>> {
+    use graphql::vulnerability_alerts_query::SecurityAdvisorySeverity;
+
+    let repo = RepoMeta::from(&notif.repository);
+    let query_vars = graphql::vulnerability_alerts_query::Variables {
+        owner: repo.owner.clone(),
+        repo: repo.name.clone(),
+    };
+    let data = graphql::query::<graphql::VulnerabilityAlertsQuery>(query_vars, octo).await?;
+    let convert_to_alerts = || -> Option<Vec<SecurityAlertMeta>> {
+        let alerts = data?
+            .repository?
+            .vulnerability_alerts?
+            .nodes?
+            .into_iter()
+            .flatten()
+            .filter_map(|alert| {
+                let vuln = alert.security_vulnerability?;
+                let advisory = alert.security_advisory?;
+                Some(SecurityAlertMeta {
+                    repo: repo.clone(),
+                    number: alert.number as usize,
+                    package: vuln.package.name,
+                    severity: match vuln.severity {
+                        SecurityAdvisorySeverity::CRITICAL => Severity::Critical,
+                        SecurityAdvisorySeverity::HIGH => Severity::High,
+                        SecurityAdvisorySeverity::MODERATE => Severity::Moderate,
+                        _ => Severity::Low,
+                    },
+                    summary: advisory.summary,
+                    ghsa_id: advisory.ghsa_id,
+                    vulnerable_range: vuln.vulnerable_version_range,
+                    patched_version: vuln.first_patched_version.map(|v| v.identifier),
+                    manifest: alert.vulnerable_manifest_path,
+                })
+            })
+            .collect();
+        Some(alerts)
+    };
+
+    let alerts = convert_to_alerts().unwrap_or_default();
+    let title = &notif.subject.title;
+    let for_package = alerts
+        .iter()
+        .position(|alert| title.contains(&alert.package));
+    let alert = match for_package {
+        Some(i) => alerts.into_iter().nth(i),
+        None => alerts.into_iter().max_by_key(|alert| alert.severity),
+    };
+    Ok(alert)
+}
+
+/// Compare a release with the release published before it. Returns `None`
+/// if it is the first release of the repo.
+pub async fn compare_with_previous_release(
+    octo: &Octocrab,
+    release: &ReleaseMeta,
+) -> Result<Option<ReleaseComparison>> {
+    #[derive(serde::Deserialize)]
+    struct Release {
+        tag_name: String,
+        draft: bool,
+    }
+    #[derive(serde::Deserialize)]
+    struct Comparison {
+        html_url: String,
+        total_commits: usize,
+        commits: Vec<CompareCommit>,
+    }
+    #[derive(serde::Deserialize)]
+    struct CompareCommit {
+        sha: String,
+        commit: CommitDetailsDeserModel,
+    }
+
+    let RepoMeta { owner, name } = &release.repo;
+    // Releases are listed newest first.
+    let releases: Vec<Release> = stats::rest(octo.get(
+        format!("repos/{owner}/{name}/releases"),
+        Some(&[("per_page", "100")]),
+    ))
+    .await?;
+    let previous = releases
+        .into_iter()
+        .filter(|r| !r.draft)
+        .skip_while(|r| r.tag_name != release.tag_name)
+        .nth(1);
+    let previous = match previous {
+        Some(previous) => previous.tag_name,
+        None => return Ok(None),
+    };
+
+    let comparison: Comparison = stats::rest(octo.get(
+        format!(
+            "repos/{owner}/{name}/compare/{previous}...{}",
+            release.tag_name
+        ),
+        None::<&()>,
+    ))
+    .await?;
+    Ok(Some(ReleaseComparison {
+        base_tag: previous,
+        head_tag: release.tag_name.clone(),
+        total_commits: comparison.total_commits,
+        commits: comparison
+            .commits
+            .into_iter()
+            .map(|c| {
+                let subject = c.commit.message.lines().next().unwrap_or_default();
+                (c.sha.chars().take(7).collect(), subject.to_string())
+            })
+            .collect(),
+        html_url: comparison.html_url,
+    }))
+}
+
+/// Download an image linked in a comment. This doesn't go through octocrab
+/// since images can be hosted anywhere, and the token shouldn't be sent to
+/// other hosts.
+pub async fn download_image(url: &str) -> Result<Vec<u8>> {
+    let response = reqwest::get(url)
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|_| Error::ImageDownload)?;
+    let bytes = response.bytes().await.map_err(|_| Error::ImageDownload)?;
+    Ok(bytes.to_vec())
+}
+
+/// Download a release asset from its browser download url.
+pub async fn download_asset(url: &str) -> Result<Vec<u8>> {
+    let response = reqwest::get(url)
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|_| Error::AssetDownload)?;
+    let bytes = response.bytes().await.map_err(|_| Error::AssetDownload)?;
+    Ok(bytes.to_vec())
+}
+
+/// Accept or decline an invitation to collaborate on a repository.
+pub async fn respond_to_invitation(
+    octo: &Octocrab,
+    invitation_id: u64,
+    accept: bool,
+) -> Result<()> {
+    let url = octo.absolute_url(format!("user/repository_invitations/{invitation_id}"))?;
+    let response = match accept {
+        true => stats::rest(octo._patch(url, None::<&()>)).await?,
+        false => stats::rest(octo._delete(url, None::<&()>)).await?,
+    };
+    octocrab::map_github_error(response).await?;
+    Ok(())
+}
+
+pub async fn comment_on_issue(
+    octo: &Octocrab,
+    repo: &RepoMeta,
+    number: usize,
+    body: &str,
+) -> Result<()> {
+    stats::rest(
+        octo.issues(&repo.owner, &repo.name)
+            .create_comment(number as u64, body),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Open an issue, returning its url.
+pub async fn create_issue(
+    octo: &Octocrab,
+    repo: &RepoMeta,
+    title: &str,
+    body: &str,
+    labels: Vec<String>,
+) -> Result<String> {
+    let issue = stats::rest(
+        octo.issues(&repo.owner, &repo.name)
+            .create(title)
+            .body(body)
+            .labels(labels)
+            .send(),
+    )
+    .await?;
+    Ok(issue.html_url.to_string())
+}
+
+pub async fn commit(octo: &Octocrab, meta: CommitMeta) -> Result<Commit> {
+    let route = format!(
+        "repos/{}/{}/commits/{}/comments",
+        meta.repo.owner, meta.repo.name, meta.sha
+    );
+    let comments: Vec<CommitComment> =
+        stats::rest(octo.get(route, Some(&[("per_page", "100")]))).await?;
+    Ok(Commit { meta, comments })
+}
+
+pub async fn gist(octo: &Octocrab, meta: GistMeta) -> Result<Gist> {
+    let route = format!("gists/{}/comments", meta.id);
+    let comments: Vec<GistComment> =
+        stats::rest(octo.get(route, Some(&[("per_page", "100")]))).await?;
+    Ok(Gist { meta, comments })
+}
+
+/// Whether only the notifications of threads the user is participating in
+/// are fetched. Starts as [`crate::config::FetchConfig::participating`].
+static PARTICIPATING: Lazy<AtomicBool> =
+    Lazy::new(|| AtomicBool::new(crate::config::get().fetch.participating));
+
+pub fn participating() -> bool {
+    PARTICIPATING.load(Ordering::Relaxed)
+}
+
+pub fn set_participating(participating: bool) {
+    PARTICIPATING.store(participating, Ordering::Relaxed);
+}
+
+/// Toggle between fetching all notifications and only the participating
+/// ones. Returns true if only participating ones are now fetched.
+pub fn toggle_participating() -> bool {
+    !PARTICIPATING.fetch_xor(true, Ordering::Relaxed)
+}
+
+/// The only repository notifications are fetched from, see
+/// [`set_fetch_repo`].
+static FETCH_REPO: OnceCell<RepoMeta> = OnceCell::new();
+
+/// Fetch only the notifications of `repo` from its own endpoint instead of
+/// the whole inbox. Has no effect after the first call.
+pub fn set_fetch_repo(repo: RepoMeta) {
+    let _ = FETCH_REPO.set(repo);
+}
+
+/// Request for the notifications of the [`FETCH_REPO`] or else of all
+/// repositories, narrowed to [`participating`] ones if enabled, in pages of
+/// [`crate::config::FetchConfig::per_page`].
+fn list_notifications<'octo>(
+    notifications: &NotificationsHandler<'octo>,
+) -> ListNotificationsBuilder<'octo> {
+    let list = match FETCH_REPO.get() {
+        Some(repo) => notifications.list_for_repo(&repo.owner, &repo.name),
+        None => notifications.list(),
+    };
+    list.participating(participating())
+        .per_page(crate::config::get().fetch.page_size())
+}
+
 async fn get_all_notifs(octo: Arc<Octocrab>) -> Result<Vec<OctoNotification>> {
-    let mut notifs = octo.activity().notifications().list().send().await?;
-    let n_pages = match notifs.number_of_pages() {
+    let notifications = octo.activity().notifications();
+    let mut notifs = stats::rest(list_notifications(&notifications).send()).await?;
+    let max_pages = crate::config::get().fetch.max_pages.unwrap_or(u32::MAX);
+    let n_pages = match notifs.number_of_pages().map(|p| p.min(max_pages)) {
         None | Some(0) | Some(1) => return Ok(notifs.take_items()),
         Some(p) => p,
     };
@@ -588,13 +1035,9 @@ async fn get_all_notifs(octo: Arc<Octocrab>) -> Result<Vec<OctoNotification>> {
     for i in 2..=n_pages {
         let octo = Arc::clone(&octo);
         tasks.push(tokio::spawn(async move {
-            Ok(octo
-                .activity()
-                .notifications()
-                .list()
-                .page(i as u8)
-                .send()
-                .await?)
+            let notifications = octo.activity().notifications();
+            let page = list_notifications(&notifications).page(i as u8).send();
+            Ok(stats::rest(page).await?)
         }));
     }
 
@@ -602,7 +1045,9 @@ async fn get_all_notifs(octo: Arc<Octocrab>) -> Result<Vec<OctoNotification>> {
         futures::future::join_all(tasks).await;
 
     let mut acc = notifs.take_items();
-    acc.reserve_exact(50 * result.len()); // Max notifications from each request is 50
+    // Every page but the last one is full
+    let per_page = crate::config::get().fetch.page_size() as usize;
+    acc.reserve_exact(per_page * result.len());
 
     let result = result.into_iter().try_fold(acc, |mut acc, task| {
         let notif = task.map_err(|_| Error::NetworkTask)?;
@@ -612,6 +1057,46 @@ async fn get_all_notifs(octo: Arc<Octocrab>) -> Result<Vec<OctoNotification>> {
     Ok(result)
 }
 
+/// The rate limits of the REST and GraphQL APIs. Checking them doesn't
+/// count against the limits.
+pub async fn rate_limit(octo: &Octocrab) -> Result<octocrab::models::RateLimit> {
+    Ok(stats::rest(octo.ratelimit().get()).await?)
+}
+
+/// Fill in when the rate limit resets if `err` is for an exceeded rate
+/// limit, since the reset time isn't part of the error returned by octocrab.
+pub async fn with_rate_limit_reset(octo: &Octocrab, err: Error) -> Error {
+    let source = match err {
+        Error::GitHubRateLimitExceeded {
+            source,
+            reset: None,
+        } => source,
+        err => return err,
+    };
+    let reset = rate_limit(octo).await.ok().and_then(|limits| {
+        let resources = limits.resources;
+        // The limit that ran out, or the REST limit if that's unknown
+        let rate = std::iter::once(&resources.core)
+            .chain(resources.graphql.as_ref())
+            .find(|rate| rate.remaining == 0)
+            .unwrap_or(&resources.core);
+        Utc.timestamp_opt(rate.reset as i64, 0).single()
+    });
+    Error::GitHubRateLimitExceeded { source, reset }
+}
+
+/// Notification threads updated after `since`, without their details.
+/// Only the first page is fetched.
+pub async fn updated_threads(
+    octo: &Octocrab,
+    since: DateTime<Utc>,
+) -> Result<Vec<OctoNotification>> {
+    let notifications = octo.activity().notifications();
+    let request = list_notifications(&notifications).since(since).send();
+    let mut page = stats::rest(request).await?;
+    Ok(page.take_items())
+}
+
 pub async fn notifications(octo: Arc<Octocrab>) -> Result<Vec<Notification>> {
     let notifs = get_all_notifs(Arc::clone(&octo)).await?;
     let tasks: Vec<JoinHandle<Result<Notification>>> = notifs
@@ -634,15 +1119,41 @@ pub async fn notifications(octo: Arc<Octocrab>) -> Result<Vec<Notification>> {
     Ok(result)
 }
 
+/// All notifications newest first, without fetching their targets, which
+/// are left [`NotificationTarget::Pending`] to be filled in by
+/// [`octo_notif_to_notif`].
+pub async fn notification_threads(octo: Arc<Octocrab>) -> Result<Vec<Notification>> {
+    let notifs = get_all_notifs(octo).await?;
+    let mut result: Vec<Notification> = notifs
+        .into_iter()
+        .map(|inner| Notification {
+            inner,
+            target: NotificationTarget::Pending,
+        })
+        .collect();
+    result.sort_unstable_by_key(Notification::sorter);
+    result.reverse();
+
+    Ok(result)
+}
+
 pub async fn mark_notification_as_read(
     octo: &Octocrab,
     notification_id: NotificationId,
 ) -> Result<()> {
-    Ok(octo
-        .activity()
-        .notifications()
-        .mark_as_read(notification_id)
-        .await?)
+    let notifications = octo.activity().notifications();
+    Ok(stats::rest(notifications.mark_as_read(notification_id)).await?)
+}
+
+/// Unsubscribe from the thread of a notification. The thread stays muted
+/// until the user comments or is mentioned in it.
+pub async fn unsubscribe_from_thread(
+    octo: &Octocrab,
+    notification_id: NotificationId,
+) -> Result<()> {
+    let notifications = octo.activity().notifications();
+    let thread = ThreadId(notification_id.0);
+    Ok(stats::rest(notifications.delete_thread_subscription(thread)).await?)
 }
 
 /// Retrieve the HTML url that can be opened in the browser to view the contents
@@ -658,20 +1169,24 @@ pub async fn resolve_html_url(octo: &Octocrab, notification: &Notification) -> R
             api_url: notification.inner.url.to_string(),
         });
     match notification.inner.subject.r#type.as_str() {
-        "Release" => {
-            let release: octocrab::models::repos::Release =
-                octo.get(default_url?, None::<&()>).await?;
-            Ok(release.html_url.to_string())
-        }
+        "Release" => match notification.target {
+            NotificationTarget::Release(ref release) => Ok(release.html_url.clone()),
+            _ => {
+                let release: octocrab::models::repos::Release =
+                    stats::rest(octo.get(default_url?, None::<&()>)).await?;
+                Ok(release.html_url.to_string())
+            }
+        },
         "Issue" => match notification.inner.subject.latest_comment_url {
             Some(ref url) => {
-                let comment: octocrab::models::issues::Comment = octo.get(url, None::<&()>).await?;
+                let comment: octocrab::models::issues::Comment =
+                    stats::rest(octo.get(url, None::<&()>)).await?;
                 Ok(comment.html_url.to_string())
             }
             None => {
                 // TODO: Return last (newest) comment in thread
                 let issue: octocrab::models::issues::Issue =
-                    octo.get(default_url?, None::<&()>).await?;
+                    stats::rest(octo.get(default_url?, None::<&()>)).await?;
                 Ok(issue.html_url.to_string())
             }
         },
@@ -680,19 +1195,145 @@ pub async fn resolve_html_url(octo: &Octocrab, notification: &Notification) -> R
             // changed files, etc. Therefore the behavior is different from clicking
             // a PR notification in the web ui, which would show the latest change.
             let pr: octocrab::models::pulls::PullRequest =
-                octo.get(default_url?, None::<&()>).await?;
+                stats::rest(octo.get(default_url?, None::<&()>)).await?;
             pr.html_url
                 .ok_or(Error::HtmlUrlNotFound {
                     api_url: notification.inner.url.to_string(),
                 })
                 .map(|url| url.to_string())
         }
+        "Commit" => match (
+            &notification.target,
+            &notification.inner.subject.latest_comment_url,
+        ) {
+            (_, Some(url)) => {
+                let comment: CommitComment = stats::rest(octo.get(url, None::<&()>)).await?;
+                Ok(comment.html_url)
+            }
+            (NotificationTarget::Commit(commit), None) => Ok(commit.html_url.clone()),
+            _ => Err(Error::HtmlUrlNotFound {
+                api_url: notification.inner.url.to_string(),
+            }),
+        },
+        "Gist" => match (
+            &notification.target,
+            &notification.inner.subject.latest_comment_url,
+        ) {
+            (NotificationTarget::Gist(gist), Some(url)) => {
+                let comment: GistComment = stats::rest(octo.get(url, None::<&()>)).await?;
+                Ok(format!("{}#gistcomment-{}", gist.html_url, comment.id))
+            }
+            (NotificationTarget::Gist(gist), None) => Ok(gist.html_url.clone()),
+            _ => Err(Error::HtmlUrlNotFound {
+                api_url: notification.inner.url.to_string(),
+            }),
+        },
+        "RepositoryInvitation" => match notification.target {
+            NotificationTarget::Invitation(ref invitation) => Ok(invitation.html_url.clone()),
+            _ => Err(Error::HtmlUrlNotFound {
+                api_url: notification.inner.url.to_string(),
+            }),
+        },
+        "RepositoryVulnerabilityAlert" | "RepositoryDependabotAlertsThread" => {
+            match notification.target {
+                NotificationTarget::SecurityAlert(ref alert) => Ok(alert.html_url()),
+                _ => Err(Error::HtmlUrlNotFound {
+                    api_url: notification.inner.url.to_string(),
+                }),
+            }
+        }
+        "CheckSuite" => match notification.target {
+            NotificationTarget::CiBuild(CiBuildMeta {
+                run: Some(ref run), ..
+            }) => Ok(run.html_url.clone()),
+            NotificationTarget::CiBuild(CiBuildMeta { ref repo, .. }) => Ok(format!(
+                "https://github.com/{}/{}/actions",
+                repo.owner, repo.name
+            )),
+            _ => Err(Error::HtmlUrlNotFound {
+                api_url: notification.inner.url.to_string(),
+            }),
+        },
         _ => Err(Error::HtmlUrlNotFound {
             api_url: notification.inner.url.to_string(),
         }),
     }
 }
 
+/// Find the workflow run that triggered a CheckSuite notification. The
+/// notification has no link to the run, so the latest run of the workflow
+/// on the branch that started before the notification was sent is used.
+pub async fn workflow_run(
+    octo: &Octocrab,
+    build: &CiBuildMeta,
+    updated_at: events::DateTimeUtc,
+) -> Result<Option<WorkflowRun>> {
+    #[derive(serde::Deserialize)]
+    struct Runs {
+        workflow_runs: Vec<WorkflowRunDeserModel>,
+    }
+    #[derive(serde::Deserialize)]
+    struct Jobs {
+        jobs: Vec<WorkflowJobDeserModel>,
+    }
+
+    let RepoMeta { owner, name } = &build.repo;
+    let mut params = vec![("per_page", "30")];
+    if let Some(ref branch) = build.branch {
+        params.push(("branch", branch));
+    }
+    let runs: Runs =
+        stats::rest(octo.get(format!("repos/{owner}/{name}/actions/runs"), Some(&params))).await?;
+    let run = runs.workflow_runs.into_iter().find(|run| {
+        run.name.as_deref() == Some(build.workflow.as_str()) && run.created_at <= updated_at
+    });
+    let run = match run {
+        Some(run) => run,
+        None => return Ok(None),
+    };
+
+    let failed_jobs = match CiConclusion::from(run.conclusion.as_deref().unwrap_or_default()) {
+        CiConclusion::Failure => {
+            let jobs: Jobs = stats::rest(octo.get(
+                format!("repos/{owner}/{name}/actions/runs/{}/jobs", run.id),
+                Some(&[("filter", "latest")]),
+            ))
+            .await?;
+            jobs.jobs
+                .into_iter()
+                .filter(|job| {
+                    CiConclusion::from(job.conclusion.as_deref().unwrap_or_default())
+                        == CiConclusion::Failure
+                })
+                .map(WorkflowJob::from)
+                .collect()
+        }
+        _ => Vec::new(),
+    };
+
+    Ok(Some(WorkflowRun {
+        id: run.id,
+        run_number: run.run_number,
+        event: run.event,
+        head_sha: run.head_sha,
+        html_url: run.html_url,
+        failed_jobs,
+    }))
+}
+
+/// Download the complete log of a workflow job as plain text.
+pub async fn workflow_job_log(octo: &Octocrab, repo: &RepoMeta, job_id: u64) -> Result<String> {
+    let url = octo.absolute_url(format!(
+        "repos/{}/{}/actions/jobs/{job_id}/logs",
+        repo.owner, repo.name
+    ))?;
+    // The API redirects to a short lived url of the log file, which isn't
+    // json and hence can't go through `Octocrab::get`.
+    let response = stats::rest(octo._get(url, None::<&()>)).await?;
+    let response = octocrab::map_github_error(response).await?;
+    response.text().await.map_err(|_| Error::JobLog)
+}
+
 /// Fetch additional information about the notification from the octocrab
 /// Notification model and construct a [`Notification`].
 pub async fn octo_notif_to_notif(
@@ -701,19 +1342,57 @@ pub async fn octo_notif_to_notif(
 ) -> Result<Notification> {
     let target = match (notif.subject.r#type.as_str(), notif.subject.url.as_ref()) {
         ("Issue", Some(url)) => {
-            let issue: IssueDeserModel = octo.get(url, None::<&()>).await?;
+            let issue: IssueDeserModel = cache::get(&octo, url).await?;
             NotificationTarget::Issue(IssueMeta::new(issue, RepoMeta::from(&notif.repository)))
         }
         ("PullRequest", Some(url)) => {
-            let pr: octocrab::models::pulls::PullRequest = octo.get(url, None::<&()>).await?;
+            let pr: octocrab::models::pulls::PullRequest = cache::get(&octo, url).await?;
             NotificationTarget::PullRequest(PullRequestMeta::new(
                 pr,
                 RepoMeta::from(&notif.repository),
             ))
         }
+        ("Commit", Some(url)) => {
+            let commit: CommitDeserModel = stats::rest(octo.get(url, None::<&()>)).await?;
+            NotificationTarget::Commit(CommitMeta::new(commit, RepoMeta::from(&notif.repository)))
+        }
+        ("Gist", Some(url)) => {
+            let gist: GistDeserModel = stats::rest(octo.get(url, None::<&()>)).await?;
+            NotificationTarget::Gist(gist.into())
+        }
+        ("RepositoryInvitation", _) => {
+            let repo = RepoMeta::from(&notif.repository);
+            // The notification doesn't link to the invitation, so look for
+            // a pending invitation to the same repo. Tokens without access
+            // to invitations shouldn't fail syncing all notifications.
+            let full_name = format!("{}/{}", repo.owner, repo.name);
+            let invitations: Option<Vec<InvitationDeserModel>> =
+                stats::rest(octo.get("user/repository_invitations", Some(&[("per_page", "100")])))
+                    .await
+                    .ok();
+            invitations
+                .into_iter()
+                .flatten()
+                .find(|i| i.repository.full_name.eq_ignore_ascii_case(&full_name))
+                .map(|i| NotificationTarget::Invitation(InvitationMeta::new(i, repo)))
+                .unwrap_or(NotificationTarget::Unknown)
+        }
+        ("RepositoryVulnerabilityAlert" | "RepositoryDependabotAlertsThread", _) => {
+            // Alerts need admin or security access to the repo, which is
+            // no reason to fail syncing all notifications.
+            security_alert(&octo, &notif)
+                .await
+                .ok()
+                .flatten()
+                .map(NotificationTarget::SecurityAlert)
+                .unwrap_or(NotificationTarget::Unknown)
+        }
         ("Release", Some(url)) => {
-            let release: octocrab::models::repos::Release = octo.get(url, None::<&()>).await?;
-            NotificationTarget::Release(release.into())
+            let release: octocrab::models::repos::Release = cache::get(&octo, url).await?;
+            NotificationTarget::Release(ReleaseMeta::new(
+                release,
+                RepoMeta::from(&notif.repository),
+            ))
         }
         ("Discussion", _) => {
             let query_vars = graphql::discussion_search_query::Variables {
@@ -757,7 +1436,18 @@ pub async fn octo_notif_to_notif(
                 .map(NotificationTarget::Discussion)
                 .unwrap_or(NotificationTarget::Unknown)
         }
-        ("CheckSuite", _) => NotificationTarget::CiBuild,
+        ("CheckSuite", _) => {
+            let mut build =
+                CiBuildMeta::from_title(&notif.subject.title, RepoMeta::from(&notif.repository));
+            // The run is only needed for the details of the build, so don't
+            // fail syncing all notifications if it can't be fetched (actions
+            // may be disabled or the token may not have access to them).
+            build.run = workflow_run(&octo, &build, notif.updated_at)
+                .await
+                .ok()
+                .flatten();
+            NotificationTarget::CiBuild(build)
+        }
         (_, _) => NotificationTarget::Unknown,
     };
 
@@ -767,6 +1457,110 @@ pub async fn octo_notif_to_notif(
     })
 }
 
+fn repo_meta(name_with_owner: &str) -> RepoMeta {
+    let (owner, name) = name_with_owner.split_once('/').unwrap_or_default();
+    RepoMeta {
+        name: name.to_string(),
+        owner: owner.to_string(),
+    }
+}
+
+/// Maximum number of results fetched by [`search`].
+const SEARCH_RESULTS: i64 = 50;
+
+/// Search issues and pull requests. Results are wrapped as notifications
+/// (see [`Notification::is_thread`]) so that they can be used in the same
+/// pipelines.
+pub async fn search(octo: &Octocrab, query: &str) -> Result<Vec<Notification>> {
+    use graphql::search_query::{
+        IssueState as GqlIssueState, IssueStateReason as GqlIssueStateReason,
+        PullRequestState as GqlPullRequestState, SearchQuerySearchNodes as Node,
+        SearchQuerySearchNodesOnIssueAuthorOn as IssueAuthor,
+        SearchQuerySearchNodesOnPullRequestAuthorOn as PullRequestAuthor,
+    };
+
+    let query_vars = graphql::search_query::Variables {
+        search: query.to_owned(),
+        first: SEARCH_RESULTS,
+    };
+    let data = graphql::query::<graphql::SearchQuery>(query_vars, octo).await?;
+    let nodes = data
+        .and_then(|d| d.search.nodes)
+        .unwrap_or_default()
+        .into_iter()
+        .flatten();
+
+    let mut results = Vec::new();
+    for node in nodes {
+        let (kind, updated_at, target) = match node {
+            Node::Issue(issue) => {
+                let repo = repo_meta(&issue.repository.name_with_owner);
+                let state = match (issue.state, issue.state_reason) {
+                    (GqlIssueState::OPEN, _) => IssueState::Open,
+                    (_, Some(GqlIssueStateReason::COMPLETED)) => {
+                        IssueState::Closed(IssueClosedReason::Completed)
+                    }
+                    _ => IssueState::Closed(IssueClosedReason::NotPlanned),
+                };
+                let labels = issue.labels.and_then(|l| l.nodes).unwrap_or_default();
+                let meta = IssueMeta {
+                    repo,
+                    title: issue.title,
+                    body: issue.body,
+                    number: issue.number as usize,
+                    author: author!(issue, IssueAuthor),
+                    state,
+                    created_at: issue.created_at,
+                    labels: labels
+                        .into_iter()
+                        .flatten()
+                        .map(|l| Label {
+                            name: l.name,
+                            color: l.color,
+                        })
+                        .collect(),
+                };
+                ("Issue", issue.updated_at, NotificationTarget::Issue(meta))
+            }
+            Node::PullRequest(pr) => {
+                let repo = repo_meta(&pr.repository.name_with_owner);
+                let state = match pr.state {
+                    GqlPullRequestState::OPEN => PullRequestState::Open,
+                    GqlPullRequestState::MERGED => PullRequestState::Merged,
+                    _ => PullRequestState::Closed,
+                };
+                let labels = pr.labels.and_then(|l| l.nodes).unwrap_or_default();
+                let meta = PullRequestMeta {
+                    repo,
+                    title: pr.title,
+                    body: pr.body,
+                    number: pr.number as usize,
+                    author: author!(pr, PullRequestAuthor),
+                    state,
+                    created_at: pr.created_at,
+                    branch: pr.head_ref_name,
+                    labels: labels
+                        .into_iter()
+                        .flatten()
+                        .map(|l| Label {
+                            name: l.name,
+                            color: l.color,
+                        })
+                        .collect(),
+                };
+                (
+                    "PullRequest",
+                    pr.updated_at,
+                    NotificationTarget::PullRequest(meta),
+                )
+            }
+            _ => continue,
+        };
+        results.push(Notification::synthetic(kind, updated_at, target)?);
+    }
+    Ok(results)
+}
+
 pub async fn open_notification_in_browser(notif: &Notification) -> Result<()> {
     let url = resolve_html_url(&octocrab::instance(), notif).await?;
     crate::util::open_url_in_browser(url)?;
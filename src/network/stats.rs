@@ -0,0 +1,172 @@
+//! Counts and durations of the requests made to GitHub, shown by the
+//! `stats` command.
+
+use std::{
+    future::Future,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use chrono::Utc;
+use octocrab::models::{Rate, RateLimit};
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Counts {
+    pub requests: usize,
+    /// Time spent waiting on all requests, summed even if they were made
+    /// concurrently.
+    pub time: Duration,
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Stats {
+    pub rest: Counts,
+    pub graphql: Counts,
+}
+
+impl Stats {
+    /// The requests made after `earlier` was taken.
+    pub fn since(&self, earlier: &Stats) -> Stats {
+        let diff = |now: Counts, then: Counts| Counts {
+            requests: now.requests - then.requests,
+            time: now.time - then.time,
+        };
+        Stats {
+            rest: diff(self.rest, earlier.rest),
+            graphql: diff(self.graphql, earlier.graphql),
+        }
+    }
+}
+
+/// A sync of all notifications.
+#[derive(Clone, Copy, Debug)]
+pub struct Refresh {
+    pub duration: Duration,
+    pub requests: Stats,
+}
+
+static TOTALS: Mutex<Stats> = Mutex::new(Stats {
+    rest: Counts {
+        requests: 0,
+        time: Duration::ZERO,
+    },
+    graphql: Counts {
+        requests: 0,
+        time: Duration::ZERO,
+    },
+});
+
+static LAST_REFRESH: Mutex<Option<Refresh>> = Mutex::new(None);
+
+/// All requests made so far.
+pub fn totals() -> Stats {
+    *TOTALS.lock().unwrap()
+}
+
+pub fn last_refresh() -> Option<Refresh> {
+    *LAST_REFRESH.lock().unwrap()
+}
+
+pub fn set_last_refresh(refresh: Refresh) {
+    *LAST_REFRESH.lock().unwrap() = Some(refresh);
+}
+
+/// Count a REST request and the time it takes.
+pub async fn rest<T>(request: impl Future<Output = T>) -> T {
+    let start = Instant::now();
+    let output = request.await;
+    let mut totals = TOTALS.lock().unwrap();
+    totals.rest.requests += 1;
+    totals.rest.time += start.elapsed();
+    output
+}
+
+/// Count a GraphQL request and the time it takes.
+pub async fn graphql<T>(request: impl Future<Output = T>) -> T {
+    let start = Instant::now();
+    let output = request.await;
+    let mut totals = TOTALS.lock().unwrap();
+    totals.graphql.requests += 1;
+    totals.graphql.time += start.elapsed();
+    output
+}
+
+/// Lines describing the last refresh, all requests and the rate limit, if
+/// it could be fetched.
+pub fn report(
+    last_refresh: Option<Refresh>,
+    totals: Stats,
+    rate_limit: Option<&RateLimit>,
+) -> Vec<String> {
+    let counts = |stats: Stats| {
+        format!(
+            "{} REST ({:.1}s) and {} GraphQL ({:.1}s) requests",
+            stats.rest.requests,
+            stats.rest.time.as_secs_f64(),
+            stats.graphql.requests,
+            stats.graphql.time.as_secs_f64()
+        )
+    };
+    let mut lines = Vec::new();
+    if let Some(refresh) = last_refresh {
+        lines.push(format!(
+            "Last refresh took {:.1}s: {}",
+            refresh.duration.as_secs_f64(),
+            counts(refresh.requests)
+        ));
+    }
+    lines.push(format!("Since start: {}", counts(totals)));
+    match rate_limit {
+        Some(rate_limit) => {
+            let rate =
+                |name: &str, rate: &Rate| format!("{name} {} of {} used", rate.used, rate.limit);
+            let mut rates = vec![rate("REST", &rate_limit.resources.core)];
+            if let Some(ref graphql) = rate_limit.resources.graphql {
+                rates.push(rate("GraphQL", graphql));
+            }
+            // `reset` is a unix timestamp
+            let minutes = (rate_limit.resources.core.reset as i64 - Utc::now().timestamp()) / 60;
+            lines.push(format!(
+                "Rate limit: {}, resets in {} minutes",
+                rates.join(", "),
+                minutes.max(0)
+            ));
+        }
+        None => lines.push("Rate limit: unavailable".to_string()),
+    }
+    lines
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let stats = |rest, graphql| Stats {
+            rest: Counts {
+                requests: rest,
+                time: Duration::from_millis(rest as u64 * 300),
+            },
+            graphql: Counts {
+                requests: graphql,
+                time: Duration::from_millis(graphql as u64 * 500),
+            },
+        };
+        assert_eq!(stats(12, 5).since(&stats(2, 1)), stats(10, 4));
+
+        let refresh = Refresh {
+            duration: Duration::from_millis(2500),
+            requests: stats(10, 4),
+        };
+        let report = report(Some(refresh), stats(12, 5), None);
+        assert_eq!(
+            report,
+            [
+                "Last refresh took 2.5s: 10 REST (3.0s) and 4 GraphQL (2.0s) requests",
+                "Since start: 12 REST (3.6s) and 5 GraphQL (2.5s) requests",
+                "Rate limit: unavailable",
+            ]
+        );
+    }
+}
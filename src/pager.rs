@@ -0,0 +1,194 @@
+//! A small full screen pager for output that doesn't fit in the terminal.
+
+use std::io::Write;
+
+use crossterm::{
+    cursor::{self, MoveTo},
+    event::{self, Event, KeyCode},
+    execute, queue,
+    style::{Print, Stylize},
+    terminal::{self, Clear, ClearType},
+    tty::IsTty,
+};
+
+use crate::error::{Error, Result};
+
+/// Print `text`, or page it if it is longer than the terminal is high and
+/// stdout is a terminal.
+pub fn print_or_page(text: &str) -> Result<()> {
+    let text = &crate::util::maybe_strip_ansi(text.to_string());
+    let fits = match terminal::size() {
+        Ok((_, rows)) => text.lines().count() < rows as usize,
+        Err(_) => true,
+    };
+    if fits || !std::io::stdout().is_tty() {
+        print!("{text}");
+        return std::io::stdout().flush().map_err(Error::Terminal);
+    }
+    page(text)
+}
+
+/// Show `text` in a full screen view that can be scrolled with j/k,
+/// space/b and g/G and searched with /, until q is pressed. Lines longer
+/// than the terminal are cut off.
+pub fn page(text: &str) -> Result<()> {
+    let text = crate::util::maybe_strip_ansi(text.to_string());
+    let lines: Vec<&str> = text.lines().collect();
+    let mut stdout = std::io::stdout();
+    terminal::enable_raw_mode().map_err(Error::Terminal)?;
+    let _ = execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide);
+
+    let result = Pager::new(&lines).run();
+
+    let _ = execute!(stdout, cursor::Show, terminal::LeaveAlternateScreen);
+    terminal::disable_raw_mode().map_err(Error::Terminal)?;
+    result
+}
+
+struct Pager<'a> {
+    lines: &'a [&'a str],
+    /// Lowercased lines without colors, to search in.
+    plain: Vec<String>,
+    top: usize,
+    query: String,
+    /// Search being typed after `/`.
+    prompt: Option<String>,
+    message: Option<&'static str>,
+}
+
+impl<'a> Pager<'a> {
+    fn new(lines: &'a [&'a str]) -> Self {
+        let plain = lines
+            .iter()
+            .map(|line| crate::util::strip_ansi(line).to_lowercase())
+            .collect();
+        Self {
+            lines,
+            plain,
+            top: 0,
+            query: String::new(),
+            prompt: None,
+            message: None,
+        }
+    }
+
+    fn run(&mut self) -> Result<()> {
+        loop {
+            let (_, rows) = terminal::size().map_err(Error::Terminal)?;
+            // The last row is used for the status line.
+            let height = (rows as usize).saturating_sub(1).max(1);
+            let max_top = self.lines.len().saturating_sub(height);
+            self.top = self.top.min(max_top);
+            self.draw(height).map_err(Error::Terminal)?;
+
+            let key = match event::read().map_err(Error::Terminal)? {
+                Event::Key(key) => key,
+                _ => continue,
+            };
+            if let Some(prompt) = &mut self.prompt {
+                match key.code {
+                    KeyCode::Char(ch) => prompt.push(ch),
+                    KeyCode::Backspace => {
+                        prompt.pop();
+                    }
+                    KeyCode::Enter => {
+                        self.query = prompt.to_lowercase();
+                        self.prompt = None;
+                        self.search(self.top, true);
+                    }
+                    KeyCode::Esc => self.prompt = None,
+                    _ => {}
+                }
+                continue;
+            }
+
+            self.message = None;
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Char('j') | KeyCode::Down | KeyCode::Enter => self.top += 1,
+                KeyCode::Char('k') | KeyCode::Up => self.top = self.top.saturating_sub(1),
+                KeyCode::Char(' ') | KeyCode::PageDown => self.top += height,
+                KeyCode::Char('b') | KeyCode::PageUp => self.top = self.top.saturating_sub(height),
+                KeyCode::Char('g') | KeyCode::Home => self.top = 0,
+                KeyCode::Char('G') | KeyCode::End => self.top = max_top,
+                KeyCode::Char('/') => self.prompt = Some(String::new()),
+                KeyCode::Char('n') => self.search(self.top + 1, true),
+                KeyCode::Char('N') => self.search(self.top, false),
+                _ => {}
+            }
+        }
+    }
+
+    /// Scroll to the next line matching the query, starting from line
+    /// `from`.
+    fn search(&mut self, from: usize, forward: bool) {
+        if self.query.is_empty() {
+            return;
+        }
+        match find(&self.plain, &self.query, from, forward) {
+            Some(line) => self.top = line,
+            None => self.message = Some("Pattern not found"),
+        }
+    }
+
+    fn draw(&self, height: usize) -> std::io::Result<()> {
+        let mut stdout = std::io::stdout();
+        queue!(stdout, Clear(ClearType::All))?;
+        for (row, line) in self.lines.iter().skip(self.top).take(height).enumerate() {
+            queue!(
+                stdout,
+                MoveTo(0, row as u16),
+                Print(line),
+                Clear(ClearType::UntilNewLine)
+            )?;
+        }
+        let status = match (&self.prompt, self.message) {
+            (Some(prompt), _) => format!("/{prompt}"),
+            (None, Some(message)) => format!(" {message} ").reverse().to_string(),
+            (None, None) => format!(
+                " {}-{} of {}  (j/k scroll, space/b page, g/G top/bottom, / search, n/N next/prev, q quit) ",
+                self.top + 1,
+                (self.top + height).min(self.lines.len()),
+                self.lines.len()
+            )
+            .reverse()
+            .to_string(),
+        };
+        queue!(
+            stdout,
+            MoveTo(0, height as u16),
+            Clear(ClearType::CurrentLine),
+            Print(status)
+        )?;
+        stdout.flush()
+    }
+}
+
+/// Index of the first line containing `query`, searching forward from line
+/// `from` or backward from the line before it, wrapping around at the ends.
+fn find(lines: &[String], query: &str, from: usize, forward: bool) -> Option<usize> {
+    let len = lines.len();
+    (0..len)
+        .map(|offset| match forward {
+            true => (from + offset) % len,
+            false => (from + len * 2 - 1 - offset) % len,
+        })
+        .find(|&i| lines[i].contains(query))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_find() {
+        let lines = ["alpha", "beta", "gamma", "alphabet"].map(String::from);
+        assert_eq!(find(&lines, "alpha", 0, true), Some(0));
+        assert_eq!(find(&lines, "alpha", 1, true), Some(3));
+        assert_eq!(find(&lines, "alpha", 3, false), Some(0));
+        assert_eq!(find(&lines, "alpha", 0, false), Some(3));
+        assert_eq!(find(&lines, "bet", 2, true), Some(3));
+        assert_eq!(find(&lines, "delta", 0, true), None);
+        assert_eq!(find(&[], "delta", 0, true), None);
+    }
+}
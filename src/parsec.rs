@@ -1,27 +1,68 @@
 //! Parser combinators used to parse the custom command line syntax.
 
-pub type ParseResult<'inp, Output> = Result<(&'inp str, Output), &'static str>;
+pub type ParseResult<'inp, Output> = Result<(&'inp str, Output), ParseError<'inp>>;
+
+/// Where and why parsing failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseError<'inp> {
+    /// The input remaining at the point of failure.
+    pub input: &'inp str,
+    /// Description of what was expected at that point, like `an index`.
+    pub expected: &'static str,
+}
+
+impl<'inp> ParseError<'inp> {
+    pub fn new(input: &'inp str, expected: &'static str) -> Self {
+        Self { input, expected }
+    }
 
-pub fn literal<'a>(lit: &'a str) -> impl Fn(&str) -> ParseResult<&'a str> {
+    /// Byte offset of the failure in `source`, the input that was
+    /// originally given to the parser.
+    pub fn position(&self, source: &str) -> usize {
+        source.len() - self.input.len()
+    }
+
+    /// Describe the error, followed by `source` with the offending word
+    /// underlined.
+    pub fn report(&self, source: &str) -> String {
+        let position = self.position(source);
+        let word = self
+            .input
+            .split(|ch: char| ch.is_whitespace() || ch == '|')
+            .next()
+            .filter(|word| !word.is_empty())
+            .or_else(|| self.input.get(..1))
+            .unwrap_or_default();
+        let message = match word {
+            "" => format!("Expected {}", self.expected),
+            word => format!("Unexpected `{word}`, expected {}", self.expected),
+        };
+        let padding = source[..position].chars().count();
+        let underline = "^".repeat(word.chars().count().max(1));
+        format!("{message}\n  {source}\n  {:padding$}{underline}", "")
+    }
+}
+
+pub fn literal(lit: &'static str) -> impl Fn(&str) -> ParseResult<&'static str> {
     move |input: &str| {
         input
             .strip_prefix(lit)
             .map(|rem| (rem, lit))
-            .ok_or("Could not parse literal")
+            .ok_or(ParseError::new(input, lit))
     }
 }
 
 pub fn pred(cond: impl Fn(char) -> bool) -> impl Fn(&str) -> ParseResult<char> {
     move |input: &str| match input.chars().next().filter(|ch| cond(*ch)) {
         Some(ch) => Ok((input.strip_prefix(ch).unwrap(), ch)),
-        None => Err("predicate not matched"),
+        None => Err(ParseError::new(input, "a different character")),
     }
 }
 
 pub fn peek(cond: impl Fn(char) -> bool) -> impl Fn(&str) -> ParseResult<char> {
     move |input: &str| match input.chars().next().filter(|ch| cond(*ch)) {
         Some(ch) => Ok((input, ch)),
-        None => Err("peek not matched"),
+        None => Err(ParseError::new(input, "a different character")),
     }
 }
 
@@ -56,7 +97,7 @@ pub fn many0<O>(parse: impl Fn(&str) -> ParseResult<O>) -> impl Fn(&str) -> Pars
 }
 
 pub fn whitespace1() -> impl Fn(&str) -> ParseResult<Vec<char>> {
-    many1(pred(|ch| ch.is_whitespace()))
+    expect(many1(pred(|ch| ch.is_whitespace())), "whitespace")
 }
 
 pub fn whitespace0() -> impl Fn(&str) -> ParseResult<Vec<char>> {
@@ -70,7 +111,7 @@ pub fn any<Output>(
         parsers
             .iter()
             .find_map(|p| p(input).ok())
-            .ok_or("Did not match any parser")
+            .ok_or(ParseError::new(input, "one of the alternatives"))
     }
 }
 
@@ -92,12 +133,29 @@ where
 /// return position gives an opaque type, and the array is typed with
 /// this opaque type making it impossible to use parsers returned by
 /// different combinators together).
+///
+/// If both fail, the error of the parser that got further is returned.
 pub fn or<P1, P2, O>(p1: P1, p2: P2) -> impl Fn(&str) -> ParseResult<O>
 where
     P1: Fn(&str) -> ParseResult<O>,
     P2: Fn(&str) -> ParseResult<O>,
 {
-    move |input: &str| p1(input).or_else(|_| p2(input))
+    move |input: &str| {
+        p1(input).or_else(|err1| {
+            p2(input).map_err(|err2| match err1.input.len() < err2.input.len() {
+                true => err1,
+                false => err2,
+            })
+        })
+    }
+}
+
+/// Replaces the description of what was expected when `parser` fails.
+pub fn expect<P, O>(parser: P, expected: &'static str) -> impl Fn(&str) -> ParseResult<O>
+where
+    P: Fn(&str) -> ParseResult<O>,
+{
+    move |input: &str| parser(input).map_err(|err| ParseError { expected, ..err })
 }
 
 pub fn left<P, O1, O2>(parser: P) -> impl Fn(&str) -> ParseResult<O1>
@@ -142,11 +200,61 @@ where
         let (next_input, output) = parser(input)?;
         match next_input {
             "" => Ok((next_input, output)),
-            _ => Err("input not fully consumed"),
+            _ => Err(ParseError::new(next_input, "end of input")),
         }
     }
 }
 
+/// Parses a character matching `cond`, or a backslash followed by
+/// whitespace or one of `\"|>`, which stands for that character.
+/// Backslashes before other characters are kept as is, so patterns
+/// like `\d+` don't need doubled backslashes.
+pub fn escaped(cond: impl Fn(char) -> bool) -> impl Fn(&str) -> ParseResult<char> {
+    move |input: &str| {
+        let is_special = |ch: &char| ch.is_whitespace() || "\\\"|>".contains(*ch);
+        if let Some(rem) = input.strip_prefix('\\') {
+            if let Some(ch) = rem.chars().next().filter(is_special) {
+                return Ok((&rem[ch.len_utf8()..], ch));
+            }
+        }
+        pred(&cond)(input)
+    }
+}
+
+/// Parses a string in double quotes, which may contain escaped quotes.
+pub fn quoted() -> impl Fn(&str) -> ParseResult<String> {
+    let contents = many0(escaped(|ch| ch != '"'));
+    let parser = left(and(right(and(literal("\""), contents)), literal("\"")));
+    move |input: &str| match parser(input) {
+        Ok((input, chars)) => Ok((input, chars.into_iter().collect())),
+        Err(err) if input.starts_with('"') => Err(ParseError::new(err.input, "a closing quote")),
+        Err(err) => Err(err),
+    }
+}
+
+/// Parses a relative duration like `3d` or `12h`. The supported units are
+/// minutes (`m`), hours (`h`), days (`d`) and weeks (`w`).
+pub fn duration() -> impl Fn(&str) -> ParseResult<chrono::Duration> {
+    move |start: &str| {
+        let (input, digits) = expect(many1(pred(|ch| ch.is_ascii_digit())), "a number")(start)?;
+        let amount = digits
+            .iter()
+            .collect::<String>()
+            .parse::<u16>()
+            .map_err(|_| ParseError::new(start, "a smaller duration"))?;
+        let (input, unit) =
+            expect(pred(|ch| "mhdw".contains(ch)), "a unit of m, h, d or w")(input)?;
+        let amount = i64::from(amount);
+        let duration = match unit {
+            'm' => chrono::Duration::minutes(amount),
+            'h' => chrono::Duration::hours(amount),
+            'd' => chrono::Duration::days(amount),
+            _ => chrono::Duration::weeks(amount),
+        };
+        Ok((input, duration))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -258,10 +366,75 @@ mod test {
         assert_eq!(parse("ow"), Ok(("ow", None)));
     }
 
+    #[test]
+    fn test_duration() {
+        let parse = duration();
+        assert_eq!(parse("3d"), Ok(("", chrono::Duration::days(3))));
+        assert_eq!(parse("12h ago"), Ok((" ago", chrono::Duration::hours(12))));
+        assert_eq!(parse("1w"), Ok(("", chrono::Duration::weeks(1))));
+        assert_eq!(parse("90m"), Ok(("", chrono::Duration::minutes(90))));
+        assert!(parse("3").is_err());
+        assert!(parse("3y").is_err());
+        assert!(parse("d").is_err());
+        assert!(parse("99999999w").is_err());
+    }
+
     #[test]
     fn test_eof() {
         let parse = eof(literal("list"));
         assert_eq!(parse("list"), Ok(("", "list")));
         assert!(parse("listed").is_err())
     }
+
+    #[test]
+    fn test_escaped() {
+        let parse = escaped(|ch| ch != ' ');
+        assert_eq!(parse("a"), Ok(("", 'a')));
+        assert_eq!(parse("\\ a"), Ok(("a", ' ')));
+        assert_eq!(parse("\\\\"), Ok(("", '\\')));
+        assert_eq!(parse("\\d"), Ok(("d", '\\')));
+        assert!(parse(" a").is_err());
+    }
+
+    #[test]
+    fn test_quoted() {
+        let parse = quoted();
+        assert_eq!(
+            parse("\"build failed\" 1"),
+            Ok((" 1", "build failed".into()))
+        );
+        assert_eq!(parse("\"say \\\"hi\\\"\""), Ok(("", "say \"hi\"".into())));
+        assert_eq!(parse("\"\""), Ok(("", String::new())));
+        assert_eq!(
+            parse("\"unterminated"),
+            Err(ParseError::new("", "a closing quote"))
+        );
+        assert!(parse("bare").is_err());
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let source = "list pr | bogus 1";
+        let err = ParseError::new(&source[10..], "an adapter or a consumer");
+        assert_eq!(err.position(source), 10);
+        assert_eq!(
+            err.report(source),
+            "Unexpected `bogus`, expected an adapter or a consumer\n  \
+             list pr | bogus 1\n            ^^^^^"
+        );
+        let err = ParseError::new("", "a closing quote");
+        assert_eq!(
+            err.report("grep \"a"),
+            "Expected a closing quote\n  grep \"a\n         ^"
+        );
+    }
+
+    #[test]
+    fn test_or_furthest_error() {
+        let parse = or(
+            and(literal("li"), literal("st")),
+            and(literal("l"), literal("x")),
+        );
+        assert_eq!(parse("lisp"), Err(ParseError::new("sp", "st")));
+    }
 }
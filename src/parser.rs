@@ -10,14 +10,71 @@ use self::types::{
     ProducerWithArgs,
 };
 
+/// Parses a run of characters up to whitespace or a pipe, so that
+/// arguments like `/fla+ky/` can be written without quoting. Parts in
+/// double quotes and backslash escaped characters may contain whitespace
+/// and pipes, as in `grep "build failed"` or `label:help\ wanted`.
+///
+/// Words can't start with `>` since it is used for redirection.
 fn word() -> impl Fn(&str) -> ParseResult<String> {
-    let parser = many1(pred(|ch| ch.is_alphanumeric()));
-    map(parser, |chars| chars.iter().collect())
+    let bare = many1(escaped(|ch| !ch.is_whitespace() && ch != '|' && ch != '"'));
+    let bare = map(bare, |chars| chars.into_iter().collect::<String>());
+    let parser = right(and(peek(|ch| ch != '>'), many1(or(quoted(), bare))));
+    map(parser, |parts| parts.concat())
 }
 
+/// Parses words up to a pipe, a redirection or the end of input.
 fn args() -> impl Fn(&str) -> ParseResult<Vec<String>> {
+    checked_args(|_| true, "an argument")
+}
+
+/// Parses arguments like [`args`], failing at the first one that is not
+/// `valid`.
+fn checked_args(
+    valid: impl Fn(&str) -> bool,
+    expected: &'static str,
+) -> impl Fn(&str) -> ParseResult<Vec<String>> {
     let arg = left(and(word(), whitespace0()));
-    many0(arg)
+    move |mut input: &str| {
+        let mut args = Vec::new();
+        while !input.is_empty()
+            && !input.starts_with(['|', '>'])
+            && !input.starts_with(char::is_whitespace)
+        {
+            let (rem, word) = arg(input)?;
+            if !valid(&word) {
+                return Err(ParseError::new(input, expected));
+            }
+            args.push(word);
+            input = rem;
+        }
+        Ok((input, args))
+    }
+}
+
+const LIST_FILTERS_EXPECTED: &str = "a filter (pr, issue, discussion, release, ci, commit, \
+    invitation, alert, gist, deps, open, closed, merged, participating, repo:owner/name, \
+    org:owner, label:name, reason:name or updated:>3d), optionally negated with !";
+
+/// Whether `arg` is a filter known to `list`, possibly negated.
+pub fn is_list_filter(arg: &str) -> bool {
+    let arg = arg.strip_prefix('!').unwrap_or(arg);
+    Producer::list_filters().contains(&arg)
+        || Producer::list_qualifiers()
+            .iter()
+            .any(|q| arg.starts_with(q))
+}
+
+/// Parses the filters of `list`, rejecting unknown ones.
+fn list_args() -> impl Fn(&str) -> ParseResult<Vec<String>> {
+    checked_args(is_list_filter, LIST_FILTERS_EXPECTED)
+}
+
+/// Parses words that don't start with a digit, so that they can precede
+/// [`uint_args`].
+fn opts() -> impl Fn(&str) -> ParseResult<Vec<String>> {
+    let opt = right(and(peek(|ch| !ch.is_ascii_digit()), word()));
+    many0(left(and(opt, whitespace0())))
 }
 
 fn uint() -> impl Fn(&str) -> ParseResult<usize> {
@@ -26,9 +83,36 @@ fn uint() -> impl Fn(&str) -> ParseResult<usize> {
     map(parser, chars_to_usize)
 }
 
+/// Parses a single index or an inclusive range of indices like `3-7`.
+fn uint_range() -> impl Fn(&str) -> ParseResult<Vec<usize>> {
+    let range = and(uint(), maybe(right(and(literal("-"), uint()))));
+    move |input: &str| {
+        let (rem, (start, end)) = range(input)?;
+        match end {
+            None => Ok((rem, vec![start])),
+            Some(end) if start > end => Err(ParseError::new(input, "a range in increasing order")),
+            // Guard against typos like `1-10000000` allocating huge vectors
+            Some(end) if end - start >= 10_000 => Err(ParseError::new(input, "a smaller range")),
+            Some(end) => Ok((rem, (start..=end).collect())),
+        }
+    }
+}
+
+/// Parses indices and ranges separated by whitespace. Anything starting
+/// with a digit has to be a valid index or range. Indices repeated by
+/// overlapping ranges are only kept the first time.
 fn uint_args() -> impl Fn(&str) -> ParseResult<Vec<usize>> {
-    let arg = left(and(uint(), whitespace0()));
-    many0(arg)
+    let arg = left(and(uint_range(), whitespace0()));
+    move |mut input: &str| {
+        let mut indices = Vec::new();
+        let mut seen = std::collections::HashSet::new();
+        while input.starts_with(|ch: char| ch.is_ascii_digit()) {
+            let (rem, range) = arg(input)?;
+            indices.extend(range.into_iter().filter(|i| seen.insert(*i)));
+            input = rem;
+        }
+        Ok((input, indices))
+    }
 }
 
 /// Parses any of the given literals into an Enum.
@@ -38,90 +122,207 @@ where
 {
     move |input: &str| {
         let lits_parser = lits.map(literal);
-        let (input, prod) = any(&lits_parser)(input)?;
-        Ok((input, E::try_from(prod)?))
+        let (rem, prod) = any(&lits_parser)(input)?;
+        let parsed = E::try_from(prod).map_err(|expected| ParseError::new(input, expected))?;
+        Ok((rem, parsed))
     }
 }
 
 fn command() -> impl Fn(&str) -> ParseResult<Command> {
-    literal_to_enum(Command::all())
+    expect(literal_to_enum(Command::all()), "a command")
 }
 
 fn producer() -> impl Fn(&str) -> ParseResult<Producer> {
-    literal_to_enum(Producer::all())
+    expect(literal_to_enum(Producer::all()), "a producer")
 }
 
 fn adapter() -> impl Fn(&str) -> ParseResult<Adapter> {
-    literal_to_enum(Adapter::all())
+    expect(literal_to_enum(Adapter::all()), "an adapter")
 }
 
 fn consumer() -> impl Fn(&str) -> ParseResult<Consumer> {
-    literal_to_enum(Consumer::all())
+    expect(literal_to_enum(Consumer::all()), "a consumer")
 }
 
 fn pipe() -> impl Fn(&str) -> ParseResult<()> {
-    map(literal("|"), |_| ())
+    map(and(literal("|"), whitespace0()), |_| ())
+}
+
+/// Parses whitespace separated arguments if there is whitespace before
+/// them. Unlike with [`maybe`], errors in the arguments are reported
+/// instead of leaving them unparsed.
+fn optional_args<A>(args: impl Fn(&str) -> ParseResult<A>) -> impl Fn(&str) -> ParseResult<A>
+where
+    A: Default,
+{
+    let args = right(and(whitespace1(), args));
+    move |input: &str| match input.starts_with(char::is_whitespace) {
+        true => args(input),
+        false => Ok((input, A::default())),
+    }
+}
+
+/// Parses a name followed by [`optional_args`].
+fn with_args<N, A>(
+    name: impl Fn(&str) -> ParseResult<N>,
+    args: impl Fn(&str) -> ParseResult<A>,
+) -> impl Fn(&str) -> ParseResult<(N, A)>
+where
+    A: Default,
+{
+    and(name, optional_args(args))
 }
 
 fn producer_with_args() -> impl Fn(&str) -> ParseResult<ProducerWithArgs> {
-    let maybe_args = maybe(right(and(whitespace1(), args())));
-    map(and(producer(), maybe_args), |(producer, args)| {
-        ProducerWithArgs {
-            producer,
-            args: args.unwrap_or_default(),
-        }
-    })
+    let producer = producer();
+    let list_args = optional_args(list_args());
+    let args = optional_args(args());
+    move |input: &str| {
+        let (input, producer) = producer(input)?;
+        let (input, args) = match producer {
+            Producer::List => list_args(input)?,
+            _ => args(input)?,
+        };
+        Ok((input, ProducerWithArgs { producer, args }))
+    }
 }
 
 fn consumer_with_args() -> impl Fn(&str) -> ParseResult<ConsumerWithArgs> {
-    let maybe_args = maybe(right(and(whitespace1(), uint_args())));
-    map(and(consumer(), maybe_args), |(consumer, args)| {
+    let parser = with_args(consumer(), and(opts(), uint_args()));
+    map(parser, |(consumer, (opts, args))| ConsumerWithArgs {
+        consumer,
+        opts,
+        args,
+    })
+}
+
+fn consumer_with_opts() -> impl Fn(&str) -> ParseResult<ConsumerWithArgs> {
+    map(with_args(consumer(), opts()), |(consumer, opts)| {
         ConsumerWithArgs {
             consumer,
-            args: args.unwrap_or_default(),
+            opts,
+            args: Vec::new(),
         }
     })
 }
 
 fn adapter_with_args() -> impl Fn(&str) -> ParseResult<AdapterWithArgs> {
-    let maybe_args = maybe(right(and(whitespace1(), args())));
-    map(and(adapter(), maybe_args), |(adapter, args)| {
-        AdapterWithArgs {
-            adapter,
-            args: args.unwrap_or_default(),
-        }
+    map(with_args(adapter(), args()), |(adapter, args)| {
+        AdapterWithArgs { adapter, args }
     })
 }
 
-fn producer_expr() -> impl Fn(&str) -> ParseResult<ProducerExpr> {
-    // TODO: Handle whitespace
+/// Parses `> file`, with optional whitespace around the file name.
+fn redirect() -> impl Fn(&str) -> ParseResult<String> {
+    let arrow = and(whitespace0(), and(literal(">"), whitespace0()));
+    left(and(right(and(arrow, word())), whitespace0()))
+}
+
+/// Parses adapters following a pipe, stopping at the first name after a
+/// pipe that isn't an adapter.
+fn piped_adapters() -> impl Fn(&str) -> ParseResult<Vec<AdapterWithArgs>> {
     let piped_adapter = right(and(pipe(), adapter_with_args()));
-    let piped_adapters = many0(piped_adapter);
-    let piped_consumer = right(and(pipe(), consumer()));
+    move |mut input: &str| {
+        let mut adapters = Vec::new();
+        loop {
+            match piped_adapter(input) {
+                Ok((rem, adapter)) => {
+                    adapters.push(adapter);
+                    input = rem;
+                }
+                // Report errors in the arguments of a recognised adapter
+                Err(err) if matches!(pipe()(input), Ok((name, _)) if adapter()(name).is_ok()) => {
+                    return Err(err)
+                }
+                Err(_) => return Ok((input, adapters)),
+            }
+        }
+    }
+}
+
+fn producer_expr() -> impl Fn(&str) -> ParseResult<ProducerExpr> {
+    let piped_adapters = piped_adapters();
+    let piped_consumer = right(and(pipe(), consumer_with_opts()));
 
     let producer_expr = and(
         and(producer_with_args(), piped_adapters),
-        maybe(piped_consumer),
+        and(maybe(piped_consumer), maybe(redirect())),
     );
-    map(producer_expr, |((prod_with_args, adap_with_args), cons)| {
-        ProducerExpr {
+    let producer_expr = map(
+        producer_expr,
+        |((prod_with_args, adap_with_args), (cons, redirect))| ProducerExpr {
             producer: prod_with_args,
             adapters: adap_with_args,
             consumer: cons,
+            redirect,
+        },
+    );
+    move |input: &str| {
+        let (rem, pexpr) = producer_expr(input)?;
+        // A pipe left over means the name after it wasn't recognised
+        match pipe()(rem) {
+            Ok((name, _)) if pexpr.consumer.is_none() => {
+                Err(ParseError::new(name, "an adapter or a consumer"))
+            }
+            _ => Ok((rem, pexpr)),
         }
-    })
+    }
+}
+
+/// Parses `help` with an optional name to show help for.
+fn help() -> impl Fn(&str) -> ParseResult<Option<String>> {
+    right(and(
+        literal("help"),
+        maybe(right(and(whitespace1(), word()))),
+    ))
+}
+
+/// Parses `new issue <owner/name>` with the labels to add to the issue.
+fn new_issue() -> impl Fn(&str) -> ParseResult<(String, Vec<String>)> {
+    right(and(
+        and(literal("new"), whitespace1()),
+        right(and(
+            and(literal("issue"), whitespace1()),
+            and(expect(word(), "a repository"), optional_args(args())),
+        )),
+    ))
 }
 
 fn parser() -> impl Fn(&str) -> ParseResult<Parsed> {
-    let command = map(eof(command()), Parsed::Command);
-    let prod_expr = map(eof(producer_expr()), Parsed::ProducerExpr);
-    let cons_with_args = map(eof(consumer_with_args()), Parsed::ConsumerWithArgs);
+    let help = map(help(), Parsed::Help);
+    let new_issue = map(new_issue(), |(repo, labels)| Parsed::NewIssue {
+        repo,
+        labels,
+    });
+    let command = map(command(), Parsed::Command);
+    let prod_expr = map(producer_expr(), Parsed::ProducerExpr);
+    let cons_with_args = map(consumer_with_args(), Parsed::ConsumerWithArgs);
+
+    let parsed = or(
+        or(or(or(help, new_issue), command), prod_expr),
+        cons_with_args,
+    );
+    right(and(whitespace0(), eof(left(and(parsed, whitespace0())))))
+}
 
-    or(or(command, prod_expr), cons_with_args)
+pub fn parse(input: &str) -> ParseResult<'_, Parsed> {
+    parser()(input).map_err(|err| match err.input.len() == input.len() {
+        true => ParseError::new(input, "a command, producer or consumer"),
+        false => err,
+    })
 }
 
-pub fn parse(input: &str) -> ParseResult<Parsed> {
-    parser()(input)
+/// Replace `$_` with the indices listed by the last `list` pipeline.
+pub fn expand_last_result(
+    cmdline: &str,
+    last_result: Option<&[usize]>,
+) -> Result<String, &'static str> {
+    if !cmdline.contains("$_") {
+        return Ok(cmdline.to_string());
+    }
+    let indices = last_result.ok_or("No previous list to use for $_")?;
+    let indices: Vec<_> = indices.iter().map(|i| i.to_string()).collect();
+    Ok(cmdline.replace("$_", &indices.join(" ")))
 }
 
 #[cfg(test)]
@@ -139,6 +340,21 @@ mod test {
         let parse = word();
         assert_eq!(parse("list"), Ok(("", s!("list"))));
         assert_eq!(parse("list pr"), Ok((" pr", s!("list"))));
+        assert_eq!(parse("/fla+ky/|done"), Ok(("|done", s!("/fla+ky/"))));
+        assert_eq!(parse("updated:>3d"), Ok(("", s!("updated:>3d"))));
+        assert_eq!(
+            parse("\"build failed\"|done"),
+            Ok(("|done", s!("build failed")))
+        );
+        assert_eq!(
+            parse("label:\"help wanted\" pr"),
+            Ok((" pr", s!("label:help wanted")))
+        );
+        assert_eq!(parse("a\\|b\\ c d"), Ok((" d", s!("a|b c"))));
+        assert_eq!(parse("/\\d+/"), Ok(("", s!("/\\d+/"))));
+        assert_eq!(parse("\\>x"), Ok(("", s!(">x"))));
+        assert!(parse("\"open").is_err());
+        assert!(parse("> prs.txt").is_err());
         assert!(parse("").is_err())
     }
 
@@ -162,6 +378,15 @@ mod test {
         assert_eq!(parse("  "), Ok(("  ", vec![])));
     }
 
+    #[test]
+    fn test_opts() {
+        let parse = opts();
+        assert_eq!(parse("repo 1 2"), Ok(("1 2", vec![s!("repo")])));
+        assert_eq!(parse("csv all"), Ok(("", vec![s!("csv"), s!("all")])));
+        assert_eq!(parse("12"), Ok(("12", vec![])));
+        assert_eq!(parse(""), Ok(("", vec![])));
+    }
+
     #[test]
     fn test_uint() {
         let parse = uint();
@@ -170,11 +395,25 @@ mod test {
         assert!(parse("").is_err())
     }
 
+    #[test]
+    fn test_uint_range() {
+        let parse = uint_range();
+        assert_eq!(parse("3"), Ok(("", vec![3])));
+        assert_eq!(parse("3-6 1"), Ok((" 1", vec![3, 4, 5, 6])));
+        assert_eq!(parse("3-3"), Ok(("", vec![3])));
+        assert_eq!(parse("3-"), Ok(("-", vec![3])));
+        assert!(parse("6-3").is_err());
+        assert!(parse("1-100000").is_err());
+    }
+
     #[test]
     fn test_uint_args() {
         let parse = uint_args();
         assert_eq!(parse("12 23 345"), Ok(("", vec![12, 23, 345])));
+        assert_eq!(parse("3-5 10 12-13"), Ok(("", vec![3, 4, 5, 10, 12, 13])));
         assert_eq!(parse("12 23 "), Ok(("", vec![12, 23])));
+        assert_eq!(parse("1-3 2-4"), Ok(("", vec![1, 2, 3, 4])));
+        assert_eq!(parse("3 3 1"), Ok(("", vec![3, 1])));
         assert_eq!(parse("12 23 | open"), Ok(("| open", vec![12, 23])));
         assert_eq!(parse("12 23| open"), Ok(("| open", vec![12, 23])));
         assert_eq!(parse(""), Ok(("", vec![])));
@@ -185,6 +424,12 @@ mod test {
     fn test_command() {
         let parse = command();
         assert_eq!(parse("reload"), Ok(("", Command::Reload)));
+        assert_eq!(parse("dates"), Ok(("", Command::Dates)));
+        assert_eq!(parse("releases"), Ok(("", Command::Releases)));
+        assert_eq!(parse("stats"), Ok(("", Command::Stats)));
+        assert_eq!(parse("error"), Ok(("", Command::LastError)));
+        assert_eq!(parse("participating"), Ok(("", Command::Participating)));
+        assert_eq!(parse("digest"), Ok(("", Command::Digest)));
         assert!(parse("list").is_err());
     }
 
@@ -193,6 +438,8 @@ mod test {
         let parse = consumer();
         assert_eq!(parse("done"), Ok(("", Consumer::Done)));
         assert_eq!(parse("open"), Ok(("", Consumer::Open)));
+        assert_eq!(parse("logs"), Ok(("", Consumer::Logs)));
+        assert_eq!(parse("decline"), Ok(("", Consumer::Decline)));
         assert_eq!(parse("open 1 2"), Ok((" 1 2", Consumer::Open)));
         assert!(parse("list").is_err());
     }
@@ -230,29 +477,40 @@ mod test {
     #[test]
     fn test_consumer_with_args() {
         let parse = consumer_with_args();
-        let test = |input, cons, args: &[usize], next_input| {
+        let test = |input, cons, opts: &[&str], args: &[usize], next_input| {
             assert_eq!(
                 parse(input),
                 Ok((
                     next_input,
                     ConsumerWithArgs {
                         consumer: cons,
+                        opts: opts.iter().map(ToString::to_string).collect(),
                         args: args.to_vec(),
                     }
                 ))
             );
         };
-        test("done 1 12", Consumer::Done, &[1, 12], "");
-        test("done", Consumer::Done, &[], "");
+        test("done 1 12", Consumer::Done, &[], &[1, 12], "");
+        test("done", Consumer::Done, &[], &[], "");
+        test("done 3-5 9", Consumer::Done, &[], &[3, 4, 5, 9], "");
+        test("count repo", Consumer::Count, &["repo"], &[], "");
+        test("count repo 1 2", Consumer::Count, &["repo"], &[1, 2], "");
+        test(
+            "edit src/main.rs 3",
+            Consumer::Edit,
+            &["src/main.rs"],
+            &[3],
+            "",
+        );
         // Fake syntax
-        test("open 1 ; done", Consumer::Open, &[1], "; done");
+        test("open 1 ; done", Consumer::Open, &[], &[1], "; done");
     }
 
     macro_rules! pexpr {
         (
             $prod:ident $($prod_args:expr)?
             $(=> [$adap:ident $($adap_args:expr)?])*
-            $(=> $cons:ident)?
+            $(=> $cons:ident $($cons_opts:expr)?)?
         ) => {
             ProducerExpr {
                 producer: ProducerWithArgs {
@@ -265,7 +523,8 @@ mod test {
                         args: pexpr!(@maybe_args $($adap_args)?),
                     },
                 )*],
-                consumer: pexpr!(@optional_conusmer $($cons)?),
+                consumer: pexpr!(@optional_conusmer $($cons $($cons_opts)?)?),
+                redirect: None,
             }
         };
 
@@ -273,7 +532,13 @@ mod test {
         (@maybe_args $args:expr) => { $args.iter().map(ToString::to_string).collect() };
 
         (@optional_conusmer) => { None };
-        (@optional_conusmer $val:ident) => { Some(Consumer::$val) };
+        (@optional_conusmer $val:ident $($opts:expr)?) => {
+            Some(ConsumerWithArgs {
+                consumer: Consumer::$val,
+                opts: pexpr!(@maybe_args $($opts)?),
+                args: vec![],
+            })
+        };
     }
 
     #[test]
@@ -337,6 +602,46 @@ mod test {
             pexpr!(List => [Confirm] => [Confirm] => Done),
             "bare producer and bare adapter*s* and bare consumer"
         );
+        test!(
+            "list pr | sort updated | done",
+            pexpr!(List ["pr"] => [Sort ["updated"]] => Done),
+            "whitespace around pipes"
+        );
+        test!(
+            "list issue | sort updated | limit 10 | open",
+            pexpr!(List ["issue"] => [Sort ["updated"]] => [Head ["10"]] => Open),
+            "adapter with numeric args"
+        );
+        test!(
+            "list|reverse|tail 3",
+            pexpr!(List => [Reverse] => [Tail ["3"]]),
+            "bare adapter followed by adapter with args"
+        );
+        test!(
+            "list | count repo",
+            pexpr!(List => Count ["repo"]),
+            "consumer with options"
+        );
+        test!(
+            "list pr | grep flaky test | done",
+            pexpr!(List ["pr"] => [Grep ["flaky", "test"]] => Done),
+            "adapter with multiple args"
+        );
+        test!(
+            "search is:open label:bug | open",
+            pexpr!(Search ["is:open", "label:bug"] => Open),
+            "search producer"
+        );
+        test!(
+            "repo helix-editor/helix issues open",
+            pexpr!(Repo ["helix-editor/helix", "issues", "open"]),
+            "repo producer"
+        );
+        test!(
+            "list pr | tee | confirm | done",
+            pexpr!(List ["pr"] => [Tee] => [Confirm] => Done),
+            "bare adapters with whitespace around pipes"
+        );
 
         // Expected because eof is not enforced in the producer_expr parser
         // but rather in the top level parser.
@@ -347,6 +652,28 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_redirect() {
+        let parse = producer_expr();
+        let redirected = |pexpr: ProducerExpr, file: &str| ProducerExpr {
+            redirect: Some(file.to_string()),
+            ..pexpr
+        };
+        assert_eq!(
+            parse("list pr open > prs.txt"),
+            Ok(("", redirected(pexpr!(List ["pr", "open"]), "prs.txt")))
+        );
+        assert_eq!(
+            parse("list|sort repo >prs.txt "),
+            Ok(("", redirected(pexpr!(List => [Sort ["repo"]]), "prs.txt")))
+        );
+        assert_eq!(
+            parse("list | done > prs.txt"),
+            Ok(("", redirected(pexpr!(List => Done), "prs.txt")))
+        );
+        assert_eq!(parse("list >"), Ok((">", pexpr!(List))));
+    }
+
     #[test]
     fn test_parser() {
         let parse = parser();
@@ -366,10 +693,111 @@ mod test {
                 "",
                 Parsed::ConsumerWithArgs(ConsumerWithArgs {
                     consumer: Consumer::Done,
+                    opts: vec![],
                     args: vec![1, 2]
                 })
             ))
         );
         assert!(parse("lister").is_err());
+        assert_eq!(
+            parse("  list  pr\t|  sort updated  |confirm|   done  "),
+            Ok((
+                "",
+                Parsed::ProducerExpr(
+                    pexpr!(List ["pr"] => [Sort ["updated"]] => [Confirm] => Done)
+                )
+            )),
+        );
+        assert_eq!(
+            parse(" reload "),
+            Ok(("", Parsed::Command(Command::Reload)))
+        );
+    }
+
+    #[test]
+    fn test_list_args() {
+        let parse = list_args();
+        let filters = ["pr", "!release", "repo:helix-editor/helix", "updated:>3d"];
+        assert_eq!(
+            parse(&filters.join(" ")),
+            Ok(("", filters.map(String::from).to_vec()))
+        );
+        for filter in Producer::list_filters()
+            .into_iter()
+            .chain(Producer::list_qualifiers())
+        {
+            assert!(parse(filter).is_ok());
+            assert!(LIST_FILTERS_EXPECTED.contains(filter.trim_end_matches(':')));
+        }
+        assert!(parse("prs").is_err());
+        assert!(parse("!").is_err());
+    }
+
+    #[test]
+    fn test_help() {
+        let parse = parser();
+        assert_eq!(parse("help"), Ok(("", Parsed::Help(None))));
+        assert_eq!(parse("help grep"), Ok(("", Parsed::Help(Some(s!("grep"))))));
+        assert!(parse("help grep tee").is_err());
+    }
+
+    #[test]
+    fn test_new_issue() {
+        let parse = parser();
+        assert_eq!(
+            parse("new issue helix-editor/helix"),
+            Ok((
+                "",
+                Parsed::NewIssue {
+                    repo: s!("helix-editor/helix"),
+                    labels: vec![]
+                }
+            ))
+        );
+        assert_eq!(
+            parse("new issue helix-editor/helix C-bug \"A-language support\""),
+            Ok((
+                "",
+                Parsed::NewIssue {
+                    repo: s!("helix-editor/helix"),
+                    labels: vec![s!("C-bug"), s!("A-language support")]
+                }
+            ))
+        );
+        assert!(parse("new issue").is_err());
+        assert!(parse("new pr helix-editor/helix").is_err());
+    }
+
+    #[test]
+    fn test_expand_last_result() {
+        assert_eq!(expand_last_result("done 1", None), Ok(s!("done 1")));
+        assert_eq!(
+            expand_last_result("done $_", Some(&[3, 5])),
+            Ok(s!("done 3 5"))
+        );
+        assert!(expand_last_result("open $_", None).is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let expected = |input| parse(input).map_err(|err| (err.position(input), err.expected));
+        assert_eq!(
+            expected("bogus"),
+            Err((0, "a command, producer or consumer"))
+        );
+        assert_eq!(
+            expected("list pr | bogus"),
+            Err((10, "an adapter or a consumer"))
+        );
+        assert_eq!(expected("list | done | tee"), Err((12, "end of input")));
+        assert_eq!(
+            expected("done 1 7-3"),
+            Err((7, "a range in increasing order"))
+        );
+        assert_eq!(expected("search \"build"), Err((13, "a closing quote")));
+        assert_eq!(expected("list | grep \"x"), Err((14, "a closing quote")));
+        assert_eq!(expected("list pr bogus"), Err((8, LIST_FILTERS_EXPECTED)));
+        assert_eq!(expected("list !bogus"), Err((5, LIST_FILTERS_EXPECTED)));
+        assert_eq!(expected("list |"), Err((6, "an adapter or a consumer")));
     }
 }
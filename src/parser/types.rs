@@ -3,11 +3,32 @@
 #[derive(Debug, PartialEq)]
 pub enum Command {
     Reload,
+    /// Toggle between relative and absolute dates.
+    Dates,
+    /// Show a digest of all release notifications.
+    Releases,
+    /// Show how long refreshes took and how many requests were made.
+    Stats,
+    /// Show the details of the last error.
+    LastError,
+    /// Toggle between fetching all notifications and only the ones of
+    /// threads the user is participating in.
+    Participating,
+    /// Print a markdown digest of the notifications of the past week.
+    Digest,
 }
 
 impl Command {
-    pub const fn all() -> [&'static str; 1] {
-        ["reload"]
+    pub const fn all() -> [&'static str; 7] {
+        [
+            "reload",
+            "dates",
+            "releases",
+            "stats",
+            "error",
+            "participating",
+            "digest",
+        ]
     }
 }
 
@@ -17,6 +38,12 @@ impl TryFrom<&str> for Command {
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         match value {
             "reload" => Ok(Self::Reload),
+            "dates" => Ok(Self::Dates),
+            "releases" => Ok(Self::Releases),
+            "stats" => Ok(Self::Stats),
+            "error" => Ok(Self::LastError),
+            "participating" => Ok(Self::Participating),
+            "digest" => Ok(Self::Digest),
             _ => Err("not a command"),
         }
     }
@@ -27,11 +54,46 @@ impl TryFrom<&str> for Command {
 #[derive(Debug, PartialEq)]
 pub enum Producer {
     List,
+    /// Search issues and pull requests on GitHub.
+    Search,
+    /// Issues and pull requests of a repository.
+    Repo,
+    /// Open issues and pull requests assigned to the user.
+    Assigned,
+    /// Open pull requests waiting for a review from the user.
+    Reviews,
+    /// Open pull requests opened by the user.
+    Created,
 }
 
 impl Producer {
-    pub const fn all() -> [&'static str; 1] {
-        ["list"]
+    pub const fn all() -> [&'static str; 6] {
+        ["list", "search", "repo", "assigned", "reviews", "created"]
+    }
+
+    /// Filters accepted by `list`. Any filter can be negated with `!`.
+    pub const fn list_filters() -> [&'static str; 14] {
+        [
+            "pr",
+            "issue",
+            "discussion",
+            "release",
+            "ci",
+            "commit",
+            "invitation",
+            "alert",
+            "gist",
+            "deps",
+            "open",
+            "closed",
+            "merged",
+            "participating",
+        ]
+    }
+
+    /// Prefixes of `list` filters that take a value, like `repo:owner/name`.
+    pub const fn list_qualifiers() -> [&'static str; 5] {
+        ["repo:", "org:", "label:", "reason:", "updated:"]
     }
 }
 
@@ -41,6 +103,11 @@ impl TryFrom<&str> for Producer {
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         match value {
             "list" => Ok(Self::List),
+            "search" => Ok(Self::Search),
+            "repo" => Ok(Self::Repo),
+            "assigned" => Ok(Self::Assigned),
+            "reviews" => Ok(Self::Reviews),
+            "created" => Ok(Self::Created),
             _ => Err("not a producer"),
         }
     }
@@ -51,11 +118,25 @@ impl TryFrom<&str> for Producer {
 #[derive(Debug, PartialEq)]
 pub enum Adapter {
     Confirm,
+    /// Reorder the notifications by a key.
+    Sort,
+    /// Keep only the first N notifications. Also available as `limit`.
+    Head,
+    /// Keep only the last N notifications.
+    Tail,
+    /// Flip the order of the notifications.
+    Reverse,
+    /// Keep notifications whose title or repository match a pattern.
+    Grep,
+    /// Print the notifications and pass them on unchanged.
+    Tee,
 }
 
 impl Adapter {
-    pub const fn all() -> [&'static str; 1] {
-        ["confirm"]
+    pub const fn all() -> [&'static str; 8] {
+        [
+            "confirm", "sort", "limit", "head", "tail", "reverse", "grep", "tee",
+        ]
     }
 }
 
@@ -65,6 +146,12 @@ impl TryFrom<&str> for Adapter {
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         match value {
             "confirm" => Ok(Self::Confirm),
+            "sort" => Ok(Self::Sort),
+            "limit" | "head" => Ok(Self::Head),
+            "tail" => Ok(Self::Tail),
+            "reverse" => Ok(Self::Reverse),
+            "grep" => Ok(Self::Grep),
+            "tee" => Ok(Self::Tee),
             _ => Err("not an adapter"),
         }
     }
@@ -77,11 +164,71 @@ pub enum Consumer {
     Open,
     Done,
     Count,
+    /// Print the logs of the failed jobs of CI builds.
+    Logs,
+    /// Accept repository invitations.
+    Accept,
+    /// Decline repository invitations.
+    Decline,
+    /// Ask dependabot to rebase its PRs.
+    Rebase,
+    /// List the downloadable files of releases.
+    Assets,
+    /// Show the changes since the previous release.
+    Compare,
+    /// Pick a link in the text of a notification to open.
+    Links,
+    /// Pick a code block in the text of a notification to copy.
+    Copy,
+    /// Stop receiving notifications for the threads.
+    Unsubscribe,
+    /// Hide the threads, or their repositories with `mute repo`, from
+    /// future syncs.
+    Mute,
+    /// Print the text and comments of a notification.
+    Show,
+    /// Show the text and comments of a notification in a full screen view.
+    View,
+    /// Open the repository pages of notifications.
+    Browse,
+    /// Print the notifications as JSON lines.
+    Json,
+    /// Write the notifications to a file, like `export csv triage.csv`.
+    Export,
+    /// Write a comment on an issue or pull request in `$EDITOR`.
+    Comment,
+    /// Open the local checkout of the repository, or a file in it, in the
+    /// editor.
+    Edit,
+    /// Copy the command that checks out a pull request locally.
+    Checkout,
 }
 
 impl Consumer {
-    pub const fn all() -> [&'static str; 3] {
-        ["open", "done", "count"]
+    pub const fn all() -> [&'static str; 21] {
+        [
+            "open",
+            "done",
+            "count",
+            "logs",
+            "accept",
+            "decline",
+            "rebase",
+            "assets",
+            "compare",
+            "links",
+            "copy",
+            "unsubscribe",
+            "mute",
+            "show",
+            "view",
+            "browse",
+            "json",
+            "export",
+            "comment",
+            "edit",
+            "checkout",
+        ]
     }
 }
 
@@ -93,6 +240,24 @@ impl TryFrom<&str> for Consumer {
             "open" => Ok(Self::Open),
             "done" => Ok(Self::Done),
             "count" => Ok(Self::Count),
+            "logs" => Ok(Self::Logs),
+            "accept" => Ok(Self::Accept),
+            "decline" => Ok(Self::Decline),
+            "rebase" => Ok(Self::Rebase),
+            "assets" => Ok(Self::Assets),
+            "compare" => Ok(Self::Compare),
+            "links" => Ok(Self::Links),
+            "copy" => Ok(Self::Copy),
+            "unsubscribe" => Ok(Self::Unsubscribe),
+            "mute" => Ok(Self::Mute),
+            "show" => Ok(Self::Show),
+            "view" => Ok(Self::View),
+            "browse" => Ok(Self::Browse),
+            "json" => Ok(Self::Json),
+            "export" => Ok(Self::Export),
+            "comment" => Ok(Self::Comment),
+            "edit" => Ok(Self::Edit),
+            "checkout" => Ok(Self::Checkout),
             _ => Err("not a consumer"),
         }
     }
@@ -109,6 +274,9 @@ pub struct ProducerWithArgs {
 #[derive(Debug, PartialEq)]
 pub struct ConsumerWithArgs {
     pub consumer: Consumer,
+    /// Options that change what the consumer does, like the grouping key
+    /// of `count`. These come before the indices.
+    pub opts: Vec<String>,
     pub args: Vec<usize>,
 }
 
@@ -122,11 +290,22 @@ pub struct AdapterWithArgs {
 pub struct ProducerExpr {
     pub producer: ProducerWithArgs,
     pub adapters: Vec<AdapterWithArgs>,
-    pub consumer: Option<Consumer>,
+    /// Indices for the consumer come from the pipeline, so `args` is
+    /// always empty here.
+    pub consumer: Option<ConsumerWithArgs>,
+    /// File the output is written to, from `> file`.
+    pub redirect: Option<String>,
 }
 
 #[derive(Debug, PartialEq)]
 pub enum Parsed {
+    /// `help`, optionally with the name to show help for.
+    Help(Option<String>),
+    /// `new issue <owner/name>`, with the labels to add to the issue.
+    NewIssue {
+        repo: String,
+        labels: Vec<String>,
+    },
     Command(Command),
     ProducerExpr(ProducerExpr),
     ConsumerWithArgs(ConsumerWithArgs),
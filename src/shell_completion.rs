@@ -0,0 +1,122 @@
+//! Completion scripts for the shells octerm is started from, printed by
+//! `octerm completions <shell>`. Pipelines given to `-c` are completed with
+//! the same names as the REPL.
+
+use crate::parser::types::{Adapter, Command, Consumer, Producer};
+
+pub const SHELLS: [&str; 3] = ["bash", "zsh", "fish"];
+
+/// The completion script for `shell`, if it is one of [`SHELLS`].
+pub fn script(shell: &str) -> Option<String> {
+    let names = names().join(" ");
+    let filters = Producer::list_filters().join(" ");
+    let shells = SHELLS.join(" ");
+    let script = match shell {
+        "bash" => format!(
+            r#"_octerm() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}"
+    local prev="${{COMP_WORDS[COMP_CWORD-1]}}"
+    case "$prev" in
+        --repo) return ;;
+        --fixture|--record) COMPREPLY=($(compgen -f -- "$cur")) ;;
+        --dump-graphql) COMPREPLY=($(compgen -d -- "$cur")) ;;
+        --only) COMPREPLY=($(compgen -W "{filters}" -- "$cur")) ;;
+        --color) COMPREPLY=($(compgen -W "always never auto" -- "$cur")) ;;
+        -c) COMPREPLY=($(compgen -W "{names}" -- "$cur")) ;;
+        run) COMPREPLY=($(compgen -f -- "$cur")) ;;
+        completions) COMPREPLY=($(compgen -W "{shells}" -- "$cur")) ;;
+        state) COMPREPLY=($(compgen -W "export import" -- "$cur")) ;;
+        export|import) COMPREPLY=($(compgen -f -- "$cur")) ;;
+        *) COMPREPLY=($(compgen -W "--repo --only --json --color --fixture --record --dump-graphql -c run completions state --help" -- "$cur")) ;;
+    esac
+}}
+
+complete -F _octerm octerm
+"#
+        ),
+        "zsh" => format!(
+            r#"#compdef octerm
+
+_arguments \
+    '*--repo[only sync notifications of a repository]:owner/name: ' \
+    '*--only[only sync notifications matching a filter]:filter:({filters})' \
+    '--json[print the results of list pipelines as JSON lines]' \
+    '--color=[when to color output]:when:(always never auto)' \
+    '(--record)--fixture[replay a fixture instead of calling GitHub]:file:_files' \
+    '(--fixture)--record[save what is fetched from GitHub to a fixture]:file:_files' \
+    '--dump-graphql[write GraphQL queries and responses to a directory]:directory:_directories' \
+    '-c[run a pipeline and exit]:pipeline:({names})' \
+    '(- *)'{{-h,--help}}'[print usage]' \
+    '1:command:(run completions state)' \
+    '2:argument:->argument' \
+    '3:file:_files'
+
+case $state in
+    argument)
+        case $words[CURRENT-1] in
+            run) _files ;;
+            completions) _values shell {shells} ;;
+            state) _values action export import ;;
+        esac
+        ;;
+esac
+"#
+        ),
+        "fish" => format!(
+            r#"complete -c octerm -f
+complete -c octerm -l repo -x -d 'Only sync notifications of a repository'
+complete -c octerm -l only -x -a '{filters}' -d 'Only sync notifications matching a filter'
+complete -c octerm -l json -d 'Print the results of list pipelines as JSON lines'
+complete -c octerm -l color -x -a 'always never auto' -d 'When to color output'
+complete -c octerm -l fixture -r -F -d 'Replay a fixture instead of calling GitHub'
+complete -c octerm -l record -r -F -d 'Save what is fetched from GitHub to a fixture'
+complete -c octerm -l dump-graphql -x -a '(__fish_complete_directories)' -d 'Write GraphQL queries and responses to a directory'
+complete -c octerm -s c -x -a '{names}' -d 'Run a pipeline and exit'
+complete -c octerm -s h -l help -d 'Print usage'
+complete -c octerm -n __fish_use_subcommand -a run -d 'Run a script and exit'
+complete -c octerm -n __fish_use_subcommand -a completions -d 'Print a shell completion script'
+complete -c octerm -n __fish_use_subcommand -a state -d 'Export or import mutes and drafts'
+complete -c octerm -n '__fish_seen_subcommand_from run' -F
+complete -c octerm -n '__fish_seen_subcommand_from completions' -x -a '{shells}'
+complete -c octerm -n '__fish_seen_subcommand_from state; and not __fish_seen_subcommand_from export import' -x -a 'export import'
+complete -c octerm -n '__fish_seen_subcommand_from export import' -F
+"#
+        ),
+        _ => return None,
+    };
+    Some(script)
+}
+
+/// Every name of the pipeline language, without duplicates.
+fn names() -> Vec<&'static str> {
+    let mut names: Vec<_> = ["help", "new"]
+        .into_iter()
+        .chain(Command::all())
+        .chain(Producer::all())
+        .chain(Adapter::all())
+        .chain(Consumer::all())
+        .collect();
+    names.sort_unstable();
+    names.dedup();
+    names
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_script() {
+        for shell in SHELLS {
+            let script = script(shell).unwrap();
+            assert!(script.contains("only"), "{shell}");
+            assert!(script.contains(" releases "), "{shell}");
+            assert!(script.contains(" merged"), "{shell}");
+            assert!(script.contains("export import"), "{shell}");
+        }
+        assert!(script("bash")
+            .unwrap()
+            .contains("complete -F _octerm octerm"));
+        assert!(script("powershell").is_none());
+    }
+}
@@ -0,0 +1,40 @@
+//! Snapshot tests of rendered output. Output is compared without colors
+//! against the text files in `src/snapshots`, which are rewritten instead
+//! when `UPDATE_SNAPSHOTS` is set:
+//!
+//! ```text
+//! UPDATE_SNAPSHOTS=1 cargo test
+//! ```
+
+use std::path::PathBuf;
+
+/// Rendered output as it would look on a terminal without colors.
+pub fn render_plain(ansi: &str) -> String {
+    crate::util::strip_ansi(ansi)
+}
+
+/// Check that `output` matches the snapshot `name`, ignoring colors.
+#[track_caller]
+pub fn assert_snapshot(name: &str, output: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("src")
+        .join("snapshots")
+        .join(format!("{name}.txt"));
+    let output = render_plain(output);
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, output).unwrap();
+        return;
+    }
+    let snapshot = std::fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "no snapshot at {}, run with UPDATE_SNAPSHOTS=1 to create it",
+            path.display()
+        )
+    });
+    assert!(
+        snapshot == output,
+        "snapshot `{name}` changed, run with UPDATE_SNAPSHOTS=1 to accept\n\
+         --- expected\n{snapshot}\n--- got\n{output}"
+    );
+}
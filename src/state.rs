@@ -0,0 +1,114 @@
+//! All local state (mute rules and drafts) in a single portable file, so
+//! that the same triage setup can be carried to another machine with
+//! `octerm state export <file>` and `octerm state import <file>`.
+
+use std::{collections::BTreeMap, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    drafts::Drafts,
+    error::{Error, Result},
+    mutes::MuteRules,
+};
+
+#[derive(Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct State {
+    pub mutes: MuteRules,
+    /// Draft text by thread, see [`Drafts`].
+    pub drafts: BTreeMap<String, String>,
+}
+
+impl State {
+    /// The state saved on this machine.
+    pub fn load() -> Result<Self> {
+        Ok(Self {
+            mutes: MuteRules::load()?,
+            drafts: Drafts::load()?.drafts,
+        })
+    }
+
+    /// Replace the state saved on this machine.
+    pub fn save(self) -> Result<()> {
+        self.mutes.save()?;
+        Drafts {
+            drafts: self.drafts,
+        }
+        .save()
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path).map_err(Error::StateRead)?;
+        toml::from_str(&contents).map_err(Error::StateParse)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let contents = toml::to_string(self).map_err(|err| {
+            Error::StateWrite(std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+        })?;
+        std::fs::write(path, contents).map_err(Error::StateWrite)
+    }
+
+    /// Add the rules and drafts of `other`. Drafts of the same thread are
+    /// replaced.
+    pub fn merge(&mut self, other: State) {
+        for repo in other.mutes.repos {
+            self.mutes.mute_repo(&repo);
+        }
+        for thread in other.mutes.threads {
+            self.mutes.mute_thread(thread);
+        }
+        self.drafts.extend(other.drafts);
+    }
+}
+
+/// Write the state of this machine to `path`.
+pub fn export(path: &Path) -> Result<()> {
+    State::load()?.write(path)
+}
+
+/// Merge the state in `path` into the state of this machine.
+pub fn import(path: &Path) -> Result<()> {
+    let mut state = State::load()?;
+    state.merge(State::read(path)?);
+    state.save()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_merge() {
+        let mut state = State::default();
+        state.mutes.mute_repo("helix-editor/helix");
+        state.mutes.mute_thread(1);
+        state
+            .drafts
+            .insert("helix-editor/helix#1".to_string(), "old".to_string());
+
+        let mut other = State::default();
+        other.mutes.mute_repo("Helix-Editor/Helix");
+        other.mutes.mute_repo("sudormrfbin/octerm");
+        other.mutes.mute_thread(1);
+        other.mutes.mute_thread(2);
+        other
+            .drafts
+            .insert("helix-editor/helix#1".to_string(), "new".to_string());
+
+        let path = std::env::temp_dir().join(format!("octerm-state-{}.toml", std::process::id()));
+        other.write(&path).unwrap();
+        let other = State::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        state.merge(other);
+        assert_eq!(
+            state.mutes.repos,
+            ["helix-editor/helix", "sudormrfbin/octerm"]
+        );
+        assert_eq!(state.mutes.threads, [1, 2]);
+        assert_eq!(state.drafts["helix-editor/helix#1"], "new");
+        assert!(State::read(&path).is_err());
+    }
+}
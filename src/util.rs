@@ -1,7 +1,14 @@
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use chrono::{DateTime, Local, TimeZone, Utc};
+
 use crate::{
+    config::Timezone,
     error::{Error, Result},
     github::{
-        DiscussionState, IssueClosedReason, IssueState, NotificationTarget, PullRequestState,
+        events::DateTimeUtc, CiConclusion, DiscussionState, IssueClosedReason, IssueState,
+        NotificationTarget, PullRequestState,
     },
 };
 
@@ -39,13 +46,24 @@ pub fn notif_target_color(target: &NotificationTarget) -> NotifColor {
             PullRequestState::Merged => NotifColor::Purple,
             PullRequestState::Closed => NotifColor::Red,
         },
-        NotificationTarget::CiBuild => NotifColor::Red,
+        NotificationTarget::CiBuild(ref build) => match build.conclusion {
+            CiConclusion::Success => NotifColor::Green,
+            CiConclusion::Failure => NotifColor::Red,
+            CiConclusion::Cancelled | CiConclusion::Unknown => NotifColor::White,
+        },
         NotificationTarget::Release(_) => NotifColor::Blue,
+        NotificationTarget::Commit(_) => NotifColor::White,
+        NotificationTarget::Gist(_) => NotifColor::White,
+        NotificationTarget::SecurityAlert(_) => NotifColor::Red,
+        NotificationTarget::Invitation(ref invitation) => match invitation.expired {
+            true => NotifColor::White,
+            false => NotifColor::Yellow,
+        },
         NotificationTarget::Discussion(ref discussion) => match discussion.state {
             DiscussionState::Unanswered => NotifColor::Yellow,
             DiscussionState::Answered => NotifColor::Purple,
         },
-        NotificationTarget::Unknown => NotifColor::White,
+        NotificationTarget::Unknown | NotificationTarget::Pending => NotifColor::White,
     }
 }
 
@@ -53,6 +71,16 @@ pub fn open_url_in_browser(url: String) -> Result<()> {
     open::that(url.as_str()).map_err(|_| Error::BrowserNotAvailable)
 }
 
+/// Copy text to the system clipboard using the OSC 52 escape sequence, which
+/// is supported by most terminals and also works over ssh.
+pub fn copy_to_clipboard(text: &str) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut stdout = std::io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", base64::encode(text))?;
+    stdout.flush()
+}
+
 /// Utility trait for writing value.boxed() instead of Box::new(value).
 pub trait Boxed {
     fn boxed(self) -> Box<Self>;
@@ -63,3 +91,262 @@ impl<T> Boxed for T {
         Box::new(self)
     }
 }
+
+static RELATIVE_DATES: AtomicBool = AtomicBool::new(true);
+
+/// Toggle between relative ("3 days ago") and absolute dates in
+/// [`format_date`]. Returns true if relative dates are now enabled.
+pub fn toggle_relative_dates() -> bool {
+    !RELATIVE_DATES.fetch_xor(true, Ordering::Relaxed)
+}
+
+/// Format a date for display, either relative to the current time or as an
+/// absolute date depending on the current toggle (see [`toggle_relative_dates`]).
+/// Absolute dates are formatted according to [`crate::config::DateConfig`].
+/// Shared by everything that renders a date so that the toggle applies
+/// uniformly.
+pub fn format_date<Tz>(date: &DateTime<Tz>) -> String
+where
+    Tz: TimeZone,
+    Tz::Offset: Display,
+{
+    if RELATIVE_DATES.load(Ordering::Relaxed) {
+        return relative_date(date.with_timezone(&Utc), Utc::now());
+    }
+    let config = &crate::config::get().dates;
+    let format = config.strftime();
+    match config.timezone {
+        Timezone::Local => date.with_timezone(&Local).format(&format).to_string(),
+        Timezone::Utc => date.with_timezone(&Utc).format(&format).to_string(),
+    }
+}
+
+/// Human readable difference between `date` and `now`, eg. "3 days ago".
+fn relative_date(date: DateTimeUtc, now: DateTimeUtc) -> String {
+    let secs = (now - date).num_seconds();
+    let (n, unit) = match secs {
+        s if s < 60 => return "just now".to_string(),
+        s if s < 60 * 60 => (s / 60, "minute"),
+        s if s < 60 * 60 * 24 => (s / (60 * 60), "hour"),
+        s if s < 60 * 60 * 24 * 30 => (s / (60 * 60 * 24), "day"),
+        s if s < 60 * 60 * 24 * 365 => (s / (60 * 60 * 24 * 30), "month"),
+        s => (s / (60 * 60 * 24 * 365), "year"),
+    };
+    let plural = if n == 1 { "" } else { "s" };
+    format!("{n} {unit}{plural} ago")
+}
+
+/// Compact duration of a countdown, eg. "12m" or "1h 5m". Past times are
+/// "0s".
+pub fn format_countdown(secs: i64) -> String {
+    let secs = secs.max(0);
+    match (secs / 3600, secs % 3600 / 60) {
+        (0, 0) => format!("{secs}s"),
+        (0, m) => format!("{m}m"),
+        (h, 0) => format!("{h}h"),
+        (h, m) => format!("{h}h {m}m"),
+    }
+}
+
+/// Remove ANSI escape sequences (colors, styles) from a string.
+pub fn strip_ansi(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch == '\x1b' && chars.peek() == Some(&'[') {
+            // CSI sequences end with a byte in the range @ to ~.
+            chars.next();
+            for ch in chars.by_ref() {
+                if ('@'..='~').contains(&ch) {
+                    break;
+                }
+            }
+        } else {
+            plain.push(ch);
+        }
+    }
+    plain
+}
+
+/// When output is colored, from the `--color` argument.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorChoice {
+    Always,
+    Never,
+    /// Only when stdout is a terminal and `NO_COLOR` is not set.
+    Auto,
+}
+
+impl ColorChoice {
+    pub fn parse(choice: &str) -> Option<Self> {
+        match choice {
+            "always" => Some(Self::Always),
+            "never" => Some(Self::Never),
+            "auto" => Some(Self::Auto),
+            _ => None,
+        }
+    }
+
+    /// Whether to color output, given the value of `NO_COLOR` and whether
+    /// stdout is a terminal.
+    fn enabled(self, no_color: Option<&std::ffi::OsStr>, is_tty: bool) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Auto => is_tty && no_color.is_none_or(|v| v.is_empty()),
+        }
+    }
+}
+
+static COLORS: AtomicBool = AtomicBool::new(true);
+
+/// Enable or disable colors for [`colors_enabled`] and [`maybe_strip_ansi`].
+pub fn set_color_choice(choice: ColorChoice) {
+    use crossterm::tty::IsTty;
+
+    let no_color = std::env::var_os("NO_COLOR");
+    let enabled = choice.enabled(no_color.as_deref(), std::io::stdout().is_tty());
+    COLORS.store(enabled, Ordering::Relaxed);
+}
+
+pub fn colors_enabled() -> bool {
+    COLORS.load(Ordering::Relaxed)
+}
+
+/// `text` without ANSI escape sequences if colors are disabled.
+pub fn maybe_strip_ansi(text: String) -> String {
+    match colors_enabled() {
+        true => text,
+        false => strip_ansi(&text),
+    }
+}
+
+/// Quote a CSV field if it contains a separator, quote or newline.
+pub fn csv_field(field: &str) -> std::borrow::Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\"")).into()
+    } else {
+        field.into()
+    }
+}
+
+/// Format a size in bytes with binary units, like `1.5 MiB`.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = UNITS[0];
+    for next in &UNITS[1..] {
+        if size < 1024.0 {
+            break;
+        }
+        size /= 1024.0;
+        unit = next;
+    }
+    format!("{size:.1} {unit}")
+}
+
+/// The last `count` lines of a workflow job log, without the timestamp that
+/// GitHub prefixes every line with.
+pub fn log_tail(log: &str, count: usize) -> Vec<&str> {
+    let lines: Vec<&str> = log
+        .trim_start_matches('\u{feff}')
+        .lines()
+        .map(strip_log_timestamp)
+        .collect();
+    lines[lines.len().saturating_sub(count)..].to_vec()
+}
+
+fn strip_log_timestamp(line: &str) -> &str {
+    match line.split_once(' ') {
+        Some((timestamp, rest)) if DateTime::parse_from_rfc3339(timestamp).is_ok() => rest,
+        _ => line,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_relative_date() {
+        let now = Utc::now();
+        let ago = |d: chrono::Duration| relative_date(now - d, now);
+
+        assert_eq!(ago(chrono::Duration::seconds(10)), "just now");
+        assert_eq!(ago(chrono::Duration::seconds(-10)), "just now");
+        assert_eq!(ago(chrono::Duration::minutes(1)), "1 minute ago");
+        assert_eq!(ago(chrono::Duration::minutes(45)), "45 minutes ago");
+        assert_eq!(ago(chrono::Duration::hours(5)), "5 hours ago");
+        assert_eq!(ago(chrono::Duration::days(3)), "3 days ago");
+        assert_eq!(ago(chrono::Duration::days(65)), "2 months ago");
+        assert_eq!(ago(chrono::Duration::days(800)), "2 years ago");
+    }
+
+    #[test]
+    fn test_log_tail() {
+        let log = "\u{feff}2022-10-01T10:00:00.1234567Z ##[group]Run cargo test\n\
+                   2022-10-01T10:00:01.1234567Z test foo ... FAILED\n\
+                   2022-10-01T10:00:02.1234567Z ##[error]Process completed with exit code 101.";
+        assert_eq!(
+            log_tail(log, 2),
+            [
+                "test foo ... FAILED",
+                "##[error]Process completed with exit code 101."
+            ]
+        );
+        assert_eq!(log_tail(log, 10).len(), 3);
+        assert_eq!(log_tail(log, 10)[0], "##[group]Run cargo test");
+        assert_eq!(log_tail("no timestamp", 1), ["no timestamp"]);
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0 MiB");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    }
+
+    #[test]
+    fn test_format_countdown() {
+        assert_eq!(format_countdown(-30), "0s");
+        assert_eq!(format_countdown(45), "45s");
+        assert_eq!(format_countdown(12 * 60 + 5), "12m");
+        assert_eq!(format_countdown(3600), "1h");
+        assert_eq!(format_countdown(3900), "1h 5m");
+    }
+
+    #[test]
+    fn test_strip_ansi() {
+        use crossterm::style::Stylize;
+
+        let colored = format!("{} #{}", "helix".blue().bold(), "12".dark_grey());
+        assert_eq!(strip_ansi(&colored), "helix #12");
+        assert_eq!(strip_ansi("plain"), "plain");
+    }
+
+    #[test]
+    fn test_color_choice() {
+        let auto = ColorChoice::parse("auto").unwrap();
+        assert!(auto.enabled(None, true));
+        assert!(auto.enabled(Some("".as_ref()), true));
+        assert!(!auto.enabled(Some("1".as_ref()), true));
+        assert!(!auto.enabled(None, false));
+        assert!(ColorChoice::Always.enabled(Some("1".as_ref()), false));
+        assert!(!ColorChoice::Never.enabled(None, true));
+        assert_eq!(ColorChoice::parse("sometimes"), None);
+    }
+
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(csv_field("helix-editor/helix"), "helix-editor/helix");
+        assert_eq!(csv_field("fix: a, b"), "\"fix: a, b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+}